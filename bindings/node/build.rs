@@ -0,0 +1,4 @@
+fn main() {
+    // Links Node.js addons, e.g. allows undefined N-API symbols on macOS
+    napi_build::setup();
+}
@@ -0,0 +1,172 @@
+// napi only registers the exports outside of test builds
+#![cfg_attr(test, allow(dead_code))]
+
+use std::{collections::HashMap, path::PathBuf, str::FromStr};
+
+use napi::{bindgen_prelude::AsyncTask, Env, Status, Task};
+use napi_derive::napi;
+use tinted_builder::Base16Scheme;
+
+use tinted_scheme_extractor::{
+    CropRegion, Error, ExtractionOptions, SchemeParams, SchemeSystem, SchemeVariant,
+};
+
+/// Format an error followed by each of its sources
+fn error_chain(err: &Error) -> String {
+    let mut message = err.to_string();
+    let mut source = std::error::Error::source(err);
+
+    while let Some(err) = source {
+        message.push_str(&format!(": {}", err));
+        source = err.source();
+    }
+
+    message
+}
+
+/// JavaScript view of `CropRegion`
+#[napi(object, js_name = "CropRegion")]
+pub struct JsCropRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// JavaScript view of `ExtractionOptions`
+#[napi(object, js_name = "ExtractionOptions")]
+pub struct JsExtractionOptions {
+    pub crop: Option<JsCropRegion>,
+    pub center_weight: Option<f64>,
+    pub sample_every: Option<u32>,
+    pub max_dimension: Option<u32>,
+}
+
+/// JavaScript view of `SchemeParams`. `system` defaults to `base16` and
+/// `variant` to `dark`
+#[napi(object, js_name = "SchemeParams")]
+pub struct JsSchemeParams {
+    pub image_path: String,
+    pub name: String,
+    pub slug: String,
+    pub author: String,
+    pub system: Option<String>,
+    pub variant: Option<String>,
+    pub description: Option<String>,
+    pub options: Option<JsExtractionOptions>,
+}
+
+/// The generated scheme, with a `palette` of `#rrggbb` colors by slot
+#[napi(object, js_name = "Scheme")]
+pub struct JsScheme {
+    pub system: String,
+    pub variant: String,
+    pub name: String,
+    pub slug: String,
+    pub author: String,
+    pub description: Option<String>,
+    pub palette: HashMap<String, String>,
+}
+
+impl From<JsExtractionOptions> for ExtractionOptions {
+    fn from(options: JsExtractionOptions) -> Self {
+        ExtractionOptions {
+            crop: options.crop.map(|crop| CropRegion {
+                x: crop.x,
+                y: crop.y,
+                width: crop.width,
+                height: crop.height,
+            }),
+            center_weight: options.center_weight.unwrap_or_default() as f32,
+            sample_every: options.sample_every.unwrap_or_default() as usize,
+            max_dimension: options.max_dimension,
+            ..ExtractionOptions::default()
+        }
+    }
+}
+
+impl From<Base16Scheme> for JsScheme {
+    fn from(scheme: Base16Scheme) -> Self {
+        JsScheme {
+            system: scheme.system.to_string(),
+            variant: scheme.variant.to_string(),
+            palette: scheme
+                .palette
+                .iter()
+                .map(|(slot, color)| {
+                    let (red, green, blue) = color.rgb;
+
+                    (
+                        slot.clone(),
+                        format!("#{:02x}{:02x}{:02x}", red, green, blue),
+                    )
+                })
+                .collect(),
+            name: scheme.name,
+            slug: scheme.slug,
+            author: scheme.author,
+            description: scheme.description,
+        }
+    }
+}
+
+impl TryFrom<JsSchemeParams> for SchemeParams {
+    type Error = Error;
+
+    fn try_from(params: JsSchemeParams) -> Result<Self, Error> {
+        let system = params.system.as_deref().unwrap_or("base16");
+        let variant = params.variant.as_deref().unwrap_or("dark");
+
+        Ok(SchemeParams {
+            image_path: PathBuf::from(params.image_path),
+            author: params.author,
+            description: params.description,
+            name: params.name,
+            slug: params.slug,
+            system: SchemeSystem::from_str(system).map_err(|_| Error::UnsupportedSchemeSystem {
+                system: system.to_string(),
+            })?,
+            variant: SchemeVariant::from_str(variant).map_err(|_| {
+                Error::UnsupportedSchemeVariant {
+                    variant: variant.to_string(),
+                }
+            })?,
+            verbose: false,
+            options: params.options.map(Into::into).unwrap_or_default(),
+        })
+    }
+}
+
+/// Extracts a scheme on the libuv thread pool
+pub struct ExtractTask {
+    params: Option<JsSchemeParams>,
+}
+
+impl Task for ExtractTask {
+    type Output = Base16Scheme;
+    type JsValue = JsScheme;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        let params = self
+            .params
+            .take()
+            .ok_or_else(|| napi::Error::new(Status::GenericFailure, "Task already ran"))?;
+
+        SchemeParams::try_from(params)
+            .and_then(tinted_scheme_extractor::create_scheme_from_image)
+            .map_err(|err| napi::Error::new(Status::GenericFailure, error_chain(&err)))
+    }
+
+    fn resolve(&mut self, _env: Env, scheme: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(scheme.into())
+    }
+}
+
+/// Create a scheme from an image file off the main thread, resolving to the
+/// scheme or rejecting with the error and its sources
+#[napi(js_name = "createSchemeFromImage", ts_return_type = "Promise<Scheme>")]
+pub fn create_scheme_from_image_js(params: JsSchemeParams) -> AsyncTask<ExtractTask> {
+    AsyncTask::new(ExtractTask {
+        params: Some(params),
+    })
+}
@@ -0,0 +1,228 @@
+use std::fmt;
+
+use palette::Srgb;
+use tinted_builder::Base16Scheme;
+
+use crate::{
+    adjust::scheme_slots,
+    utils::{contrast_ratio, with_min_contrast},
+    Error,
+};
+
+/// The WCAG 2 level a contrast ratio reaches for text
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum WcagLevel {
+    /// Below 3:1
+    Fail,
+    /// At least 3:1, enough for large text and UI components
+    AaLarge,
+    /// At least 4.5:1
+    Aa,
+    /// At least 7:1
+    Aaa,
+}
+
+impl WcagLevel {
+    /// Get the level a contrast ratio reaches
+    ///
+    /// # Arguments
+    /// * `ratio` - The contrast ratio, from 1.0 to 21.0
+    pub fn from_ratio(ratio: f32) -> Self {
+        match ratio {
+            ratio if ratio >= 7.0 => WcagLevel::Aaa,
+            ratio if ratio >= 4.5 => WcagLevel::Aa,
+            ratio if ratio >= 3.0 => WcagLevel::AaLarge,
+            _ => WcagLevel::Fail,
+        }
+    }
+}
+
+impl fmt::Display for WcagLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            WcagLevel::Fail => "fail",
+            WcagLevel::AaLarge => "AA large",
+            WcagLevel::Aa => "AA",
+            WcagLevel::Aaa => "AAA",
+        })
+    }
+}
+
+/// How readable one slot is
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SlotAccessibility {
+    pub slot: String,
+    /// The contrast ratio against base00, the background
+    pub background_contrast: f32,
+    /// The contrast ratio against base05, the foreground
+    pub foreground_contrast: f32,
+    /// The level reached against base00
+    pub level: WcagLevel,
+    /// The level this slot should reach against base00: AA large for
+    /// base03, which templates use for comments, and AA for the other text
+    /// slots. Background slots, base01 and base02, have none
+    pub required: Option<WcagLevel>,
+    /// The nearest color reaching the required level, as `rrggbb`, when
+    /// the slot doesn't
+    pub suggestion: Option<String>,
+}
+
+/// The contrast of every slot of a scheme against its background and
+/// foreground
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccessibilityReport {
+    pub slots: Vec<SlotAccessibility>,
+}
+
+impl AccessibilityReport {
+    /// Whether every slot reaches its required level, e.g. to fail a CI
+    /// pipeline otherwise
+    pub fn passes(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// The slots that don't reach their required level
+    pub fn failures(&self) -> impl Iterator<Item = &SlotAccessibility> {
+        self.slots
+            .iter()
+            .filter(|slot| slot.required.is_some_and(|required| slot.level < required))
+    }
+}
+
+impl fmt::Display for AccessibilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<8} {:>9} {:>9} {:<8} {:<8} suggestion",
+            "slot", "vs base00", "vs base05", "level", "required"
+        )?;
+
+        for slot in &self.slots {
+            writeln!(
+                f,
+                "{:<8} {:>9.2} {:>9.2} {:<8} {:<8} {}",
+                slot.slot,
+                slot.background_contrast,
+                slot.foreground_contrast,
+                slot.level.to_string(),
+                slot.required
+                    .map(|required| required.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                slot.suggestion
+                    .as_deref()
+                    .map(|hex| format!("#{}", hex))
+                    .unwrap_or_default()
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Check the contrast of every slot of a scheme against base00 and base05,
+/// suggesting colors for the slots that are too hard to read
+///
+/// # Arguments
+/// * `scheme` - A reference to the scheme to check
+pub fn accessibility_report(scheme: &Base16Scheme) -> Result<AccessibilityReport, Error> {
+    let color = |slot: &str| -> Result<Srgb<u8>, Error> {
+        let (red, green, blue) = scheme
+            .palette
+            .get(slot)
+            .ok_or_else(|| Error::UnknownSlot {
+                slot: slot.to_string(),
+            })?
+            .rgb;
+
+        Ok(Srgb::new(red, green, blue))
+    };
+    let background = color("base00")?;
+    let foreground = color("base05")?;
+    let slots = scheme_slots(&scheme.system)
+        .iter()
+        .filter(|slot| scheme.palette.contains_key(*slot))
+        .map(|slot| {
+            let color = color(slot)?;
+            let background_contrast = contrast_ratio(color.into_format(), background.into_format());
+            let level = WcagLevel::from_ratio(background_contrast);
+            let required = match slot.as_str() {
+                "base00" | "base01" | "base02" => None,
+                "base03" => Some(WcagLevel::AaLarge),
+                _ => Some(WcagLevel::Aa),
+            };
+            let suggestion = required
+                .filter(|required| level < *required)
+                .map(|required| {
+                    let min_contrast = match required {
+                        WcagLevel::AaLarge => 3.0,
+                        _ => 4.5,
+                    };
+                    let rgb = with_min_contrast(color, background, min_contrast);
+
+                    format!("{:02x}{:02x}{:02x}", rgb.red, rgb.green, rgb.blue)
+                });
+
+            Ok(SlotAccessibility {
+                slot: slot.clone(),
+                background_contrast,
+                foreground_contrast: contrast_ratio(color.into_format(), foreground.into_format()),
+                level,
+                required,
+                suggestion,
+            })
+        })
+        .collect::<Result<_, Error>>()?;
+
+    Ok(AccessibilityReport { slots })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::scheme_color;
+    use std::collections::HashMap;
+    use tinted_builder::{SchemeSystem, SchemeVariant};
+
+    #[test]
+    fn test_accessibility_report() {
+        let palette: HashMap<_, _> = [
+            ("base00", "000000"),
+            ("base03", "666666"),
+            ("base05", "FFFFFF"),
+            ("base08", "330000"),
+        ]
+        .iter()
+        .map(|(slot, hex)| (slot.to_string(), scheme_color(hex.to_string()).unwrap()))
+        .collect();
+        let scheme = Base16Scheme {
+            author: String::new(),
+            description: None,
+            name: "Report".to_string(),
+            slug: "report".to_string(),
+            system: SchemeSystem::Base16,
+            variant: SchemeVariant::Dark,
+            palette,
+        };
+        let report = accessibility_report(&scheme).unwrap();
+        let failures: Vec<&str> = report.failures().map(|slot| slot.slot.as_str()).collect();
+
+        assert_eq!(report.slots.len(), 4);
+        assert_eq!(report.slots[2].level, WcagLevel::Aaa);
+        assert_eq!(failures, vec!["base08"]);
+        assert!(!report.passes());
+
+        let suggestion = report.slots[3].suggestion.as_deref().unwrap();
+        let rgb = Srgb::new(
+            u8::from_str_radix(&suggestion[0..2], 16).unwrap(),
+            u8::from_str_radix(&suggestion[2..4], 16).unwrap(),
+            u8::from_str_radix(&suggestion[4..6], 16).unwrap(),
+        );
+
+        assert!(contrast_ratio(rgb.into_format(), Srgb::new(0.0, 0.0, 0.0)) >= 4.5);
+        assert!(report.to_string().starts_with("slot "));
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use palette::{FromColor, Hsl, IntoColor, Srgb};
+use tinted_builder::{Base16Scheme, Color as SchemeColor, SchemeSystem};
+
+use crate::{utils::scheme_color_from_srgb, Error};
+
+/// A relative hue, saturation and lightness change for a scheme color
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct HslAdjustment {
+    /// Hue rotation in degrees
+    pub hue: f32,
+    /// Saturation change between -1.0 and 1.0
+    pub saturation: f32,
+    /// Lightness change between -1.0 and 1.0
+    pub lightness: f32,
+}
+
+/// Get the palette slot names used by a scheme system, in order
+///
+/// # Arguments
+/// * `system` - A reference to a SchemeSystem
+pub fn scheme_slots(system: &SchemeSystem) -> Vec<String> {
+    let count = match system {
+        SchemeSystem::Base24 => 24,
+        _ => 16,
+    };
+
+    (0..count)
+        .map(|index| format!("base{:02X}", index))
+        .collect()
+}
+
+/// Nudge a single scheme color by a hue, saturation and lightness amount
+///
+/// # Arguments
+/// * `scheme` - A mutable reference to the Base16Scheme to update
+/// * `slot` - The palette slot to update, e.g. `base08`
+/// * `adjustment` - A HslAdjustment to apply to the slot
+pub fn adjust_scheme_color(
+    scheme: &mut Base16Scheme,
+    slot: &str,
+    adjustment: HslAdjustment,
+) -> Result<(), Error> {
+    adjust_palette_color(&mut scheme.palette, slot, adjustment)
+}
+
+/// Nudge a single palette color, as `adjust_scheme_color` does
+pub(crate) fn adjust_palette_color(
+    palette: &mut HashMap<String, SchemeColor>,
+    slot: &str,
+    adjustment: HslAdjustment,
+) -> Result<(), Error> {
+    let color = palette.get_mut(slot).ok_or_else(|| Error::UnknownSlot {
+        slot: slot.to_string(),
+    })?;
+    let (red, green, blue) = color.rgb;
+    let hsl: Hsl = Hsl::from_color(Srgb::new(red, green, blue).into_format::<f32>());
+    let hsl = Hsl::new(
+        hsl.hue + adjustment.hue,
+        (hsl.saturation + adjustment.saturation).clamp(0.0, 1.0),
+        (hsl.lightness + adjustment.lightness).clamp(0.0, 1.0),
+    );
+    let rgb: Srgb = hsl.into_color();
+    let rgb: Srgb<u8> = rgb.into_format();
+
+    *color = scheme_color_from_srgb(rgb);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tinted_builder::SchemeVariant;
+
+    #[test]
+    fn test_scheme_slots() {
+        let slots = scheme_slots(&SchemeSystem::Base24);
+
+        assert_eq!(slots.len(), 24);
+        assert_eq!(slots[10], "base0A");
+        assert_eq!(slots[23], "base17");
+    }
+
+    #[test]
+    fn test_adjust_scheme_color() {
+        let mut scheme = Base16Scheme {
+            author: "Author".to_string(),
+            description: None,
+            name: "Name".to_string(),
+            slug: "name".to_string(),
+            system: SchemeSystem::Base16,
+            variant: SchemeVariant::Dark,
+            palette: HashMap::from([(
+                "base08".to_string(),
+                SchemeColor::new("FF0000".to_string()).unwrap(),
+            )]),
+        };
+
+        adjust_scheme_color(
+            &mut scheme,
+            "base08",
+            HslAdjustment {
+                hue: 120.0,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(scheme.palette["base08"].rgb, (0, 255, 0));
+        assert!(adjust_scheme_color(&mut scheme, "base99", HslAdjustment::default()).is_err());
+    }
+}
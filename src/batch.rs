@@ -0,0 +1,243 @@
+use std::{
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    slice,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use tinted_builder::Base16Scheme;
+
+use crate::{
+    create_scheme_from_image, extract_scheme_from_loaded_images, phash::perceptual_hash,
+    utils::load_image_frames, Error, SchemeParams,
+};
+
+/// Create one scheme per image, processing several images at once
+///
+/// Images are processed on a bounded pool of threads, one per available
+/// CPU, or on the current thread when only one is available. A failing
+/// image doesn't stop the others; the results are returned in the same
+/// order as `image_paths`.
+///
+/// # Arguments
+/// * `image_paths` - The images to create schemes from
+/// * `params_fn` - Returns the SchemeParams for an image path, e.g. to derive
+///   the scheme name from the file name. `image_path` is set to the path
+pub fn create_schemes_from_images<F>(
+    image_paths: &[PathBuf],
+    params_fn: F,
+) -> Vec<Result<Base16Scheme, Error>>
+where
+    F: Fn(&Path) -> SchemeParams + Sync,
+{
+    parallel_map(image_paths, |image_path| {
+        create_scheme_from_image(SchemeParams {
+            image_path: image_path.clone(),
+            ..params_fn(image_path)
+        })
+    })
+}
+
+/// An image left out of a batch because it looks like an earlier one
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Duplicate {
+    /// The image that was skipped
+    pub path: PathBuf,
+    /// The earlier image it looks like, whose scheme it shares
+    pub original: PathBuf,
+    /// How many bits their perceptual hashes differ by, from 0 to 64
+    pub distance: u32,
+}
+
+/// The schemes of a batch with near-duplicate images skipped
+#[derive(Debug)]
+pub struct DeduplicatedBatch {
+    /// The scheme of every image that was kept, in the order of the paths
+    pub schemes: Vec<(PathBuf, Result<Base16Scheme, Error>)>,
+    /// The images that were skipped, e.g. to report them
+    pub duplicates: Vec<Duplicate>,
+}
+
+/// Like `create_schemes_from_images`, but skip images whose perceptual
+/// hash is within `max_distance` bits of an earlier image's, e.g.
+/// re-encoded or resized copies in a wallpaper collection
+///
+/// Every image is decoded once and extracted while it's hashed, and the
+/// schemes of the duplicates are then dropped. Images that can't be decoded
+/// to hash them are kept, so their error is reported with the schemes.
+///
+/// # Arguments
+/// * `image_paths` - The images to create schemes from
+/// * `max_distance` - How many of the 64 hash bits may differ for images to
+///   count as duplicates, e.g. 8. 0 only skips images that hash the same
+/// * `params_fn` - Returns the SchemeParams for an image path. `image_path`
+///   is set to the path
+pub fn create_deduplicated_schemes_from_images<F>(
+    image_paths: &[PathBuf],
+    max_distance: u32,
+    params_fn: F,
+) -> DeduplicatedBatch
+where
+    F: Fn(&Path) -> SchemeParams + Sync,
+{
+    // Every image is decoded once, for its hash and its scheme
+    let results = parallel_map(image_paths, |image_path| {
+        let params = SchemeParams {
+            image_path: image_path.clone(),
+            ..params_fn(image_path)
+        };
+        let frames = load_image_frames(image_path, &params.options);
+        let hash = frames
+            .as_ref()
+            .ok()
+            .and_then(|frames| frames.first().map(perceptual_hash));
+        let scheme = params.validate().and_then(|_| {
+            extract_scheme_from_loaded_images(slice::from_ref(image_path), params, || frames)
+                .map(|extraction| extraction.scheme)
+        });
+
+        (hash, scheme)
+    });
+    let mut kept: Vec<(&PathBuf, Option<u64>)> = Vec::new();
+    let mut schemes = Vec::new();
+    let mut duplicates = Vec::new();
+
+    for (path, (hash, scheme)) in image_paths.iter().zip(results) {
+        let original = hash.and_then(|hash| {
+            kept.iter()
+                .filter_map(|(kept_path, kept_hash)| {
+                    kept_hash.map(|kept_hash| (*kept_path, (hash ^ kept_hash).count_ones()))
+                })
+                .filter(|(_, distance)| *distance <= max_distance)
+                .min_by_key(|(_, distance)| *distance)
+        });
+
+        match original {
+            Some((original, distance)) => duplicates.push(Duplicate {
+                path: path.clone(),
+                original: original.clone(),
+                distance,
+            }),
+            None => {
+                kept.push((path, hash));
+                schemes.push((path.clone(), scheme));
+            }
+        }
+    }
+
+    DeduplicatedBatch {
+        schemes,
+        duplicates,
+    }
+}
+
+/// Map every item on a bounded pool of threads, one per available CPU, or
+/// on the current thread when only one is available, keeping the order
+fn parallel_map<T, R, F>(items: &[T], map: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let thread_count = thread::available_parallelism()
+        .map(NonZeroUsize::get)
+        .unwrap_or(1)
+        .min(items.len());
+    let next_index = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new(items.iter().map(|_| None).collect());
+
+    let worker = || loop {
+        let index = next_index.fetch_add(1, Ordering::Relaxed);
+        let Some(item) = items.get(index) else {
+            break;
+        };
+        let result = map(item);
+
+        results.lock().unwrap_or_else(|e| e.into_inner())[index] = Some(result);
+    };
+
+    // Without parallelism, e.g. on targets without threads, don't spawn any
+    if thread_count <= 1 {
+        worker();
+    } else {
+        thread::scope(|scope| {
+            for _ in 0..thread_count {
+                scope.spawn(worker);
+            }
+        });
+    }
+
+    results
+        .into_inner()
+        .unwrap_or_else(|e| e.into_inner())
+        .into_iter()
+        .map(|result| result.expect("every item is processed before the threads are joined"))
+        .collect()
+}
+
+#[cfg(all(test, feature = "png"))]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+    use std::{env, fs};
+    use tinted_builder::{SchemeSystem, SchemeVariant};
+
+    #[test]
+    fn test_create_deduplicated_schemes_from_images() {
+        let dir = env::temp_dir().join(format!(
+            "tinted-scheme-extractor-batch-{}",
+            std::process::id()
+        ));
+        let gradient = RgbImage::from_fn(64, 64, |x, y| Rgb([x as u8 * 4, y as u8 * 4, 128]));
+        let stripes = RgbImage::from_fn(64, 64, |x, _| {
+            if x % 16 < 8 {
+                Rgb([200, 40, 40])
+            } else {
+                Rgb([20, 20, 30])
+            }
+        });
+        let paths: Vec<PathBuf> = ["a.png", "b.png", "c.png", "missing.png"]
+            .iter()
+            .map(|name| dir.join(name))
+            .collect();
+
+        fs::create_dir_all(&dir).unwrap();
+        gradient.save(&paths[0]).unwrap();
+        gradient.save(&paths[1]).unwrap();
+        stripes.save(&paths[2]).unwrap();
+
+        let batch = create_deduplicated_schemes_from_images(&paths, 0, |_| SchemeParams {
+            image_path: PathBuf::new(),
+            author: "Author".to_string(),
+            description: None,
+            name: "Batch".to_string(),
+            slug: "batch".to_string(),
+            system: SchemeSystem::Base16,
+            variant: SchemeVariant::Dark,
+            verbose: false,
+            options: Default::default(),
+        });
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            batch.duplicates,
+            vec![Duplicate {
+                path: paths[1].clone(),
+                original: paths[0].clone(),
+                distance: 0,
+            }]
+        );
+
+        let kept: Vec<&PathBuf> = batch.schemes.iter().map(|(path, _)| path).collect();
+
+        assert_eq!(kept, vec![&paths[0], &paths[2], &paths[3]]);
+        assert!(batch.schemes[0].1.is_ok());
+        assert!(batch.schemes[1].1.is_ok());
+        assert!(batch.schemes[2].1.is_err());
+    }
+}
@@ -0,0 +1,272 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use image::DynamicImage;
+use palette::{FromColor, Lab, Lch, Srgb};
+use tinted_builder::Color as SchemeColor;
+
+use crate::{
+    extract_scheme_from_dynamic_image, finish_extraction,
+    gamut::to_srgb_within_gamut,
+    utils::{load_image_frames, scheme_color_from_srgb, separate_accents},
+    Error, Extraction, ExtractionOptions, SchemeParams,
+};
+
+/// Create one scheme from several images, each influencing it by its
+/// weight, e.g. 70% wallpaper and 30% logo so brand colors show without
+/// taking over. `params.image_path` is not read.
+///
+/// Every image is extracted on its own and the palettes are merged slot
+/// by slot: the grays are averaged in Lab, and the accents in LCh, where
+/// the hue is averaged on the color wheel, weighted by chroma too, so
+/// vivid accents aren't washed out by gray ones. The diagnostics are those
+/// of the heaviest image, except for `confidence` and `provenance`, which
+/// are left empty since blended colors don't come from any one image.
+///
+/// # Arguments
+/// * `images` - The image paths and their weights, which must be positive
+/// * `params` - The SchemeParams used for every image
+pub fn extract_scheme_from_weighted_images(
+    images: &[(PathBuf, f32)],
+    params: SchemeParams,
+) -> Result<Extraction, Error> {
+    params.validate()?;
+
+    // The frames of an animation share its weight
+    let images: Vec<(DynamicImage, f32)> = images
+        .iter()
+        .map(|(path, weight)| {
+            load_image_frames(path, &params.options).map(|frames| {
+                let weight = *weight / frames.len() as f32;
+
+                frames
+                    .into_iter()
+                    .map(move |frame| (frame, weight))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+
+    extract_scheme_from_weighted_dynamic_images(&images, params)
+}
+
+/// Like `extract_scheme_from_weighted_images`, but with images that have
+/// already been decoded
+pub fn extract_scheme_from_weighted_dynamic_images(
+    images: &[(DynamicImage, f32)],
+    params: SchemeParams,
+) -> Result<Extraction, Error> {
+    params.validate()?;
+
+    if images.is_empty() {
+        return Err(Error::NoImages);
+    }
+
+    if let Some((_, weight)) = images
+        .iter()
+        .find(|(_, weight)| !weight.is_finite() || *weight <= 0.0)
+    {
+        return Err(Error::InvalidWeight { weight: *weight });
+    }
+
+    // Reports and surfaces are only made for the blended palette
+    let image_params = SchemeParams {
+        options: ExtractionOptions {
+            report: None,
+            surfaces: false,
+            accessibility_report: false,
+            ..params.options.clone()
+        },
+        ..params.clone()
+    };
+    let extractions: Vec<(Extraction, f32)> = images
+        .iter()
+        .map(|(image, weight)| {
+            extract_scheme_from_dynamic_image(image, image_params.clone())
+                .map(|extraction| (extraction, *weight))
+        })
+        .collect::<Result<_, _>>()?;
+    let palette = blend_palettes(
+        &extractions
+            .iter()
+            .map(|(extraction, weight)| (&extraction.scheme.palette, *weight))
+            .collect::<Vec<_>>(),
+    )?;
+    let (
+        Extraction {
+            mut scheme,
+            mut diagnostics,
+            ..
+        },
+        _,
+    ) = extractions
+        .into_iter()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .expect("there is at least one image");
+
+    scheme.palette = palette;
+    diagnostics.confidence.clear();
+    diagnostics.provenance.clear();
+
+    if let Some(min_delta_e) = params.options.min_accent_delta_e_or_default() {
+        separate_accents(&mut scheme.palette, &scheme.system, min_delta_e)?;
+    }
+
+    // The report shows the image with the most weight
+    let image = params.options.report.and_then(|_| {
+        images
+            .iter()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(image, _)| image.to_rgba8())
+    });
+
+    finish_extraction(scheme, diagnostics, &params.options, image.as_ref())
+}
+
+/// Merge palettes slot by slot, weighting each palette's colors
+fn blend_palettes(
+    palettes: &[(&HashMap<String, SchemeColor>, f32)],
+) -> Result<HashMap<String, SchemeColor>, Error> {
+    let Some((first, _)) = palettes.first() else {
+        return Ok(HashMap::new());
+    };
+    let to_lab = |color: &SchemeColor| -> Lab {
+        let (red, green, blue) = color.rgb;
+
+        Lab::from_color(Srgb::new(red, green, blue).into_format::<f32>())
+    };
+    let mut blended = HashMap::new();
+
+    for slot in first.keys() {
+        let colors: Vec<(Lab, f32)> = palettes
+            .iter()
+            .filter_map(|(palette, weight)| palette.get(slot).map(|color| (to_lab(color), *weight)))
+            .collect();
+        let total: f32 = colors.iter().map(|(_, weight)| weight).sum();
+        let is_gray = matches!(
+            slot.as_str(),
+            "base00" | "base01" | "base02" | "base03" | "base04" | "base05" | "base06" | "base07"
+        );
+        let color = if is_gray {
+            colors
+                .iter()
+                .fold(Lab::new(0.0, 0.0, 0.0), |sum, (lab, weight)| {
+                    Lab::new(
+                        sum.l + lab.l * weight / total,
+                        sum.a + lab.a * weight / total,
+                        sum.b + lab.b * weight / total,
+                    )
+                })
+        } else {
+            blend_accent(&colors, total)
+        };
+        let rgb = to_srgb_within_gamut(color);
+
+        blended.insert(slot.clone(), scheme_color_from_srgb(rgb));
+    }
+
+    Ok(blended)
+}
+
+/// Average accents in LCh, with the hue averaged on the color wheel and
+/// weighted by chroma, falling back to the heaviest accent's hue when the
+/// hues cancel out
+fn blend_accent(colors: &[(Lab, f32)], total: f32) -> Lab {
+    let (lightness, chroma, a, b) = colors.iter().fold(
+        (0.0, 0.0, 0.0, 0.0),
+        |(lightness, chroma, a, b), (lab, weight)| {
+            let lch = Lch::from_color(*lab);
+            let share = weight / total;
+
+            (
+                lightness + lch.l * share,
+                chroma + lch.chroma * share,
+                a + lab.a * share,
+                b + lab.b * share,
+            )
+        },
+    );
+    let hue = if a.hypot(b) > f32::EPSILON {
+        b.atan2(a).to_degrees()
+    } else {
+        colors
+            .iter()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(lab, _)| Lch::from_color(*lab).hue.into_degrees())
+            .unwrap_or_default()
+    };
+
+    Lab::from_color(Lch::new(lightness, chroma, hue))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::scheme_color;
+    use image::{Rgba, RgbaImage};
+    use tinted_builder::{SchemeSystem, SchemeVariant};
+
+    fn palette(hexes: &[(&str, &str)]) -> HashMap<String, SchemeColor> {
+        hexes
+            .iter()
+            .map(|(slot, hex)| (slot.to_string(), scheme_color(hex.to_string()).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_blend_palettes() {
+        let wallpaper = palette(&[("base00", "000000"), ("base08", "CC3333")]);
+        let logo = palette(&[("base00", "FFFFFF"), ("base08", "3333CC")]);
+        let blended = blend_palettes(&[(&wallpaper, 0.7), (&logo, 0.3)]).unwrap();
+        let base00 = Lab::from_color(
+            Srgb::new(
+                blended["base00"].rgb.0,
+                blended["base00"].rgb.1,
+                blended["base00"].rgb.2,
+            )
+            .into_format::<f32>(),
+        );
+        let (red, _, blue) = blended["base08"].rgb;
+
+        assert!((base00.l - 30.0).abs() < 1.0);
+        // The accent leans towards the heavier red without turning gray
+        assert!(red > blue);
+        assert!(red - blue > 40);
+    }
+
+    #[test]
+    fn test_blend_diagnostics() {
+        let image =
+            |color: [u8; 4]| DynamicImage::ImageRgba8(RgbaImage::from_pixel(32, 32, Rgba(color)));
+        let params = SchemeParams {
+            image_path: PathBuf::new(),
+            author: "Author".to_string(),
+            description: None,
+            name: "Blend".to_string(),
+            slug: "blend".to_string(),
+            system: SchemeSystem::Base16,
+            variant: SchemeVariant::Dark,
+            verbose: false,
+            options: ExtractionOptions {
+                surfaces: true,
+                accessibility_report: true,
+                ..ExtractionOptions::default()
+            },
+        };
+        let extraction = extract_scheme_from_weighted_dynamic_images(
+            &[
+                (image([200, 40, 40, 255]), 0.7),
+                (image([40, 40, 200, 255]), 0.3),
+            ],
+            params,
+        )
+        .unwrap();
+
+        assert!(extraction.diagnostics.confidence.is_empty());
+        assert!(extraction.diagnostics.provenance.is_empty());
+        assert!(extraction.surfaces.is_some());
+        assert!(extraction.accessibility.is_some());
+    }
+}
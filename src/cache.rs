@@ -0,0 +1,398 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use image::DynamicImage;
+use palette::Srgb;
+use tinted_builder::{Base16Scheme, Color as SchemeColor, SchemeSystem, SchemeVariant};
+
+#[cfg(feature = "video")]
+use crate::VideoFrame;
+use crate::{
+    extract_scheme_from_dynamic_images, finish_extraction, metadata::scheme_description,
+    Base0FPolicy, ColorPass, CropRegion, Denoise, Diagnostics, Error, Extraction,
+    ExtractionOptions, FrameSelection, HslAdjustment, HueRange, InverseColors, SchemeParams,
+    SubjectBias, VariantProfile, WhiteBalance,
+};
+
+/// Get the directory extraction results are cached in
+/// This is `$XDG_CACHE_HOME/tinted-scheme-extractor`, falling back to
+/// `~/.cache` when `XDG_CACHE_HOME` isn't set
+pub fn extraction_cache_dir() -> Result<PathBuf, Error> {
+    let cache_dir = match env::var_os("XDG_CACHE_HOME").filter(|value| !value.is_empty()) {
+        Some(cache_dir) => PathBuf::from(cache_dir),
+        None => env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(".cache"))
+            .ok_or_else(|| Error::Cache {
+                message: "Unable to determine the home directory".to_string(),
+                source: None,
+            })?,
+    };
+
+    Ok(cache_dir.join(env!("CARGO_PKG_NAME")))
+}
+
+/// Bumped whenever the key or the entry format changes, so entries written
+/// in another format are never read
+const CACHE_FORMAT: u32 = 2;
+
+/// Everything besides the images that affects the extracted palette and
+/// diagnostics. Options that are applied again on a hit, e.g. `surfaces`
+/// or the description, are left out so they don't split the cache
+#[derive(serde::Serialize)]
+struct CacheKey<'a> {
+    format: u32,
+    version: &'static str,
+    system: &'a SchemeSystem,
+    variant: &'a SchemeVariant,
+    crop: &'a Option<CropRegion>,
+    center_weight: f32,
+    subject_bias: &'a Option<SubjectBias>,
+    skin_tone_weight: Option<f32>,
+    sample_every: usize,
+    max_dimension: Option<u32>,
+    denoise: &'a Option<Denoise>,
+    white_balance: &'a Option<WhiteBalance>,
+    equalize: bool,
+    superpixels: Option<u32>,
+    frames: &'a FrameSelection,
+    #[cfg(feature = "svg")]
+    svg_size: Option<u32>,
+    #[cfg(feature = "pdf")]
+    pdf_page: u16,
+    #[cfg(feature = "video")]
+    video_frame: &'a VideoFrame,
+    max_color_distance: f64,
+    inverse_colors: &'a InverseColors,
+    max_delta_e: f32,
+    profile: &'a Option<VariantProfile>,
+    light_ladder: &'a [ColorPass],
+    dark_ladder: &'a [ColorPass],
+    contrast_ramp: &'a Option<[f32; 7]>,
+    background: &'a Option<Srgb<u8>>,
+    gray_tint: Option<f32>,
+    min_accent_delta_e: Option<f32>,
+    min_accent_chroma: Option<f32>,
+    min_hue_entropy: Option<f32>,
+    duotone: bool,
+    raw_accents: bool,
+    base0f: &'a Base0FPolicy,
+    accent_hue_ranges: &'a Option<[Option<HueRange>; 8]>,
+    slot_adjustments: &'a BTreeMap<String, HslAdjustment>,
+}
+
+/// Hash the contents of the images together with the `CacheKey` of the
+/// params. The crate version is part of the key so cached results don't
+/// outlive changes to the extraction itself
+fn cache_key(image_paths: &[PathBuf], params: &SchemeParams) -> Result<String, Error> {
+    // Destructured without `..` so new options have to be sorted into the
+    // key or left out on purpose
+    let ExtractionOptions {
+        crop,
+        center_weight,
+        subject_bias,
+        skin_tone_weight,
+        sample_every,
+        max_dimension,
+        denoise,
+        white_balance,
+        equalize,
+        superpixels,
+        limits: _,
+        frames,
+        #[cfg(feature = "svg")]
+        svg_size,
+        #[cfg(feature = "pdf")]
+        pdf_page,
+        #[cfg(feature = "video")]
+        video_frame,
+        max_color_distance,
+        inverse_colors,
+        max_delta_e,
+        profile,
+        light_ladder: _,
+        dark_ladder: _,
+        contrast_ramp,
+        background,
+        gray_tint,
+        summary_description: _,
+        accessibility_report: _,
+        report: _,
+        surfaces: _,
+        min_accent_delta_e,
+        min_accent_chroma,
+        min_hue_entropy,
+        duotone,
+        raw_accents,
+        base0f,
+        accent_hue_ranges,
+        slot_adjustments,
+        anchor_selector: _,
+        cache: _,
+    } = &params.options;
+    let key = CacheKey {
+        format: CACHE_FORMAT,
+        version: env!("CARGO_PKG_VERSION"),
+        system: &params.system,
+        variant: &params.variant,
+        crop,
+        center_weight: *center_weight,
+        subject_bias,
+        skin_tone_weight: *skin_tone_weight,
+        sample_every: *sample_every,
+        max_dimension: *max_dimension,
+        denoise,
+        white_balance,
+        equalize: *equalize,
+        superpixels: *superpixels,
+        frames,
+        #[cfg(feature = "svg")]
+        svg_size: *svg_size,
+        #[cfg(feature = "pdf")]
+        pdf_page: *pdf_page,
+        #[cfg(feature = "video")]
+        video_frame,
+        max_color_distance: *max_color_distance,
+        inverse_colors,
+        max_delta_e: *max_delta_e,
+        profile,
+        light_ladder: params.options.light_ladder_or_default(),
+        dark_ladder: params.options.dark_ladder_or_default(),
+        contrast_ramp,
+        background,
+        gray_tint: *gray_tint,
+        min_accent_delta_e: *min_accent_delta_e,
+        min_accent_chroma: *min_accent_chroma,
+        min_hue_entropy: *min_hue_entropy,
+        duotone: *duotone,
+        raw_accents: *raw_accents,
+        base0f,
+        accent_hue_ranges,
+        slot_adjustments,
+    };
+    let mut hasher = blake3::Hasher::new();
+
+    for image_path in image_paths {
+        let contents = fs::read(image_path).map_err(|err| Error::Cache {
+            message: format!("Unable to read {}", image_path.display()),
+            source: Some(err),
+        })?;
+
+        hasher.update(blake3::hash(&contents).as_bytes());
+    }
+
+    let key = serde_json::to_vec(&key).map_err(|err| Error::Cache {
+        message: format!("Unable to serialize the cache key: {}", err),
+        source: None,
+    })?;
+
+    hasher.update(&key);
+
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// A cached extraction, with the palette's colors as hex strings
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CacheEntry {
+    palette: BTreeMap<String, String>,
+    diagnostics: Diagnostics,
+}
+
+fn read_cache_entry(path: &Path) -> Option<(HashMap<String, SchemeColor>, Diagnostics)> {
+    let contents = fs::read_to_string(path).ok()?;
+    let entry: CacheEntry = serde_json::from_str(&contents).ok()?;
+    let palette = entry
+        .palette
+        .into_iter()
+        .map(|(slot, hex)| Some((slot, SchemeColor::new(hex).ok()?)))
+        .collect::<Option<_>>()?;
+
+    Some((palette, entry.diagnostics))
+}
+
+fn write_cache_entry(path: &Path, extraction: &Extraction) -> Result<(), Error> {
+    let entry = CacheEntry {
+        palette: extraction
+            .scheme
+            .palette
+            .iter()
+            .map(|(slot, color)| {
+                let (red, green, blue) = color.rgb;
+
+                (
+                    slot.clone(),
+                    format!("{:02X}{:02X}{:02X}", red, green, blue),
+                )
+            })
+            .collect(),
+        diagnostics: extraction.diagnostics.clone(),
+    };
+    let contents = serde_json::to_string(&entry).map_err(|err| Error::Cache {
+        message: format!("Unable to serialize the cache entry: {}", err),
+        source: None,
+    })?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| Error::Cache {
+            message: format!("Unable to create {}", parent.display()),
+            source: Some(err),
+        })?;
+    }
+
+    fs::write(path, contents).map_err(|err| Error::Cache {
+        message: format!("Unable to write {}", path.display()),
+        source: Some(err),
+    })
+}
+
+/// Extract a scheme from the images, reusing a cached palette when the same
+/// images were extracted with the same settings before. `load` decodes the
+/// images on a cache miss
+pub(crate) fn extract_scheme_cached<F>(
+    image_paths: &[PathBuf],
+    params: SchemeParams,
+    load: F,
+) -> Result<Extraction, Error>
+where
+    F: FnOnce() -> Result<Vec<DynamicImage>, Error>,
+{
+    let key = cache_key(image_paths, &params)?;
+    let entry_path = extraction_cache_dir()?.join(format!("{}.json", key));
+
+    if let Some((palette, diagnostics)) = read_cache_entry(&entry_path) {
+        let SchemeParams {
+            image_path,
+            author,
+            description,
+            name,
+            slug,
+            system,
+            variant,
+            options,
+            ..
+        } = params;
+        let description =
+            scheme_description(description, &image_path, &diagnostics, &variant, &options);
+
+        let scheme = Base16Scheme {
+            author,
+            description,
+            name,
+            slug,
+            system,
+            variant,
+            palette,
+        };
+        return finish_extraction(scheme, diagnostics, &options, None);
+    }
+
+    let extraction = extract_scheme_from_dynamic_images(&load()?, params)?;
+
+    // Caching is best effort, a failed write shouldn't fail the extraction
+    let _ = write_cache_entry(&entry_path, &extraction);
+
+    Ok(extraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        export::tests::test_scheme, ColorDiversity, ReportFormat, SlotProvenance,
+        DEFAULT_LIGHT_LADDER,
+    };
+
+    #[test]
+    fn test_cache_entry_round_trip() {
+        let diagnostics = Diagnostics {
+            light_passes: 2,
+            dark_passes: 3,
+            light_pass: DEFAULT_LIGHT_LADDER.get(1).copied(),
+            low_diversity: true,
+            diversity: ColorDiversity {
+                hue_entropy: 0.125,
+                mean_chroma: 4.5,
+                chroma_deviation: 1.75,
+                colored_fraction: 0.0625,
+            },
+            dominant_hue: Some(210.5),
+            color_cast: Some(Srgb::new(255, 230, 200)),
+            confidence: BTreeMap::from([("base08".to_string(), 0.75)]),
+            provenance: BTreeMap::from([(
+                "base08".to_string(),
+                SlotProvenance {
+                    source: "color_thief cluster 2".to_string(),
+                    pure_color: Some("red".to_string()),
+                    delta_e: Some(12.5),
+                    lightness_shift: None,
+                },
+            )]),
+            ..Diagnostics::default()
+        };
+        let extraction = Extraction {
+            scheme: test_scheme(SchemeSystem::Base16),
+            diagnostics: diagnostics.clone(),
+            surfaces: None,
+            accessibility: None,
+            report: None,
+        };
+        let path = env::temp_dir().join(format!(
+            "tinted-scheme-extractor-cache-{}.json",
+            std::process::id()
+        ));
+
+        write_cache_entry(&path, &extraction).unwrap();
+
+        let (palette, cached) = read_cache_entry(&path).unwrap();
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(palette, extraction.scheme.palette);
+        assert_eq!(cached, diagnostics);
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let params = |options: ExtractionOptions| SchemeParams {
+            image_path: PathBuf::new(),
+            author: "Author".to_string(),
+            description: None,
+            name: "Name".to_string(),
+            slug: "name".to_string(),
+            system: SchemeSystem::Base16,
+            variant: SchemeVariant::Dark,
+            verbose: false,
+            options,
+        };
+        let key = |options: ExtractionOptions| cache_key(&[], &params(options)).unwrap();
+        let default_key = key(ExtractionOptions::default());
+
+        // Options applied again on a hit share the entry
+        assert_eq!(
+            key(ExtractionOptions {
+                surfaces: true,
+                accessibility_report: true,
+                report: Some(ReportFormat::Html),
+                summary_description: true,
+                ..Default::default()
+            }),
+            default_key
+        );
+        // The default ladders are the same entry spelled out
+        assert_eq!(
+            key(ExtractionOptions {
+                light_ladder: Some(DEFAULT_LIGHT_LADDER.to_vec()),
+                ..Default::default()
+            }),
+            default_key
+        );
+        assert_ne!(
+            key(ExtractionOptions {
+                max_delta_e: 10.0,
+                ..Default::default()
+            }),
+            default_key
+        );
+    }
+}
@@ -1,10 +1,17 @@
-use palette::{rgb::Rgb, FromColor, Hsl, IntoColor, Srgb};
+use std::fmt;
 
-#[derive(Clone, Copy, Debug)]
-pub(crate) struct Color {
-    pub(crate) associated_pure_color: PureColor,
-    pub(crate) value: Srgb<u8>,
-    pub(crate) distance: f64,
+use palette::{color_difference::Ciede2000, rgb::Rgb, FromColor, Hsl, IntoColor, Lab, Srgb};
+
+use crate::{utils::scheme_color, Error};
+
+/// A color along with the pure color it's associated with, which is how
+/// the extractor decides the slot a color fits
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Color {
+    pub associated_pure_color: PureColor,
+    pub value: Srgb<u8>,
+    /// The Euclidean distance between `value` and the pure color
+    pub distance: f64,
 }
 
 impl Color {
@@ -14,7 +21,7 @@ impl Color {
     /// # Arguments
     /// * `pure_color` - A PureColor enum
     /// * `value` - A Srgb<u8> color
-    pub(crate) fn new(pure_color: PureColor, value: Srgb<u8>) -> Self {
+    pub fn new(pure_color: PureColor, value: Srgb<u8>) -> Self {
         let distance = Color::get_distance(&Color::from(pure_color).value, &value);
 
         Color {
@@ -24,13 +31,23 @@ impl Color {
         }
     }
 
-    /// Create a new color from a pure color
-    pub(crate) fn from(pure_color: PureColor) -> Self {
-        Color {
-            associated_pure_color: pure_color,
-            value: pure_color.get_rgb(),
-            distance: 0.0,
-        }
+    /// Create a new color associated with the closest pure color
+    ///
+    /// # Arguments
+    /// * `value` - A Srgb<u8> color
+    pub fn classify(value: Srgb<u8>) -> Self {
+        Color::new(PureColor::closest(value), value)
+    }
+
+    /// Parse a hex color such as `#CC3333` or `cc3333`, associated with the
+    /// closest pure color
+    ///
+    /// # Arguments
+    /// * `hex` - The hex color, with or without a leading `#`
+    pub fn from_hex(hex: &str) -> Result<Self, Error> {
+        let (red, green, blue) = scheme_color(hex.trim_start_matches('#').to_string())?.rgb;
+
+        Ok(Color::classify(Srgb::new(red, green, blue)))
     }
 
     /// Get the inverse of the color
@@ -51,7 +68,7 @@ impl Color {
     /// # Arguments
     /// * `c1` - A reference to a Srgb<u8> color
     /// * `c2` - A reference to a Srgb<u8> color
-    pub(crate) fn get_distance(c1: &Srgb<u8>, c2: &Srgb<u8>) -> f64 {
+    pub fn get_distance(c1: &Srgb<u8>, c2: &Srgb<u8>) -> f64 {
         // Order of c1 and c2 doesn't matter
         let dr = c1.red as i32 - c2.red as i32;
         let dg = c1.green as i32 - c2.green as i32;
@@ -60,8 +77,21 @@ impl Color {
         ((dr * dr + dg * dg + db * db) as f64).sqrt()
     }
 
+    /// Get the perceptual difference between two colors
+    /// The difference is calculated using the CIEDE2000 formula, where about
+    /// 2.3 is just noticeable
+    ///
+    /// # Arguments
+    /// * `c1` - A reference to a Srgb<u8> color
+    /// * `c2` - A reference to a Srgb<u8> color
+    pub fn get_delta_e(c1: &Srgb<u8>, c2: &Srgb<u8>) -> f32 {
+        let lab = |color: &Srgb<u8>| Lab::from_color(color.into_format::<f32>());
+
+        lab(c1).difference(lab(c2))
+    }
+
     /// Convert the color to a hex string
-    pub(crate) fn to_hex(self) -> String {
+    pub fn to_hex(self) -> String {
         let (r, g, b) = self.value.into_components();
 
         format!("{:02X}{:02X}{:02X}", r, g, b)
@@ -82,11 +112,7 @@ impl Color {
         );
         let updated_rgb: Rgb = updated_saturation.into_color();
 
-        self.value = Srgb::new(
-            (updated_rgb.red * 255.0) as u8,
-            (updated_rgb.green * 255.0) as u8,
-            (updated_rgb.blue * 255.0) as u8,
-        );
+        self.value = updated_rgb.into_format();
 
         self
     }
@@ -95,26 +121,46 @@ impl Color {
     ///
     /// # Arguments
     ///
-    /// * `value` - A f32 value between 0.0 and 1.0
+    /// * `value` - A f32 value between -1.0 and 1.0, darkening the color
+    ///   when negative
     ///
     pub(crate) fn add_lightness(mut self, value: f32) -> Self {
         let hsl: Hsl = Hsl::from_color(self.value.into_format::<f32>());
-        let updated_lightness = (hsl.lightness + value.clamp(0.0, 1.0)).clamp(0.0, 1.0);
+        let updated_lightness = (hsl.lightness + value.clamp(-1.0, 1.0)).clamp(0.0, 1.0);
         let hsl: Hsl = Hsl::new(hsl.hue, hsl.saturation, updated_lightness);
         let updated_rgb: Rgb = hsl.into_color();
 
-        self.value = Srgb::new(
-            (updated_rgb.red * 255.0) as u8,
-            (updated_rgb.green * 255.0) as u8,
-            (updated_rgb.blue * 255.0) as u8,
-        );
+        self.value = updated_rgb.into_format();
 
         self
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub(crate) enum PureColor {
+impl From<PureColor> for Color {
+    fn from(pure_color: PureColor) -> Self {
+        Color {
+            associated_pure_color: pure_color,
+            value: pure_color.get_rgb(),
+            distance: 0.0,
+        }
+    }
+}
+
+impl From<Color> for Srgb<u8> {
+    fn from(color: Color) -> Self {
+        color.value
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} ({})", self.to_hex(), self.associated_pure_color)
+    }
+}
+
+/// The reference colors that extracted colors are matched to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PureColor {
     Red,
     Yellow,
     Orange,
@@ -130,7 +176,38 @@ pub(crate) enum PureColor {
 }
 
 impl PureColor {
-    pub(crate) fn get_rgb(&self) -> Srgb<u8> {
+    /// Every pure color, in the order the extractor tries them
+    pub const ALL: [PureColor; 12] = [
+        PureColor::Red,
+        PureColor::Yellow,
+        PureColor::Orange,
+        PureColor::Green,
+        PureColor::Cyan,
+        PureColor::Blue,
+        PureColor::Purple,
+        PureColor::Brown,
+        PureColor::Magenta,
+        PureColor::Azure,
+        PureColor::SpringGreen,
+        PureColor::LightCyan,
+    ];
+
+    /// Get the pure color closest to a color, the first in `ALL` on ties
+    ///
+    /// # Arguments
+    /// * `value` - A Srgb<u8> color
+    pub fn closest(value: Srgb<u8>) -> PureColor {
+        PureColor::ALL
+            .iter()
+            .copied()
+            .min_by(|a, b| {
+                Color::get_distance(&a.get_rgb(), &value)
+                    .total_cmp(&Color::get_distance(&b.get_rgb(), &value))
+            })
+            .unwrap_or(PureColor::Red)
+    }
+
+    pub fn get_rgb(&self) -> Srgb<u8> {
         match self {
             PureColor::Red => Srgb::new(255, 0, 0),
             PureColor::Yellow => Srgb::new(255, 255, 0),
@@ -147,7 +224,7 @@ impl PureColor {
         }
     }
 
-    pub(crate) fn as_str(&self) -> &str {
+    pub fn as_str(&self) -> &str {
         match self {
             PureColor::Red => "red",
             PureColor::Yellow => "yellow",
@@ -164,7 +241,7 @@ impl PureColor {
         }
     }
 
-    pub(crate) fn get_inverse(&self) -> PureColor {
+    pub fn get_inverse(&self) -> PureColor {
         match self {
             PureColor::Red => PureColor::Cyan,
             PureColor::Yellow => PureColor::Blue,
@@ -182,6 +259,12 @@ impl PureColor {
     }
 }
 
+impl fmt::Display for PureColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -194,6 +277,32 @@ mod tests {
         assert_eq!(color.value, Srgb::new(255, 51, 51));
     }
 
+    #[test]
+    fn test_add_lightness_darkens() {
+        let color = Color::new(PureColor::Red, Srgb::new(255, 0, 0));
+        let color = color.add_lightness(-0.2);
+
+        assert_eq!(color.value, Srgb::new(153, 0, 0));
+    }
+
+    #[test]
+    fn test_add_lightness_rounds() {
+        // Reference values from Python's colorsys: 213.2, 129.6, 87.8
+        let color = Color::new(PureColor::Orange, Srgb::new(200, 100, 50));
+        let color = color.add_lightness(0.1);
+
+        assert_eq!(color.value, Srgb::new(213, 130, 88));
+    }
+
+    #[test]
+    fn test_to_saturated_rounds() {
+        // Reference values from Python's colorsys: 143.75, 118.75, 106.25
+        let color = Color::new(PureColor::Orange, Srgb::new(200, 100, 50));
+        let color = color.to_saturated(0.5);
+
+        assert_eq!(color.value, Srgb::new(144, 119, 106));
+    }
+
     #[test]
     fn test_get_distance() {
         let color1 = Srgb::new(255, 0, 0);
@@ -211,6 +320,24 @@ mod tests {
         assert_eq!(color.value, Srgb::new(0, 255, 255));
     }
 
+    #[test]
+    fn test_from_hex() {
+        let color = Color::from_hex("#E01010").unwrap();
+
+        assert_eq!(color.associated_pure_color, PureColor::Red);
+        assert_eq!(Srgb::from(color), Srgb::new(224, 16, 16));
+        assert_eq!(color.to_string(), "#E01010 (red)");
+        assert_eq!(
+            Color::from_hex("#CC3333").unwrap().associated_pure_color,
+            PureColor::Brown
+        );
+        assert_eq!(
+            Color::from_hex("ff00ff").unwrap().associated_pure_color,
+            PureColor::Magenta
+        );
+        assert!(Color::from_hex("nope").is_err());
+    }
+
     #[test]
     fn test_to_hex() {
         let color = Color::new(PureColor::Red, Srgb::new(255, 0, 0));
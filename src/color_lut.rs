@@ -0,0 +1,129 @@
+use std::sync::LazyLock;
+
+use palette::Srgb;
+
+use crate::color::PureColor;
+
+/// Width of a lookup cell along each channel
+const CELL_SIZE: u32 = 8;
+/// Number of lookup cells along each channel
+const CELLS_PER_CHANNEL: u32 = 256 / CELL_SIZE;
+
+/// What is known about the colors of a 8×8×8 cell before looking at them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct LutEntry {
+    /// Index into `PureColor::ALL` of the pure color closest to the center
+    /// of the cell
+    pub(crate) nearest: usize,
+    /// The whole part of the smallest distance any color of the cell can
+    /// have to any pure color
+    pub(crate) bucket: u16,
+}
+
+/// The entries of all 32×32×32 cells, built on first use
+static COLOR_LUT: LazyLock<Vec<LutEntry>> = LazyLock::new(|| {
+    let mut entries = Vec::with_capacity(CELLS_PER_CHANNEL.pow(3) as usize);
+
+    for red in 0..CELLS_PER_CHANNEL {
+        for green in 0..CELLS_PER_CHANNEL {
+            for blue in 0..CELLS_PER_CHANNEL {
+                entries.push(cell_entry(red, green, blue));
+            }
+        }
+    }
+
+    entries
+});
+
+/// The squared distance from `value` to the closest value of the cell
+/// `cell` along one channel
+fn channel_bound(value: u8, cell: u32) -> u32 {
+    let low = cell * CELL_SIZE;
+    let high = low + CELL_SIZE - 1;
+    let value = value as u32;
+    let distance = if value < low {
+        low - value
+    } else {
+        value.saturating_sub(high)
+    };
+
+    distance * distance
+}
+
+fn cell_entry(red: u32, green: u32, blue: u32) -> LutEntry {
+    let center = |cell: u32| (cell * CELL_SIZE) as f64 + (CELL_SIZE - 1) as f64 / 2.0;
+    let (nearest, _) = PureColor::ALL
+        .iter()
+        .map(|pure_color| {
+            let rgb = pure_color.get_rgb();
+            let dr = center(red) - rgb.red as f64;
+            let dg = center(green) - rgb.green as f64;
+            let db = center(blue) - rgb.blue as f64;
+
+            dr * dr + dg * dg + db * db
+        })
+        .enumerate()
+        .fold((0, f64::MAX), |best, (i, distance)| {
+            if distance < best.1 {
+                (i, distance)
+            } else {
+                best
+            }
+        });
+    let bound = PureColor::ALL
+        .iter()
+        .map(|pure_color| {
+            let rgb = pure_color.get_rgb();
+
+            channel_bound(rgb.red, red)
+                + channel_bound(rgb.green, green)
+                + channel_bound(rgb.blue, blue)
+        })
+        .min()
+        .unwrap_or(0);
+
+    LutEntry {
+        nearest,
+        bucket: (bound as f64).sqrt() as u16,
+    }
+}
+
+/// Look up the entry of the cell a color falls into
+pub(crate) fn lookup(color: Srgb<u8>) -> LutEntry {
+    let index = ((color.red as usize >> 3) << 10)
+        | ((color.green as usize >> 3) << 5)
+        | (color.blue as usize >> 3);
+
+    COLOR_LUT[index]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    #[test]
+    fn test_lookup() {
+        for (i, pure_color) in PureColor::ALL.iter().enumerate() {
+            let entry = lookup(pure_color.get_rgb());
+
+            assert_eq!(entry.bucket, 0);
+            assert_eq!(PureColor::ALL[entry.nearest], *pure_color, "{i}");
+        }
+
+        // Every color of a cell is at least `bucket` away from every pure
+        // color
+        for value in (0..=255u32).step_by(3) {
+            let color = Srgb::new(
+                value as u8,
+                (value * 7 % 256) as u8,
+                (value * 13 % 256) as u8,
+            );
+            let entry = lookup(color);
+
+            for pure_color in PureColor::ALL {
+                assert!(Color::get_distance(&color, &pure_color.get_rgb()) >= entry.bucket as f64);
+            }
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::{BTreeMap, HashMap};
+
+use palette::Srgb;
+use tinted_builder::{Color as SchemeColor, SchemeSystem, SchemeVariant};
+
+use crate::{
+    color::{Color, PureColor},
+    utils::second_dominant_color,
+    Base0FPolicy, Diagnostics, ExtractionOptions,
+};
+
+/// The pure colors of base08 to base0E, and of base10 to base16 for Base24.
+/// base0F follows `ExtractionOptions::base0f`
+pub(crate) const ACCENT_PURE_COLORS: [PureColor; 7] = [
+    PureColor::Red,
+    PureColor::Orange,
+    PureColor::Yellow,
+    PureColor::Green,
+    PureColor::Cyan,
+    PureColor::Blue,
+    PureColor::Purple,
+];
+/// How much confidence is kept when color_thief couldn't be used
+const FALLBACK_FACTOR: f32 = 0.7;
+
+/// Work out how much every slot can be trusted, from 0.0 to 1.0
+///
+/// Grays follow the ladder pass that found their anchor, blending from
+/// the background's anchor at base00 to the foreground's at base07.
+/// Accents average how close their color is to the pure color, relative
+/// to `ExtractionOptions::max_delta_e`, and how
+/// dominant it is, with colors that aren't color_thief clusters counting
+/// as half as dominant. A second dominant hue in base0F only counts its
+/// dominance. Synthesized accents have no confidence, and
+/// everything is lowered when color_thief couldn't be used.
+pub(crate) fn slot_confidence(
+    palette: &HashMap<String, SchemeColor>,
+    system: &SchemeSystem,
+    variant: &SchemeVariant,
+    diagnostics: &Diagnostics,
+    accents: &[Color],
+    color_thief_palette: &[Srgb<u8>],
+    options: &ExtractionOptions,
+) -> BTreeMap<String, f32> {
+    let factor = if diagnostics.color_thief_fallback {
+        FALLBACK_FACTOR
+    } else {
+        1.0
+    };
+    let anchor = |passes: u32, ladder_len: usize| {
+        if passes == 0 {
+            1.0
+        } else {
+            1.0 - (passes - 1) as f32 / (ladder_len + 1) as f32
+        }
+    };
+    let light = anchor(
+        diagnostics.light_passes,
+        options.light_ladder_or_default().len(),
+    );
+    let dark = anchor(
+        diagnostics.dark_passes,
+        options.dark_ladder_or_default().len(),
+    );
+    let (background, foreground) = match variant {
+        SchemeVariant::Light => (light, dark),
+        _ => (dark, light),
+    };
+    let background = if options.background.is_some() {
+        1.0
+    } else {
+        background * factor
+    };
+    let foreground = foreground * factor;
+    let mut confidence = BTreeMap::new();
+
+    for index in 0..8 {
+        let t = index as f32 / 7.0;
+
+        confidence.insert(
+            format!("base0{}", index),
+            background + t * (foreground - background),
+        );
+    }
+
+    let offsets: &[usize] = match system {
+        SchemeSystem::Base24 => &[0x08, 0x10],
+        _ => &[0x08],
+    };
+
+    let dominance =
+        |value: Srgb<u8>| match color_thief_palette.iter().position(|color| *color == value) {
+            Some(rank) => 1.0 - 0.5 * rank as f32 / color_thief_palette.len() as f32,
+            None => 0.5,
+        };
+    let second_hue = match options.base0f {
+        Base0FPolicy::SecondDominantHue => second_dominant_color(color_thief_palette),
+        _ => None,
+    };
+    let pure_colors = ACCENT_PURE_COLORS
+        .iter()
+        .copied()
+        .chain([options.base0f.pure_color()]);
+
+    for (index, pure_color) in pure_colors.enumerate() {
+        let value = match second_hue.filter(|_| index == 7) {
+            Some(value) => dominance(value) * factor,
+            None => accents
+                .iter()
+                .find(|color| color.associated_pure_color == pure_color)
+                .map_or(0.0, |color| {
+                    let delta_e =
+                        Color::get_delta_e(&color.associated_pure_color.get_rgb(), &color.value);
+                    let distance = (1.0 - delta_e / options.max_delta_e.max(1.0)).clamp(0.0, 1.0);
+
+                    (distance + dominance(color.value)) / 2.0 * factor
+                }),
+        };
+
+        for offset in offsets {
+            confidence.insert(format!("base{:02X}", offset + index), value);
+        }
+    }
+
+    confidence.retain(|slot, _| palette.contains_key(slot));
+    confidence
+}
+
+/// The confidence of a single-color palette, whose grays come straight
+/// from the image and whose accents are all synthesized
+pub(crate) fn flat_confidence(palette: &HashMap<String, SchemeColor>) -> BTreeMap<String, f32> {
+    palette
+        .keys()
+        .map(|slot| {
+            let is_gray = slot.len() == 6 && slot.starts_with("base0") && slot.as_bytes()[5] < b'8';
+
+            (slot.clone(), if is_gray { 1.0 } else { 0.0 })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::scheme_color;
+
+    #[test]
+    fn test_slot_confidence() {
+        let palette: HashMap<String, SchemeColor> = ["base00", "base07", "base08", "base0B"]
+            .iter()
+            .map(|slot| {
+                (
+                    slot.to_string(),
+                    scheme_color("808080".to_string()).unwrap(),
+                )
+            })
+            .collect();
+        let red = Srgb::new(250, 10, 10);
+        let diagnostics = Diagnostics {
+            light_passes: 1,
+            dark_passes: 4,
+            ..Diagnostics::default()
+        };
+        let confidence = slot_confidence(
+            &palette,
+            &SchemeSystem::Base16,
+            &SchemeVariant::Dark,
+            &diagnostics,
+            &[Color::new(PureColor::Red, red)],
+            &[red],
+            &ExtractionOptions::default(),
+        );
+
+        assert_eq!(confidence.len(), 4);
+        assert_eq!(confidence["base00"], 0.25);
+        assert_eq!(confidence["base07"], 1.0);
+        assert!(confidence["base08"] > 0.9);
+        // base0B was synthesized
+        assert_eq!(confidence["base0B"], 0.0);
+        assert_eq!(flat_confidence(&palette)["base07"], 1.0);
+        assert_eq!(flat_confidence(&palette)["base08"], 0.0);
+    }
+}
@@ -0,0 +1,219 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard},
+    thread,
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+use tinted_builder::Base16Scheme;
+
+use crate::{
+    create_scheme_from_image, export_scheme, utils::error_chain, Error, OutputFormat, SchemeParams,
+};
+
+/// The most responses kept in memory before the cache is cleared
+const MAX_CACHED_RESPONSES: usize = 64;
+
+/// A request to the daemon, sent as a single line of JSON
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DaemonRequest {
+    #[serde(flatten)]
+    pub params: SchemeParams,
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+/// The daemon's reply to a request, sent as a single line of JSON
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DaemonResponse {
+    /// The scheme YAML or exported file as a string, or the scheme as a
+    /// JSON object
+    Scheme(serde_json::Value),
+    /// The error and its sources
+    Error(String),
+}
+
+#[derive(Default)]
+struct ResponseCache {
+    /// Responses keyed by request, with the image's modification time
+    responses: HashMap<String, (SystemTime, String)>,
+}
+
+/// Listen on a Unix socket and extract schemes for each request, keeping the
+/// process warm between wallpaper changes
+///
+/// Every connection may send any number of [`DaemonRequest`]s, one JSON
+/// object per line, e.g. `{"image_path": "wall.png", "author": "Author",
+/// "name": "Wall", "slug": "wall", "system": "base16", "variant": "dark",
+/// "format": "json"}`, and gets one [`DaemonResponse`] line back for each,
+/// e.g. `{"scheme": ...}` or `{"error": "..."}`. Responses are cached in
+/// memory until the image is modified. This only returns if the socket
+/// can't be created.
+///
+/// # Arguments
+/// * `socket_path` - Where to create the socket. A stale socket left behind
+///   by a daemon that is no longer running is replaced
+pub fn serve_unix_socket(socket_path: &Path) -> Result<(), Error> {
+    if socket_path.exists() {
+        if UnixStream::connect(socket_path).is_ok() {
+            return Err(Error::Daemon {
+                message: format!("A daemon is already listening on {}", socket_path.display()),
+                source: None,
+            });
+        }
+
+        fs::remove_file(socket_path).map_err(|err| Error::Daemon {
+            message: format!("Unable to remove {}", socket_path.display()),
+            source: Some(err),
+        })?;
+    }
+
+    let listener = UnixListener::bind(socket_path).map_err(|err| Error::Daemon {
+        message: format!("Unable to listen on {}", socket_path.display()),
+        source: Some(err),
+    })?;
+    let cache = Arc::new(Mutex::new(ResponseCache::default()));
+
+    // A failed connection only affects that client, so keep accepting others
+    for stream in listener.incoming().flatten() {
+        let cache = Arc::clone(&cache);
+
+        thread::spawn(move || handle_connection(stream, &cache));
+    }
+
+    Ok(())
+}
+
+fn handle_connection(stream: UnixStream, cache: &Mutex<ResponseCache>) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if writeln!(writer, "{}", respond(line.trim(), cache)).is_err() {
+            return;
+        }
+    }
+}
+
+/// Get the JSON response line for a request line
+fn respond(line: &str, cache: &Mutex<ResponseCache>) -> String {
+    let request: DaemonRequest = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(err) => return error_response(format!("Invalid request: {}", err)),
+    };
+    let modified = fs::metadata(&request.params.image_path)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+
+    if let Some((cached_modified, response)) = lock(cache).responses.get(line) {
+        if Some(*cached_modified) == modified {
+            return response.clone();
+        }
+    }
+
+    let response = match create_scheme_from_image(request.params) {
+        Ok(scheme) => scheme_response(&scheme, request.format),
+        Err(err) => return error_response(error_chain(&err)),
+    };
+
+    if let Some(modified) = modified {
+        let mut cache = lock(cache);
+
+        if cache.responses.len() >= MAX_CACHED_RESPONSES {
+            cache.responses.clear();
+        }
+
+        cache
+            .responses
+            .insert(line.to_string(), (modified, response.clone()));
+    }
+
+    response
+}
+
+fn lock(cache: &Mutex<ResponseCache>) -> MutexGuard<'_, ResponseCache> {
+    cache.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+fn scheme_response(scheme: &Base16Scheme, format: OutputFormat) -> String {
+    let scheme = match format {
+        OutputFormat::Yaml => Ok(serde_json::Value::String(scheme.to_string())),
+        OutputFormat::Json => serde_json::to_value(scheme).map_err(|err| err.to_string()),
+        OutputFormat::Export(format) => export_scheme(scheme, format)
+            .map(serde_json::Value::String)
+            .map_err(|err| error_chain(&err)),
+    };
+
+    match scheme {
+        Ok(scheme) => to_line(&DaemonResponse::Scheme(scheme)),
+        Err(err) => error_response(err),
+    }
+}
+
+fn error_response(message: String) -> String {
+    to_line(&DaemonResponse::Error(message))
+}
+
+fn to_line(response: &DaemonResponse) -> String {
+    // Serializing a JSON value or a string can't fail
+    serde_json::to_string(response).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ExportFormat;
+
+    #[test]
+    fn test_respond_reports_errors() {
+        let cache = Mutex::new(ResponseCache::default());
+
+        assert!(respond("not json", &cache).starts_with(r#"{"error":"Invalid request: "#));
+
+        let response = respond(
+            r#"{"image_path": "/does/not/exist.png", "author": "Author", "name": "Name", "slug": "name", "system": "base16", "variant": "dark"}"#,
+            &cache,
+        );
+
+        assert!(response.starts_with(r#"{"error":"image decode: /does/not/exist.png: "#));
+        assert!(lock(&cache).responses.is_empty());
+    }
+
+    #[test]
+    fn test_request_formats() {
+        let format = |format: &str| {
+            serde_json::from_str::<DaemonRequest>(&format!(
+                r#"{{"image_path": "wall.png", "author": "Author", "name": "Name", "slug": "name", "system": "base16", "variant": "dark", "format": "{}"}}"#,
+                format
+            ))
+            .map(|request| request.format)
+            .ok()
+        };
+
+        assert_eq!(format("json"), Some(OutputFormat::Json));
+        assert_eq!(
+            format("tailwind"),
+            Some(OutputFormat::Export(ExportFormat::Tailwind))
+        );
+        assert_eq!(format("word"), None);
+        assert_eq!(
+            serde_json::to_string(&OutputFormat::Export(ExportFormat::Tailwind)).unwrap(),
+            r#""tailwind""#
+        );
+    }
+}
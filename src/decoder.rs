@@ -0,0 +1,59 @@
+use std::io::{BufRead, Seek};
+
+use image::{
+    error::{DecodingError, ImageFormatHint, LimitError, LimitErrorKind},
+    ImageError, ImageFormat, ImageResult, Limits, RgbaImage,
+};
+use zune_core::{colorspace::ColorSpace, options::DecoderOptions};
+use zune_jpeg::{errors::DecodeErrors, JpegDecoder};
+
+/// Decode a JPEG with zune-jpeg straight into RGBA pixels, which skips
+/// the image crate's RGB decode and the copy when converting it to RGBA
+///
+/// # Arguments
+/// * `reader` - The encoded JPEG
+/// * `limits` - The decoding limits, enforced before any pixels are decoded
+pub(crate) fn decode_jpeg<R: BufRead + Seek>(reader: R, limits: &Limits) -> ImageResult<RgbaImage> {
+    // Dimensions are checked against `limits` below, like the image crate does
+    let options = DecoderOptions::default()
+        .set_max_width(usize::MAX)
+        .set_max_height(usize::MAX)
+        .jpeg_set_out_colorspace(ColorSpace::RGBA);
+    let mut decoder = JpegDecoder::new_with_options(reader, options);
+
+    decoder.decode_headers().map_err(jpeg_error)?;
+
+    let (width, height) = decoder
+        .dimensions()
+        .map(|(width, height)| (width as u32, height as u32))
+        .ok_or_else(|| jpeg_error(DecodeErrors::FormatStatic("Missing JPEG headers")))?;
+
+    limits.check_dimensions(width, height)?;
+
+    let size = decoder
+        .output_buffer_size()
+        .ok_or_else(|| jpeg_error(DecodeErrors::FormatStatic("Missing JPEG headers")))?;
+
+    if limits
+        .max_alloc
+        .is_some_and(|max_alloc| size as u64 > max_alloc)
+    {
+        return Err(ImageError::Limits(LimitError::from_kind(
+            LimitErrorKind::InsufficientMemory,
+        )));
+    }
+
+    let mut pixels = vec![0; size];
+
+    decoder.decode_into(&mut pixels).map_err(jpeg_error)?;
+
+    RgbaImage::from_raw(width, height, pixels)
+        .ok_or_else(|| jpeg_error(DecodeErrors::FormatStatic("Unexpected JPEG buffer size")))
+}
+
+fn jpeg_error(err: DecodeErrors) -> ImageError {
+    ImageError::Decoding(DecodingError::new(
+        ImageFormatHint::Exact(ImageFormat::Jpeg),
+        err,
+    ))
+}
@@ -0,0 +1,124 @@
+use std::borrow::Cow;
+
+use image::RgbaImage;
+use palette::{FromColor, Lab, Lch, Srgb};
+
+/// The most pixels looked at, spread evenly over the images
+const MAX_SAMPLES: u64 = 65_536;
+/// The LCh chroma above which a pixel counts as colored
+const MIN_CHROMA: f32 = 10.0;
+/// How many bins of 10° the hues are counted in
+const HUE_BINS: usize = 36;
+
+/// How colorful the source images are, in `Diagnostics::diversity`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColorDiversity {
+    /// How evenly the colored pixels spread over the hue circle, from 0.0
+    /// when they all share one hue to 1.0 when every hue is as common, as
+    /// the Shannon entropy of their hues in 10° bins
+    pub hue_entropy: f32,
+    /// The mean LCh chroma of all pixels, about 0.0 to 130.0
+    pub mean_chroma: f32,
+    /// The standard deviation of the pixels' LCh chroma
+    pub chroma_deviation: f32,
+    /// The share of pixels with an LCh chroma above 10.0, from 0.0 to 1.0
+    pub colored_fraction: f32,
+}
+
+/// Measure how colorful the images are, along with their mean color in Lab
+pub(crate) fn color_diversity(images: &[Cow<RgbaImage>]) -> (ColorDiversity, Srgb<u8>) {
+    let pixel_count: u64 = images
+        .iter()
+        .map(|image| image.width() as u64 * image.height() as u64)
+        .sum();
+    let step = pixel_count.div_ceil(MAX_SAMPLES).max(1) as usize;
+    let mut bins = [0u64; HUE_BINS];
+    let (mut count, mut colored) = (0u64, 0u64);
+    let (mut chroma_sum, mut chroma_squares) = (0.0_f64, 0.0_f64);
+    let mut lab_sum = [0.0_f64; 3];
+
+    for pixel in images.iter().flat_map(|image| image.pixels()).step_by(step) {
+        let [red, green, blue, _] = pixel.0;
+        let lab = Lab::from_color(Srgb::new(red, green, blue).into_format::<f32>());
+        let lch = Lch::from_color(lab);
+
+        count += 1;
+        chroma_sum += lch.chroma as f64;
+        chroma_squares += (lch.chroma as f64).powi(2);
+        lab_sum[0] += lab.l as f64;
+        lab_sum[1] += lab.a as f64;
+        lab_sum[2] += lab.b as f64;
+
+        if lch.chroma > MIN_CHROMA {
+            let bin = (lch.hue.into_positive_degrees() / 360.0 * HUE_BINS as f32) as usize;
+
+            bins[bin.min(HUE_BINS - 1)] += 1;
+            colored += 1;
+        }
+    }
+
+    if count == 0 {
+        return (ColorDiversity::default(), Srgb::new(0, 0, 0));
+    }
+
+    let entropy: f64 = bins
+        .iter()
+        .filter(|bin| **bin > 0)
+        .map(|bin| {
+            let share = *bin as f64 / colored as f64;
+
+            -share * share.ln()
+        })
+        .sum();
+    let mean_chroma = chroma_sum / count as f64;
+    let mean = lab_sum.map(|sum| (sum / count as f64) as f32);
+
+    (
+        ColorDiversity {
+            hue_entropy: (entropy / (HUE_BINS as f64).ln()) as f32,
+            mean_chroma: mean_chroma as f32,
+            chroma_deviation: (chroma_squares / count as f64 - mean_chroma.powi(2))
+                .max(0.0)
+                .sqrt() as f32,
+            colored_fraction: (colored as f64 / count as f64) as f32,
+        },
+        Srgb::from_color(Lab::new(mean[0], mean[1], mean[2])).into_format(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn test_color_diversity() {
+        let gray = RgbaImage::from_pixel(10, 10, Rgba([128, 128, 128, 255]));
+        let (diversity, mean) = color_diversity(&[Cow::Owned(gray)]);
+
+        assert_eq!(diversity.hue_entropy, 0.0);
+        assert_eq!(diversity.colored_fraction, 0.0);
+        assert!(diversity.mean_chroma < 1.0);
+        assert_eq!(mean, Srgb::new(128, 128, 128));
+
+        // Shades of a single blue have no hue entropy
+        let blues = RgbaImage::from_fn(10, 10, |x, _| Rgba([0, 0, 100 + x as u8 * 15, 255]));
+        let (diversity, _) = color_diversity(&[Cow::Owned(blues)]);
+
+        assert!(diversity.hue_entropy < 0.1);
+        assert_eq!(diversity.colored_fraction, 1.0);
+        assert!(diversity.chroma_deviation > 5.0);
+
+        // A rainbow spreads over the hue circle
+        let rainbow = RgbaImage::from_fn(360, 1, |x, _| {
+            let rgb: Srgb = Srgb::from_color(palette::Hsv::new(x as f32, 1.0, 1.0));
+            let rgb: Srgb<u8> = rgb.into_format();
+
+            Rgba([rgb.red, rgb.green, rgb.blue, 255])
+        });
+        let (diversity, _) = color_diversity(&[Cow::Owned(rainbow)]);
+
+        assert!(diversity.hue_entropy > 0.8);
+    }
+}
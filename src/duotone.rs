@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use palette::{FromColor, Lch, Srgb};
+use tinted_builder::{Color as SchemeColor, SchemeSystem, SchemeVariant};
+
+use crate::{
+    gamut::to_srgb_within_gamut,
+    utils::{scheme_color_from_srgb, second_dominant_color},
+    Error,
+};
+
+/// The LCh lightness and chroma of the four variations of each hue, for
+/// dark schemes
+const DARK_VARIATIONS: [(f32, f32); 4] = [(62.0, 60.0), (70.0, 48.0), (78.0, 36.0), (55.0, 42.0)];
+/// The same for light schemes, darker so they stay readable
+const LIGHT_VARIATIONS: [(f32, f32); 4] = [(45.0, 60.0), (38.0, 48.0), (30.0, 36.0), (52.0, 42.0)];
+/// How much further from the background Base24's bright accents are, and
+/// how much of their chroma they keep
+const BRIGHT_LIGHTNESS: f32 = 8.0;
+const BRIGHT_CHROMA: f32 = 0.8;
+
+/// Get the two hues of a duotone scheme: the dominant hue and the hue of
+/// the second dominant color, or its complement when there's none
+///
+/// # Arguments
+/// * `dominant_hue` - The dominant hue in degrees
+/// * `colors` - The dominant colors, most dominant first
+pub(crate) fn duotone_hues(dominant_hue: f32, colors: &[Srgb<u8>]) -> (f32, f32) {
+    let secondary = second_dominant_color(colors)
+        .map(|color| {
+            Lch::from_color(color.into_format::<f32>())
+                .hue
+                .into_positive_degrees()
+        })
+        .unwrap_or((dominant_hue + 180.0).rem_euclid(360.0));
+
+    (dominant_hue, secondary)
+}
+
+/// Replace the accents with variations of two hues: base08, base0A, base0C
+/// and base0E use the first hue, base09, base0B, base0D and base0F the
+/// second, each pair with its own lightness and chroma so the accents stay
+/// apart. Base24's bright accents are further from the background and
+/// slightly desaturated
+///
+/// # Arguments
+/// * `palette` - A mutable reference to the scheme palette to adjust
+/// * `system` - A reference to the SchemeSystem deciding the accent slots
+/// * `variant` - A reference to the SchemeVariant deciding the lightness
+/// * `hues` - The two hues in degrees, e.g. from `duotone_hues`
+pub(crate) fn duotone_accents(
+    palette: &mut HashMap<String, SchemeColor>,
+    system: &SchemeSystem,
+    variant: &SchemeVariant,
+    hues: (f32, f32),
+) -> Result<(), Error> {
+    let (variations, bright_step) = match variant {
+        SchemeVariant::Light => (LIGHT_VARIATIONS, -BRIGHT_LIGHTNESS),
+        _ => (DARK_VARIATIONS, BRIGHT_LIGHTNESS),
+    };
+    let to_scheme_color = |lightness: f32, chroma: f32, hue: f32| {
+        let rgb = to_srgb_within_gamut(Lch::new(lightness, chroma, hue));
+
+        scheme_color_from_srgb(rgb)
+    };
+
+    for index in 0..8 {
+        let hue = if index % 2 == 0 { hues.0 } else { hues.1 };
+        let (lightness, chroma) = variations[index / 2];
+
+        palette.insert(
+            format!("base{:02X}", 0x08 + index),
+            to_scheme_color(lightness, chroma, hue),
+        );
+
+        if let SchemeSystem::Base24 = system {
+            palette.insert(
+                format!("base{:02X}", 0x10 + index),
+                to_scheme_color(
+                    (lightness + bright_step).clamp(0.0, 100.0),
+                    chroma * BRIGHT_CHROMA,
+                    hue,
+                ),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duotone_accents() {
+        let blue = Srgb::new(30, 60, 200);
+        let orange = Srgb::new(220, 120, 20);
+        let blue_hue = Lch::from_color(blue.into_format::<f32>())
+            .hue
+            .into_positive_degrees();
+        let (first, second) = duotone_hues(blue_hue, &[blue, orange]);
+
+        assert_eq!(first, blue_hue);
+        assert!((second - 60.0).abs() < 20.0);
+
+        // Without a second color, the complement is used
+        let (_, complement) = duotone_hues(blue_hue, &[blue]);
+
+        assert!((complement - (blue_hue + 180.0).rem_euclid(360.0)).abs() < f32::EPSILON);
+
+        let mut palette = HashMap::new();
+
+        duotone_accents(
+            &mut palette,
+            &SchemeSystem::Base24,
+            &SchemeVariant::Dark,
+            (first, second),
+        )
+        .unwrap();
+
+        assert_eq!(palette.len(), 16);
+
+        let hue = |slot: &str| {
+            let (red, green, blue) = palette[slot].rgb;
+
+            Lch::from_color(Srgb::new(red, green, blue).into_format::<f32>())
+                .hue
+                .into_positive_degrees()
+        };
+
+        for slot in ["base08", "base0A", "base0C", "base0E", "base10"] {
+            assert!((hue(slot) - first).abs() < 5.0, "{}", slot);
+        }
+
+        for slot in ["base09", "base0B", "base0D", "base0F", "base17"] {
+            assert!((hue(slot) - second).abs() < 5.0, "{}", slot);
+        }
+
+        assert_ne!(palette["base08"].rgb, palette["base0A"].rgb);
+    }
+}
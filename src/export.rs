@@ -0,0 +1,164 @@
+use tinted_builder::Base16Scheme;
+
+use crate::Error;
+#[cfg(feature = "exporters")]
+use crate::{
+    gtk::gtk_css,
+    hyprland::hyprland_colors,
+    neovim::neovim_colorscheme,
+    notification::{dunst_config, mako_config},
+    qt::qt_color_scheme,
+    rofi::rofi_theme,
+    tailwind::tailwind_config,
+    tmux::tmux_theme,
+    vscode::vscode_theme,
+    waybar::waybar_colors,
+    zed::zed_theme,
+    ExportFormat,
+};
+
+/// Render a scheme in an export format, e.g. a Tailwind CSS config, ready
+/// to be written to a file
+///
+/// # Arguments
+/// * `scheme` - A reference to the generated Base16Scheme
+/// * `format` - The format to render, e.g. parsed from its name with
+///   `ExportFormat::from_str`
+#[cfg(feature = "exporters")]
+pub fn export_scheme(scheme: &Base16Scheme, format: ExportFormat) -> Result<String, Error> {
+    match format {
+        ExportFormat::Tailwind => tailwind_config(scheme),
+        ExportFormat::VsCode => vscode_theme(scheme),
+        ExportFormat::Zed => zed_theme(scheme),
+        ExportFormat::Neovim => neovim_colorscheme(scheme),
+        ExportFormat::Tmux => tmux_theme(scheme),
+        ExportFormat::Rofi => rofi_theme(scheme),
+        ExportFormat::Dunst => dunst_config(scheme),
+        ExportFormat::Mako => mako_config(scheme),
+        ExportFormat::Gtk => gtk_css(scheme),
+        ExportFormat::Qt => qt_color_scheme(scheme),
+        ExportFormat::Hyprland => hyprland_colors(scheme),
+        ExportFormat::Waybar => waybar_colors(scheme),
+    }
+}
+
+/// Get the color of a slot as `#rrggbb`, failing for slots the scheme
+/// doesn't have
+pub(crate) fn slot_hex(scheme: &Base16Scheme, slot: &str) -> Result<String, Error> {
+    let (red, green, blue) = scheme
+        .palette
+        .get(slot)
+        .ok_or_else(|| Error::UnknownSlot {
+            slot: slot.to_string(),
+        })?
+        .rgb;
+
+    Ok(format!("#{:02x}{:02x}{:02x}", red, green, blue))
+}
+
+/// Quote text as a JSON string
+#[cfg(feature = "exporters")]
+pub(crate) fn json_string(text: &str) -> String {
+    let mut quoted = String::from('"');
+
+    for character in text.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            character if character.is_control() => {
+                quoted.push_str(&format!("\\u{:04x}", character as u32))
+            }
+            character => quoted.push(character),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+/// Put text on a single line, for names in comments
+pub(crate) fn single_line(text: &str) -> String {
+    text.split(['\r', '\n'])
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tinted_builder::{Color as SchemeColor, SchemeSystem, SchemeVariant};
+
+    /// A scheme with every slot of the system, the grays from black to
+    /// white and the accents from red to brown
+    pub(crate) fn test_scheme(system: SchemeSystem) -> Base16Scheme {
+        let hexes = [
+            "000000", "242424", "494949", "6d6d6d", "929292", "b6b6b6", "dbdbdb", "ffffff",
+            "ff0000", "ff8000", "ffff00", "00ff00", "00ffff", "0000ff", "8000ff", "804000",
+            "ff4040", "ffa040", "ffff40", "40ff40", "40ffff", "4040ff", "a040ff", "a06020",
+        ];
+        let count = match system {
+            SchemeSystem::Base24 => 24,
+            _ => 16,
+        };
+        let palette: HashMap<String, SchemeColor> = hexes[..count]
+            .iter()
+            .enumerate()
+            .map(|(index, hex)| {
+                (
+                    format!("base{:02X}", index),
+                    SchemeColor::new(hex.to_string()).unwrap(),
+                )
+            })
+            .collect();
+
+        Base16Scheme {
+            system,
+            name: "Test \"Scheme\"".to_string(),
+            slug: "test-scheme".to_string(),
+            author: "Author".to_string(),
+            description: None,
+            variant: SchemeVariant::Dark,
+            palette,
+        }
+    }
+
+    #[test]
+    fn test_export_helpers() {
+        let scheme = test_scheme(SchemeSystem::Base16);
+
+        assert_eq!(slot_hex(&scheme, "base0D").unwrap(), "#0000ff");
+        assert!(matches!(
+            slot_hex(&scheme, "base10"),
+            Err(Error::UnknownSlot { .. })
+        ));
+        assert_eq!(single_line("Wall\r\npaper\n"), "Wall paper");
+    }
+
+    #[test]
+    #[cfg(feature = "exporters")]
+    fn test_export_scheme() {
+        use std::str::FromStr;
+
+        let scheme = test_scheme(SchemeSystem::Base16);
+
+        assert_eq!(
+            json_string("Say \"hi\"\\\n\u{1}"),
+            r#""Say \"hi\"\\\n\u0001""#
+        );
+
+        for format in ExportFormat::ALL {
+            assert_eq!(ExportFormat::from_str(format.name()).unwrap(), format);
+            assert!(!export_scheme(&scheme, format).unwrap().is_empty());
+        }
+
+        assert!(matches!(
+            ExportFormat::from_str("word"),
+            Err(Error::UnsupportedExportFormat { .. })
+        ));
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+
+use image::{DynamicImage, Rgba, RgbaImage};
+use palette::{color_difference::Ciede2000, FromColor, Lab, Srgb};
+use tinted_builder::Base16Scheme;
+
+use crate::{extract_scheme_from_dynamic_image, Error, SchemeParams};
+
+/// The bits kept of each channel when counting a frame's colors
+const CHANNEL_BITS: u8 = 5;
+/// The largest side frames are shrunk to before their colors are counted
+const FRAME_SIZE: u32 = 256;
+/// The width of the image the running histogram is drawn as
+const HISTOGRAM_WIDTH: u32 = 256;
+/// How many pixels the running histogram is drawn as
+const HISTOGRAM_PIXELS: f32 = 65_536.0;
+/// Colors whose share of the running histogram falls below this are dropped
+const MIN_SHARE: f32 = 1e-5;
+
+/// A color of the running histogram
+#[derive(Clone, Copy, Debug, Default)]
+struct Bin {
+    /// The decayed share of the pixels with this color
+    weight: f32,
+    /// The weighted sums of those pixels' channels, for their mean color
+    red: f32,
+    green: f32,
+    blue: f32,
+}
+
+impl Bin {
+    fn mean(&self) -> Rgba<u8> {
+        let channel = |sum: f32| (sum / self.weight).round().clamp(0.0, 255.0) as u8;
+
+        Rgba([
+            channel(self.red),
+            channel(self.green),
+            channel(self.blue),
+            255,
+        ])
+    }
+}
+
+/// Generates schemes from a sequence of frames, e.g. a video or an animated
+/// wallpaper, without flickering between frames
+///
+/// Each frame's colors are counted into a running color histogram whose
+/// earlier counts decay exponentially, and the scheme is extracted from that
+/// histogram. A new scheme is only emitted once it has drifted far enough
+/// from the last emitted one. Options that depend on where pixels are in the
+/// frame, e.g. `center_weight` and `subject_bias`, don't apply.
+#[derive(Debug)]
+pub struct FrameExtractor {
+    params: SchemeParams,
+    decay: f32,
+    threshold: f32,
+    histogram: HashMap<u16, Bin>,
+    emitted_palette: HashMap<String, Lab>,
+}
+
+impl FrameExtractor {
+    /// Create a new frame extractor
+    ///
+    /// # Arguments
+    /// * `params` - The SchemeParams used for every frame. `image_path` is not
+    ///   read
+    /// * `decay` - How much of the running histogram is kept for each new
+    ///   frame, between 0.0 (only use the latest frame) and 1.0
+    /// * `threshold` - The largest CIEDE2000 delta E any slot may drift from
+    ///   the last emitted scheme before a new scheme is emitted
+    pub fn new(params: SchemeParams, decay: f32, threshold: f32) -> Self {
+        FrameExtractor {
+            params,
+            decay: decay.clamp(0.0, 1.0),
+            threshold: threshold.max(0.0),
+            histogram: HashMap::new(),
+            emitted_palette: HashMap::new(),
+        }
+    }
+
+    /// Add the next frame, returning a new scheme when the palette has
+    /// changed beyond the threshold. The first frame always returns a scheme
+    ///
+    /// # Arguments
+    /// * `frame` - A reference to the next decoded frame
+    pub fn push_frame(&mut self, frame: &DynamicImage) -> Result<Option<Base16Scheme>, Error> {
+        self.count_frame(frame);
+
+        let scheme =
+            extract_scheme_from_dynamic_image(&self.histogram_image(), self.params.clone())?.scheme;
+        let palette: HashMap<String, Lab> = scheme
+            .palette
+            .iter()
+            .map(|(slot, color)| {
+                let (red, green, blue) = color.rgb;
+
+                (
+                    slot.clone(),
+                    Lab::from_color(Srgb::new(red, green, blue).into_format::<f32>()),
+                )
+            })
+            .collect();
+        let changed = self.emitted_palette.is_empty()
+            || palette.iter().any(|(slot, lab)| {
+                self.emitted_palette
+                    .get(slot)
+                    .is_none_or(|emitted| emitted.difference(*lab) > self.threshold)
+            });
+
+        if !changed {
+            return Ok(None);
+        }
+
+        self.emitted_palette = palette;
+
+        Ok(Some(scheme))
+    }
+
+    /// Decay the running histogram and count a frame's colors into it. The
+    /// first frame fills it on its own
+    fn count_frame(&mut self, frame: &DynamicImage) {
+        let frame = frame.thumbnail(FRAME_SIZE, FRAME_SIZE).to_rgba8();
+        let pixels: Vec<&Rgba<u8>> = frame.pixels().filter(|pixel| pixel[3] > 0).collect();
+        let frame_weight = if self.histogram.is_empty() {
+            1.0
+        } else {
+            1.0 - self.decay
+        };
+        let pixel_weight = frame_weight / pixels.len().max(1) as f32;
+
+        for bin in self.histogram.values_mut() {
+            bin.weight *= self.decay;
+            bin.red *= self.decay;
+            bin.green *= self.decay;
+            bin.blue *= self.decay;
+        }
+
+        for pixel in pixels {
+            let [red, green, blue, _] = pixel.0;
+            let key = |channel: u8| (channel >> (8 - CHANNEL_BITS)) as u16;
+            let bin = self
+                .histogram
+                .entry(key(red) << (2 * CHANNEL_BITS) | key(green) << CHANNEL_BITS | key(blue))
+                .or_default();
+
+            bin.weight += pixel_weight;
+            bin.red += red as f32 * pixel_weight;
+            bin.green += green as f32 * pixel_weight;
+            bin.blue += blue as f32 * pixel_weight;
+        }
+
+        self.histogram.retain(|_, bin| bin.weight >= MIN_SHARE);
+    }
+
+    /// Draw the running histogram as an image, with each color covering its
+    /// share of the pixels
+    fn histogram_image(&self) -> DynamicImage {
+        let total: f32 = self.histogram.values().map(|bin| bin.weight).sum();
+        let mut bins: Vec<(&u16, &Bin)> = self.histogram.iter().collect();
+
+        bins.sort_by(|(a_key, a), (b_key, b)| b.weight.total_cmp(&a.weight).then(a_key.cmp(b_key)));
+
+        let mut image = Vec::new();
+
+        for (_, bin) in &bins {
+            let count = (bin.weight / total * HISTOGRAM_PIXELS).round() as usize;
+
+            image.extend((0..count).flat_map(|_| bin.mean().0));
+        }
+
+        // Fill the last row with the most common color
+        let row_bytes = HISTOGRAM_WIDTH as usize * 4;
+        let height = image.len().div_ceil(row_bytes);
+
+        if let Some((_, bin)) = bins.first() {
+            let padding = height * row_bytes - image.len();
+
+            image.extend(bin.mean().0.iter().cycle().take(padding));
+        }
+
+        RgbaImage::from_raw(HISTOGRAM_WIDTH, height as u32, image)
+            .map(DynamicImage::ImageRgba8)
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tinted_builder::{SchemeSystem, SchemeVariant};
+
+    const COLORS: [[u8; 3]; 5] = [
+        [20, 20, 30],
+        [200, 40, 40],
+        [40, 160, 60],
+        [50, 80, 200],
+        [220, 200, 180],
+    ];
+    const SHIFTED: [[u8; 3]; 5] = [
+        [30, 20, 20],
+        [220, 140, 40],
+        [160, 40, 160],
+        [40, 180, 180],
+        [180, 200, 220],
+    ];
+
+    fn extractor(decay: f32, threshold: f32) -> FrameExtractor {
+        let params = SchemeParams {
+            image_path: PathBuf::new(),
+            author: "Author".to_string(),
+            description: None,
+            name: "Frames".to_string(),
+            slug: "frames".to_string(),
+            system: SchemeSystem::Base16,
+            variant: SchemeVariant::Dark,
+            verbose: false,
+            options: Default::default(),
+        };
+
+        FrameExtractor::new(params, decay, threshold)
+    }
+
+    fn frame(colors: &[[u8; 3]]) -> DynamicImage {
+        DynamicImage::ImageRgba8(RgbaImage::from_fn(64, 64, |x, _| {
+            let [red, green, blue] = colors[x as usize * colors.len() / 64];
+
+            Rgba([red, green, blue, 255])
+        }))
+    }
+
+    fn share(extractor: &FrameExtractor, color: [u8; 3]) -> f32 {
+        extractor
+            .histogram
+            .values()
+            .filter(|bin| bin.mean().0[..3] == color)
+            .map(|bin| bin.weight)
+            .sum()
+    }
+
+    #[test]
+    fn test_histogram_decay() {
+        let mut extractor = extractor(0.75, 5.0);
+        let blue = [30, 60, 200];
+        let red = [200, 30, 40];
+
+        extractor.push_frame(&frame(&[blue])).unwrap();
+        assert!((share(&extractor, blue) - 1.0).abs() < 1e-4);
+
+        extractor.push_frame(&frame(&[red])).unwrap();
+        assert!((share(&extractor, blue) - 0.75).abs() < 1e-4);
+        assert!((share(&extractor, red) - 0.25).abs() < 1e-4);
+
+        extractor.push_frame(&frame(&[red])).unwrap();
+        assert!((share(&extractor, blue) - 0.5625).abs() < 1e-4);
+
+        let image = extractor.histogram_image().to_rgba8();
+        let blue_pixels = image.pixels().filter(|pixel| pixel.0[..3] == blue).count();
+
+        assert_eq!(image.width(), HISTOGRAM_WIDTH);
+        assert!((blue_pixels as f32 / HISTOGRAM_PIXELS - 0.5625).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_threshold() {
+        let mut extractor = extractor(0.0, 5.0);
+
+        assert!(extractor.push_frame(&frame(&COLORS)).unwrap().is_some());
+        assert!(extractor.push_frame(&frame(&COLORS)).unwrap().is_none());
+        assert!(extractor.push_frame(&frame(&SHIFTED)).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_threshold_decay() {
+        // Mid grays aren't the closest pixels to any pure color, so only
+        // their share of the histogram moves the scheme
+        let grays = [[110, 110, 110], [140, 140, 140]];
+        let mut extractor = extractor(0.0, 5.0);
+
+        assert!(extractor.push_frame(&frame(&COLORS)).unwrap().is_some());
+        assert!(extractor.push_frame(&frame(&grays)).unwrap().is_some());
+
+        // A new frame barely moves a histogram that keeps most of the old ones
+        let mut extractor = self::extractor(0.99, 5.0);
+
+        assert!(extractor.push_frame(&frame(&COLORS)).unwrap().is_some());
+        assert!(extractor.push_frame(&frame(&grays)).unwrap().is_none());
+    }
+}
@@ -0,0 +1,88 @@
+use palette::{convert::FromColorUnclamped, Clamp, FromColor, IsWithinBounds, Oklch, Srgb};
+
+/// How many times the chroma range is halved while searching for the most
+/// saturated color within sRGB, enough to be exact in 8-bit sRGB
+const SEARCH_STEPS: usize = 16;
+
+/// Convert a color to 8-bit sRGB, reducing its Oklch chroma until it fits
+/// within sRGB rather than clipping each channel, which shifts the hue of
+/// saturated colors near the edges of the gamut. The Oklch lightness and
+/// hue are kept
+///
+/// # Arguments
+/// * `color` - The color to convert, e.g. an `Lch` color whose lightness
+///   was adjusted
+pub(crate) fn to_srgb_within_gamut<C>(color: C) -> Srgb<u8>
+where
+    Oklch: FromColor<C>,
+{
+    let oklch = Oklch::from_color(color);
+    let with_chroma =
+        |chroma: f32| Srgb::from_color_unclamped(Oklch::new(oklch.l, chroma, oklch.hue));
+    let rgb = with_chroma(oklch.chroma);
+
+    if rgb.is_within_bounds() {
+        return rgb.into_format();
+    }
+
+    if oklch.l <= 0.0 || oklch.l >= 1.0 {
+        return rgb.clamp().into_format();
+    }
+
+    let (mut within, mut outside) = (0.0, oklch.chroma);
+
+    for _ in 0..SEARCH_STEPS {
+        let chroma = (within + outside) / 2.0;
+
+        if with_chroma(chroma).is_within_bounds() {
+            within = chroma;
+        } else {
+            outside = chroma;
+        }
+    }
+
+    // The search ends within rounding of the edge, so clamping only removes
+    // floating point error
+    with_chroma(within).clamp().into_format()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use palette::Lch;
+
+    fn hue_difference(a: Srgb<u8>, b: Oklch) -> f32 {
+        let hue = Oklch::from_color(a.into_format::<f32>())
+            .hue
+            .into_positive_degrees();
+        let difference = (hue - b.hue.into_positive_degrees()).rem_euclid(360.0);
+
+        difference.min(360.0 - difference)
+    }
+
+    #[test]
+    fn test_to_srgb_within_gamut() {
+        // A saturated red and blue, both lighter than sRGB can show at
+        // their chroma, which clipping turns towards magenta and cyan
+        let red = Oklch::new(0.7, 0.3, 10.0);
+        let blue = Oklch::new(0.75, 0.25, 250.0);
+
+        for color in [red, blue] {
+            let clipped: Srgb<u8> = Srgb::from_color_unclamped(color).clamp().into_format();
+            let within = to_srgb_within_gamut(color);
+            let lightness = Oklch::from_color(within.into_format::<f32>()).l;
+
+            assert!(hue_difference(clipped, color) > 2.0);
+            assert!(hue_difference(within, color) < 0.5);
+            assert!((lightness - color.l).abs() < 0.01);
+        }
+
+        // Colors within sRGB are unchanged
+        let orange = Srgb::new(200u8, 100, 50);
+
+        assert_eq!(
+            to_srgb_within_gamut(Lch::from_color(orange.into_format::<f32>())),
+            orange
+        );
+    }
+}
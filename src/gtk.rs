@@ -0,0 +1,118 @@
+use std::fmt::Write;
+
+use tinted_builder::Base16Scheme;
+
+use crate::{
+    export::{single_line, slot_hex},
+    Error,
+};
+
+/// The named colors of libadwaita for GTK4 and their slots
+const GTK4_COLORS: [(&str, &str); 38] = [
+    ("accent_color", "base0D"),
+    ("accent_bg_color", "base0D"),
+    ("accent_fg_color", "base00"),
+    ("destructive_color", "base08"),
+    ("destructive_bg_color", "base08"),
+    ("destructive_fg_color", "base00"),
+    ("success_color", "base0B"),
+    ("success_bg_color", "base0B"),
+    ("success_fg_color", "base00"),
+    ("warning_color", "base0A"),
+    ("warning_bg_color", "base0A"),
+    ("warning_fg_color", "base00"),
+    ("error_color", "base08"),
+    ("error_bg_color", "base08"),
+    ("error_fg_color", "base00"),
+    ("window_bg_color", "base01"),
+    ("window_fg_color", "base05"),
+    ("view_bg_color", "base00"),
+    ("view_fg_color", "base05"),
+    ("headerbar_bg_color", "base01"),
+    ("headerbar_fg_color", "base05"),
+    ("headerbar_border_color", "base02"),
+    ("headerbar_backdrop_color", "base00"),
+    ("headerbar_shade_color", "base02"),
+    ("sidebar_bg_color", "base01"),
+    ("sidebar_fg_color", "base05"),
+    ("sidebar_backdrop_color", "base00"),
+    ("sidebar_shade_color", "base02"),
+    ("card_bg_color", "base02"),
+    ("card_fg_color", "base05"),
+    ("card_shade_color", "base01"),
+    ("dialog_bg_color", "base01"),
+    ("dialog_fg_color", "base05"),
+    ("popover_bg_color", "base02"),
+    ("popover_fg_color", "base05"),
+    ("popover_shade_color", "base01"),
+    ("shade_color", "base00"),
+    ("scrollbar_outline_color", "base02"),
+];
+/// The named colors of GTK3 themes based on Adwaita and their slots
+const GTK3_COLORS: [(&str, &str); 13] = [
+    ("theme_bg_color", "base01"),
+    ("theme_fg_color", "base05"),
+    ("theme_base_color", "base00"),
+    ("theme_text_color", "base05"),
+    ("theme_selected_bg_color", "base0D"),
+    ("theme_selected_fg_color", "base00"),
+    ("insensitive_bg_color", "base01"),
+    ("insensitive_fg_color", "base03"),
+    ("borders", "base02"),
+    ("unfocused_borders", "base02"),
+    ("warning_color", "base0A"),
+    ("error_color", "base08"),
+    ("success_color", "base0B"),
+];
+
+/// Render `@define-color` lines overriding the named colors of GTK4's
+/// libadwaita and of GTK3's Adwaita, e.g. for `~/.config/gtk-4.0/gtk.css`
+/// and `~/.config/gtk-3.0/gtk.css`. Windows use base01, views base00 and
+/// the accent is base0D
+///
+/// # Arguments
+/// * `scheme` - A reference to the generated Base16Scheme
+pub fn gtk_css(scheme: &Base16Scheme) -> Result<String, Error> {
+    let mut css = format!(
+        "/* {} by {} */\n\n/* GTK4 and libadwaita */\n",
+        single_line(&scheme.name).replace("*/", "* /"),
+        single_line(&scheme.author).replace("*/", "* /")
+    );
+
+    for (name, slot) in GTK4_COLORS {
+        let _ = writeln!(css, "@define-color {} {};", name, slot_hex(scheme, slot)?);
+    }
+
+    css.push_str("\n/* GTK3 */\n");
+
+    // warning_color, error_color and success_color are the same in both
+    for (name, slot) in GTK3_COLORS
+        .iter()
+        .filter(|(name, _)| !GTK4_COLORS.iter().any(|(gtk4, _)| gtk4 == name))
+    {
+        let _ = writeln!(css, "@define-color {} {};", name, slot_hex(scheme, slot)?);
+    }
+
+    Ok(css)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::test_scheme;
+    use tinted_builder::SchemeSystem;
+
+    #[test]
+    fn test_gtk_css() {
+        let css = gtk_css(&test_scheme(SchemeSystem::Base16)).unwrap();
+
+        assert!(css.starts_with(
+            "/* Test \"Scheme\" by Author */\n\n/* GTK4 and libadwaita */\n@define-color accent_color #0000ff;\n"
+        ));
+        assert!(css.contains("@define-color window_bg_color #242424;\n"));
+        assert!(css.contains("@define-color view_bg_color #000000;\n"));
+        assert!(css.contains("\n/* GTK3 */\n@define-color theme_bg_color #242424;\n"));
+        assert_eq!(css.matches("@define-color warning_color ").count(), 1);
+        assert!(css.ends_with("@define-color unfocused_borders #494949;\n"));
+    }
+}
@@ -0,0 +1,73 @@
+use image::{
+    error::{DecodingError, ImageFormatHint},
+    ImageError, ImageResult, Limits, RgbaImage,
+};
+use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+/// The `ftyp` brands of HEIF files with HEVC images, e.g. iPhone photos
+const HEIF_BRANDS: [&[u8; 4]; 8] = [
+    b"heic", b"heix", b"hevc", b"hevx", b"heim", b"heis", b"mif1", b"msf1",
+];
+
+/// Whether data is a HEIC or HEIF file, which the image crate can't guess
+/// the format of
+pub(crate) fn is_heif(data: &[u8]) -> bool {
+    data.get(4..8) == Some(b"ftyp")
+        && data
+            .get(8..12)
+            .is_some_and(|brand| HEIF_BRANDS.iter().any(|heif| brand == *heif))
+}
+
+/// Decode the primary image of a HEIC or HEIF file with the system's
+/// libheif, with its rotation and cropping applied
+///
+/// # Arguments
+/// * `data` - The HEIF file's contents
+/// * `limits` - The decoding limits, enforced before decoding the pixels
+pub(crate) fn decode_heif(data: &[u8], limits: &Limits) -> ImageResult<RgbaImage> {
+    let context = HeifContext::read_from_bytes(data).map_err(heif_error)?;
+    let handle = context.primary_image_handle().map_err(heif_error)?;
+
+    limits.check_dimensions(handle.width(), handle.height())?;
+
+    let image = LibHeif::new()
+        .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgba), None)
+        .map_err(heif_error)?;
+    let plane = image
+        .planes()
+        .interleaved
+        .ok_or_else(|| heif_error("The HEIF image has no RGBA plane"))?;
+    let (width, height) = (plane.width, plane.height);
+
+    // Rows can be padded beyond the image's width
+    let pixels = plane
+        .data
+        .chunks(plane.stride)
+        .take(height as usize)
+        .flat_map(|row| &row[..width as usize * 4])
+        .copied()
+        .collect();
+
+    RgbaImage::from_raw(width, height, pixels)
+        .ok_or_else(|| heif_error("Unexpected HEIF plane size"))
+}
+
+fn heif_error(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> ImageError {
+    ImageError::Decoding(DecodingError::new(
+        ImageFormatHint::Name("HEIF".to_string()),
+        err,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_heif() {
+        assert!(is_heif(b"\0\0\0\x18ftypheic\0\0\0\0mif1heic"));
+        assert!(is_heif(b"\0\0\0\x1cftypmif1\0\0\0\0mif1heic"));
+        assert!(!is_heif(b"\0\0\0\x1cftypavif\0\0\0\0avifmif1"));
+        assert!(!is_heif(b"ftyp"));
+    }
+}
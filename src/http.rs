@@ -0,0 +1,230 @@
+use std::{
+    io::{Cursor, Read},
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+    thread,
+};
+
+use tinted_builder::Base16Scheme;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::{
+    export_scheme, extract_scheme_from_dynamic_images,
+    utils::{decode_image_frames, error_chain},
+    Error, ExportFormat, ExtractionOptions, OutputFormat, SchemeParams, SchemeSystem,
+    SchemeVariant,
+};
+
+/// The largest image body that is accepted
+const MAX_BODY_BYTES: u64 = 64 * 1024 * 1024;
+/// How many requests are handled at once, which bounds the memory used
+/// for bodies and decoding. Further requests wait for a worker
+const WORKERS: usize = 4;
+
+/// Serve `POST /extract` over HTTP, returning the scheme for the image sent
+/// as the request body
+///
+/// The scheme is described by the query string: `name`, `slug`, `author`,
+/// `description`, `system` (`base16` or `base24`, the default is `base16`),
+/// `variant` (`dark` or `light`, the default is `dark`) and `format` (`yaml`
+/// or `json`, the default is `yaml`), e.g.
+/// `POST /extract?name=Wall&slug=wall&author=Author&format=json`. Errors are
+/// returned as `{"error": "..."}`. Four requests are handled at once and
+/// the rest wait, so at most four bodies of up to 64MiB are decoded at a
+/// time. This only returns if the server can't be started.
+///
+/// # Arguments
+/// * `address` - The address to listen on, e.g. `127.0.0.1:8080`
+/// * `options` - The ExtractionOptions used for every request. Set `limits`
+///   when the server is reachable by untrusted clients
+pub fn serve_http(address: &str, options: ExtractionOptions) -> Result<(), Error> {
+    let server = Arc::new(Server::http(address).map_err(|source| Error::Http { source })?);
+    let workers: Vec<_> = (0..WORKERS)
+        .map(|_| {
+            let server = Arc::clone(&server);
+            let options = options.clone();
+
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    let options = options.clone();
+
+                    // Keep the worker when a request panics, e.g. in a decoder
+                    let _ =
+                        panic::catch_unwind(AssertUnwindSafe(|| handle_request(request, options)));
+                }
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        let _ = worker.join();
+    }
+
+    Ok(())
+}
+
+fn handle_request(mut request: Request, options: ExtractionOptions) {
+    let (path, query) = request.url().split_once('?').unwrap_or((request.url(), ""));
+    let response = match (request.method(), path) {
+        (Method::Post, "/extract") => {
+            let query = query.to_string();
+
+            match extract(&mut request, &query, options) {
+                Ok((scheme, format)) => scheme_response(&scheme, format),
+                Err((status, message)) => error_response(status, message),
+            }
+        }
+        (_, "/extract") => error_response(405, "Only POST is supported".to_string()),
+        _ => error_response(404, "Not found".to_string()),
+    };
+
+    // The client may have gone away, there's no one left to tell
+    let _ = request.respond(response);
+}
+
+/// Extract the scheme for a request, or get the status and message to fail with
+fn extract(
+    request: &mut Request,
+    query: &str,
+    options: ExtractionOptions,
+) -> Result<(Base16Scheme, OutputFormat), (u16, String)> {
+    let mut params = SchemeParams {
+        image_path: Default::default(),
+        author: String::new(),
+        description: None,
+        name: String::new(),
+        slug: String::new(),
+        system: SchemeSystem::Base16,
+        variant: SchemeVariant::Dark,
+        verbose: false,
+        options,
+    };
+    let mut format = OutputFormat::Yaml;
+
+    for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+        match key.as_ref() {
+            "author" => params.author = value.into_owned(),
+            "description" => params.description = Some(value.into_owned()),
+            "name" => params.name = value.into_owned(),
+            "slug" => params.slug = value.into_owned(),
+            "system" => {
+                params.system = SchemeSystem::from_str(&value)
+                    .map_err(|_| (400, format!("Unsupported system: {}", value)))?
+            }
+            "variant" => {
+                params.variant = SchemeVariant::from_str(&value)
+                    .map_err(|_| (400, format!("Unsupported variant: {}", value)))?
+            }
+            "format" => {
+                format = match value.as_ref() {
+                    "yaml" => OutputFormat::Yaml,
+                    "json" => OutputFormat::Json,
+                    name => OutputFormat::Export(
+                        ExportFormat::from_str(name)
+                            .map_err(|_| (400, format!("Unsupported format: {}", value)))?,
+                    ),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    params.validate().map_err(error_status)?;
+
+    let mut body = Vec::new();
+
+    request
+        .as_reader()
+        .take(MAX_BODY_BYTES + 1)
+        .read_to_end(&mut body)
+        .map_err(|err| (400, err.to_string()))?;
+
+    if body.len() as u64 > MAX_BODY_BYTES {
+        return Err((
+            413,
+            format!("Images over {} bytes aren't accepted", MAX_BODY_BYTES),
+        ));
+    }
+
+    let images = decode_image_frames(
+        Cursor::new(body),
+        Path::new("request body"),
+        None,
+        &params.options,
+    )
+    .map_err(error_status)?;
+
+    extract_scheme_from_dynamic_images(&images, params)
+        .map(|extraction| (extraction.scheme, format))
+        .map_err(error_status)
+}
+
+fn error_status(err: Error) -> (u16, String) {
+    let status = match err {
+        Error::EmptyName
+        | Error::InvalidSlug { .. }
+        | Error::UnsupportedSchemeSystem { .. }
+        | Error::UnsupportedSchemeVariant { .. }
+        | Error::SlotNotInSystem { .. }
+        | Error::InvalidCropRegion { .. }
+        | Error::InvalidGrid { .. } => 400,
+        Error::LimitsExceeded { .. } => 413,
+        Error::ImageDecode { .. }
+        | Error::CorruptImage { .. }
+        | Error::UnsupportedFormat { .. }
+        | Error::EmptyImage => 422,
+        _ => 500,
+    };
+
+    (status, error_chain(&err))
+}
+
+fn scheme_response(scheme: &Base16Scheme, format: OutputFormat) -> Response<Cursor<Vec<u8>>> {
+    let (body, content_type) = match format {
+        OutputFormat::Yaml => (Ok(scheme.to_string()), "application/yaml"),
+        OutputFormat::Json => (
+            serde_json::to_string(scheme).map_err(|err| err.to_string()),
+            "application/json",
+        ),
+        OutputFormat::Export(format) => (
+            export_scheme(scheme, format).map_err(|err| error_chain(&err)),
+            format.media_type(),
+        ),
+    };
+
+    match body {
+        Ok(body) => Response::from_string(body).with_header(content_type_header(content_type)),
+        Err(err) => error_response(500, err),
+    }
+}
+
+fn error_response(status: u16, message: String) -> Response<Cursor<Vec<u8>>> {
+    Response::from_string(serde_json::json!({ "error": message }).to_string())
+        .with_status_code(status)
+        .with_header(content_type_header("application/json"))
+}
+
+fn content_type_header(content_type: &str) -> Header {
+    Header::from_bytes("Content-Type", content_type).expect("content types are valid headers")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_status() {
+        assert_eq!(error_status(Error::EmptyName).0, 400);
+        assert_eq!(error_status(Error::EmptyImage).0, 422);
+        assert_eq!(
+            error_status(Error::Tinty {
+                message: String::new(),
+                source: None,
+            })
+            .0,
+            500
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use std::fmt::Write;
+
+use tinted_builder::Base16Scheme;
+
+use crate::{
+    adjust::scheme_slots,
+    export::{single_line, slot_hex},
+    Error,
+};
+
+/// The group options, with their values referencing the slot variables
+const GROUP: [(&str, &str); 4] = [
+    ("col.border_active", "$base0E $base0D 45deg"),
+    ("col.border_inactive", "$base03"),
+    ("col.border_locked_active", "$base08 $base09 45deg"),
+    ("col.border_locked_inactive", "$base02"),
+];
+/// The groupbar options, with their values referencing the slot variables
+const GROUPBAR: [(&str, &str); 5] = [
+    ("text_color", "$base05"),
+    ("col.active", "$base0D"),
+    ("col.inactive", "$base02"),
+    ("col.locked_active", "$base08"),
+    ("col.locked_inactive", "$base01"),
+];
+
+/// Render a Hyprland config snippet to `source` from `hyprland.conf`,
+/// defining a variable for every slot, a base0D to base0C gradient for the
+/// active border and the colors of groups
+///
+/// # Arguments
+/// * `scheme` - A reference to the generated Base16Scheme
+pub fn hyprland_colors(scheme: &Base16Scheme) -> Result<String, Error> {
+    let mut colors = format!(
+        "# {} by {}\n",
+        single_line(&scheme.name),
+        single_line(&scheme.author)
+    );
+
+    for slot in scheme_slots(&scheme.system) {
+        let hex = slot_hex(scheme, &slot)?;
+        let _ = writeln!(colors, "${} = rgb({})", slot, &hex[1..]);
+    }
+
+    colors.push_str(
+        "\ngeneral {\n    col.active_border = $base0D $base0C 45deg\n    col.inactive_border = $base02\n}\n\ngroup {\n",
+    );
+
+    for (option, value) in GROUP {
+        let _ = writeln!(colors, "    {} = {}", option, value);
+    }
+
+    colors.push_str("\n    groupbar {\n");
+
+    for (option, value) in GROUPBAR {
+        let _ = writeln!(colors, "        {} = {}", option, value);
+    }
+
+    colors.push_str("    }\n}\n");
+
+    Ok(colors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::test_scheme;
+    use tinted_builder::SchemeSystem;
+
+    #[test]
+    fn test_hyprland_colors() {
+        let colors = hyprland_colors(&test_scheme(SchemeSystem::Base24)).unwrap();
+
+        assert!(colors.starts_with("# Test \"Scheme\" by Author\n$base00 = rgb(000000)\n"));
+        assert!(colors.contains("$base0D = rgb(0000ff)\n"));
+        assert!(colors.contains("$base17 = rgb(a06020)\n"));
+        assert!(colors.contains("    col.active_border = $base0D $base0C 45deg\n"));
+        assert!(colors.contains("\n    groupbar {\n        text_color = $base05\n"));
+        assert!(colors.ends_with("        col.locked_inactive = $base01\n    }\n}\n"));
+    }
+}
@@ -0,0 +1,108 @@
+use std::io::Read;
+
+use image::{
+    error::{DecodingError, ImageFormatHint, LimitError, LimitErrorKind},
+    ImageError, ImageFormat, ImageResult, Limits, RgbaImage,
+};
+use jpeg_decoder::{Decoder, PixelFormat};
+
+/// Decode a JPEG at the smallest DCT scale, from 1/8 to full size, whose
+/// longer side is still at least `size`, which skips most of the inverse
+/// DCT and the full-size buffer a thumbnail would otherwise be made from
+///
+/// # Arguments
+/// * `reader` - The encoded JPEG
+/// * `size` - The length the longer side is scaled down towards
+/// * `limits` - The decoding limits, enforced before any pixels are decoded
+pub(crate) fn decode_scaled_jpeg<R: Read>(
+    reader: R,
+    size: u32,
+    limits: &Limits,
+) -> ImageResult<RgbaImage> {
+    let mut decoder = Decoder::new(reader);
+
+    decoder.read_info().map_err(jpeg_error)?;
+
+    let info = decoder
+        .info()
+        .ok_or_else(|| jpeg_error("Missing JPEG headers"))?;
+
+    limits.check_dimensions(u32::from(info.width), u32::from(info.height))?;
+
+    let requested = size.min(u32::from(u16::MAX)) as u16;
+    let (width, height) = decoder.scale(requested, requested).map_err(jpeg_error)?;
+    let (width, height) = (u32::from(width), u32::from(height));
+
+    if limits
+        .max_alloc
+        .is_some_and(|max_alloc| u64::from(width) * u64::from(height) * 4 > max_alloc)
+    {
+        return Err(ImageError::Limits(LimitError::from_kind(
+            LimitErrorKind::InsufficientMemory,
+        )));
+    }
+
+    let pixels = decoder.decode().map_err(jpeg_error)?;
+    let pixels = match info.pixel_format {
+        PixelFormat::L8 => pixels
+            .iter()
+            .flat_map(|&luma| [luma, luma, luma, 255])
+            .collect(),
+        PixelFormat::RGB24 => pixels
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+            .collect(),
+        PixelFormat::CMYK32 => pixels
+            .chunks_exact(4)
+            .flat_map(|cmyk| {
+                let k = 255 - u16::from(cmyk[3]);
+                let channel = |value: u8| ((255 - u16::from(value)) * k / 255) as u8;
+
+                [channel(cmyk[0]), channel(cmyk[1]), channel(cmyk[2]), 255]
+            })
+            .collect(),
+        // 12-bit and lossless JPEGs can't be scaled anyway, so they're left
+        // to the full decode
+        PixelFormat::L16 => return Err(jpeg_error("16-bit JPEGs aren't scaled")),
+    };
+
+    RgbaImage::from_raw(width, height, pixels)
+        .ok_or_else(|| jpeg_error("Unexpected JPEG buffer size"))
+}
+
+fn jpeg_error(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> ImageError {
+    ImageError::Decoding(DecodingError::new(
+        ImageFormatHint::Exact(ImageFormat::Jpeg),
+        err,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{codecs::jpeg::JpegEncoder, Rgb, RgbImage};
+
+    #[test]
+    fn test_decode_scaled_jpeg() {
+        let image = RgbImage::from_pixel(400, 200, Rgb([200, 40, 40]));
+        let mut data = Vec::new();
+
+        JpegEncoder::new_with_quality(&mut data, 90)
+            .encode_image(&image)
+            .unwrap();
+
+        // 1/4 is the smallest scale at least 100 pixels wide
+        let scaled = decode_scaled_jpeg(data.as_slice(), 100, &Limits::default()).unwrap();
+
+        assert_eq!(scaled.dimensions(), (100, 50));
+
+        let [red, green, blue, alpha] = scaled.get_pixel(50, 25).0;
+
+        assert!(red.abs_diff(200) < 8 && green.abs_diff(40) < 8 && blue.abs_diff(40) < 8);
+        assert_eq!(alpha, 255);
+
+        let full = decode_scaled_jpeg(data.as_slice(), 1000, &Limits::default()).unwrap();
+
+        assert_eq!(full.dimensions(), (400, 200));
+    }
+}
@@ -0,0 +1,52 @@
+use std::io::Cursor;
+
+use image::{
+    error::{DecodingError, ImageFormatHint},
+    DynamicImage, ImageDecoder, ImageError, ImageResult, Limits, RgbaImage,
+};
+use jxl_oxide::integration::JxlDecoder;
+
+/// The signature of a bare JPEG XL codestream
+const CODESTREAM_SIGNATURE: [u8; 2] = [0xff, 0x0a];
+/// The signature box that starts a JPEG XL container
+const CONTAINER_SIGNATURE: [u8; 12] = [
+    0x00, 0x00, 0x00, 0x0c, 0x4a, 0x58, 0x4c, 0x20, 0x0d, 0x0a, 0x87, 0x0a,
+];
+
+/// Whether data is a JPEG XL codestream or container, which the image crate
+/// can't guess the format of
+pub(crate) fn is_jxl(data: &[u8]) -> bool {
+    data.starts_with(&CODESTREAM_SIGNATURE) || data.starts_with(&CONTAINER_SIGNATURE)
+}
+
+/// Decode the first frame of a JPEG XL file with jxl-oxide
+///
+/// # Arguments
+/// * `data` - The JPEG XL file's contents
+/// * `limits` - The decoding limits, enforced before decoding the pixels
+pub(crate) fn decode_jxl(data: &[u8], limits: &Limits) -> ImageResult<RgbaImage> {
+    let mut decoder = JxlDecoder::new(Cursor::new(data)).map_err(jxl_error)?;
+
+    decoder.set_limits(limits.clone())?;
+
+    DynamicImage::from_decoder(decoder).map(DynamicImage::into_rgba8)
+}
+
+fn jxl_error(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> ImageError {
+    ImageError::Decoding(DecodingError::new(
+        ImageFormatHint::Name("JPEG XL".to_string()),
+        err,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_jxl() {
+        assert!(is_jxl(&[0xff, 0x0a, 0xfa, 0x1f]));
+        assert!(is_jxl(&CONTAINER_SIGNATURE));
+        assert!(!is_jxl(&[0xff, 0xd8, 0xff, 0xe0]));
+    }
+}
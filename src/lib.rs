@@ -1,46 +1,656 @@
+mod accessibility;
+mod adjust;
+mod batch;
+mod blend;
+#[cfg(feature = "cache")]
+mod cache;
 mod color;
+mod color_lut;
+mod confidence;
+#[cfg(all(feature = "daemon", unix))]
+mod daemon;
+#[cfg(feature = "zune-jpeg")]
+mod decoder;
+mod diversity;
+mod duotone;
+mod export;
+mod frames;
+mod gamut;
+#[cfg(feature = "exporters")]
+mod gtk;
+#[cfg(feature = "heif")]
+mod heif;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "exporters")]
+mod hyprland;
+#[cfg(feature = "jpeg")]
+mod jpeg;
+#[cfg(feature = "jxl")]
+mod jxl;
+mod metadata;
+#[cfg(feature = "exporters")]
+mod neovim;
+#[cfg(feature = "exporters")]
+mod notification;
+mod options;
+#[cfg(feature = "pdf")]
+mod pdf;
+mod phash;
+#[cfg(feature = "preview")]
+mod preview;
+mod provenance;
+#[cfg(feature = "pyo3")]
+mod python;
+#[cfg(feature = "exporters")]
+mod qt;
+mod random;
+#[cfg(feature = "raw")]
+mod raw;
+mod regions;
+mod report;
+#[cfg(feature = "exporters")]
+mod rofi;
+#[cfg(feature = "screen")]
+mod screen;
+mod single_color;
+mod superpixel;
+mod surface;
+#[cfg(feature = "svg")]
+mod svg;
+#[cfg(feature = "exporters")]
+mod tailwind;
+mod template;
+#[cfg(feature = "test-utils")]
+mod test_utils;
+mod tinty;
+#[cfg(feature = "exporters")]
+mod tmux;
+#[cfg(feature = "tui")]
+mod tui;
 mod utils;
+#[cfg(feature = "video")]
+mod video;
+#[cfg(feature = "exporters")]
+mod vscode;
+mod wallpaper;
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(feature = "exporters")]
+mod waybar;
+mod white_balance;
+mod xterm;
+#[cfg(feature = "exporters")]
+mod zed;
 
+use image::{DynamicImage, RgbaImage};
 use palette::{rgb::Rgb, FromColor, Hsl, Srgb};
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    path::PathBuf,
+    time::SystemTime,
+};
 use tinted_builder::{Base16Scheme, Color as SchemeColor};
 
 use crate::{
-    color::Color,
+    adjust::adjust_palette_color,
+    confidence::{flat_confidence, slot_confidence},
+    diversity::color_diversity,
+    duotone::{duotone_accents, duotone_hues},
+    metadata::scheme_description,
+    provenance::{flat_provenance, slot_provenance},
+    report::render_report,
+    single_color::{monochrome_color, single_color, single_color_palette},
     utils::{
-        create_palette_with_color_thief_colors, create_palette_with_inverse_colors, dark_color,
-        find_closest_palette, fix_colors, generate_gradient, light_color, load_image,
+        anchor_color, colors_in_images, constrain_accent_hues,
+        create_palette_with_color_thief_colors, create_palette_with_inverse_colors, dominant_hue,
+        fallback_palette, fill_missing_accents, fix_colors, generate_contrast_ramp,
+        generate_gradient, get_color_thief_palette, legible_yellows, load_image_frames,
+        lock_background, prepare_image, saturate_accents, scan_pixels, scheme_color,
+        scheme_color_from_srgb, second_dominant_color, separate_accents, subject_pixels,
+        tint_grays, without_skin_tones,
     },
+    white_balance::{color_cast, correct_white_balance, estimate_light},
 };
 
+pub use accessibility::{accessibility_report, AccessibilityReport, SlotAccessibility, WcagLevel};
+pub use adjust::{adjust_scheme_color, scheme_slots, HslAdjustment};
+pub use batch::{
+    create_deduplicated_schemes_from_images, create_schemes_from_images, DeduplicatedBatch,
+    Duplicate,
+};
+pub use blend::{extract_scheme_from_weighted_dynamic_images, extract_scheme_from_weighted_images};
+#[cfg(feature = "cache")]
+pub use cache::extraction_cache_dir;
+pub use color::{Color, PureColor};
+#[cfg(all(feature = "daemon", unix))]
+pub use daemon::{serve_unix_socket, DaemonRequest, DaemonResponse};
+pub use diversity::ColorDiversity;
+#[cfg(feature = "exporters")]
+pub use export::export_scheme;
+pub use frames::FrameExtractor;
+#[cfg(feature = "exporters")]
+pub use gtk::gtk_css;
+#[cfg(feature = "http")]
+pub use http::serve_http;
+#[cfg(feature = "exporters")]
+pub use hyprland::hyprland_colors;
+pub use image;
+#[cfg(feature = "exporters")]
+pub use neovim::neovim_colorscheme;
+#[cfg(feature = "exporters")]
+pub use notification::{dunst_config, mako_config};
+#[cfg(feature = "exporters")]
+pub use options::ExportFormat;
+#[cfg(feature = "video")]
+pub use options::VideoFrame;
+pub use options::{
+    AnchorSelector, Base0FPolicy, ColorPass, CropRegion, DecodeLimits, Denoise, ExtractionOptions,
+    FrameSelection, HueRange, InverseColors, OutputFormat, ReportFormat, SubjectBias,
+    VariantProfile, WhiteBalance, DEFAULT_ACCENT_HUE_RANGES, DEFAULT_CONTRAST_RAMP,
+    DEFAULT_DARK_LADDER, DEFAULT_LIGHT_LADDER, DEFAULT_MAX_COLOR_DISTANCE, DEFAULT_MAX_DELTA_E,
+};
+pub use palette;
+pub use phash::perceptual_hash;
+#[cfg(feature = "preview")]
+pub use preview::{render_preview, PreviewProtocol};
+pub use provenance::{scheme_yaml_with_provenance, SlotProvenance};
+#[cfg(feature = "exporters")]
+pub use qt::qt_color_scheme;
+pub use random::{pick_random_image, RandomImageFilter};
+pub use regions::{extract_schemes_by_region, RegionExtraction, RegionExtractions, RegionLayout};
+pub use report::quality_report;
+#[cfg(feature = "exporters")]
+pub use rofi::rofi_theme;
+#[cfg(feature = "screen")]
+pub use screen::{capture_screen, ScreenRegion};
+pub use surface::{surface_colors, SurfaceColors};
+#[cfg(feature = "exporters")]
+pub use tailwind::tailwind_config;
+pub use template::{render_template, template_context};
+#[cfg(feature = "test-utils")]
+pub use test_utils::{
+    assert_golden_scheme, flat_image, gradient_image, noise_image, UPDATE_GOLDEN_VAR,
+};
 pub use tinted_builder::{SchemeSystem, SchemeVariant};
+pub use tinty::{
+    apply_with_tinty, tinty_custom_schemes_dir, tinty_data_dir, write_tinty_custom_scheme,
+    TintyScheme,
+};
+#[cfg(feature = "exporters")]
+pub use tmux::tmux_theme;
+#[cfg(feature = "tui")]
+pub use tui::edit_scheme;
+#[cfg(feature = "exporters")]
+pub use vscode::vscode_theme;
+pub use wallpaper::{
+    current_wallpaper, extract_schemes_by_output, output_wallpapers, OutputExtraction,
+    OutputWallpaper,
+};
+#[cfg(feature = "exporters")]
+pub use waybar::waybar_colors;
+pub use xterm::{ansi_slots, nearest_xterm_index, xterm_color, xterm_palette, XtermPalette};
+#[cfg(feature = "exporters")]
+pub use zed::zed_theme;
 
 #[non_exhaustive]
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
-    #[error("no colors")]
-    NoColors(String),
-    #[error("generate colors")]
-    GenerateColors(String),
-    #[error("unsupported scheme variant")]
-    UnsupportedSchemeVariant(String),
-    #[error("other")]
-    Other(String),
+    #[error("cache: {message}")]
+    Cache {
+        message: String,
+        #[source]
+        source: Option<std::io::Error>,
+    },
+    #[error("no colors found in the images")]
+    NoColors,
+    #[error("no images to extract colors from")]
+    NoImages,
+    #[error("no matching images in {}", dir.display())]
+    NoImagesInDir {
+        dir: PathBuf,
+        #[source]
+        source: Option<std::io::Error>,
+    },
+    #[error("empty image")]
+    EmptyImage,
+    #[error("image decode: {}", path.display())]
+    ImageDecode {
+        path: PathBuf,
+        #[source]
+        source: image::ImageError,
+    },
+    #[error("corrupt image: {}", path.display())]
+    CorruptImage {
+        path: PathBuf,
+        #[source]
+        source: image::ImageError,
+    },
+    #[error("image decoding limits exceeded: {}", path.display())]
+    LimitsExceeded {
+        path: PathBuf,
+        #[source]
+        source: image::ImageError,
+    },
+    #[error("unsupported image format: {}", path.display())]
+    UnsupportedFormat {
+        path: PathBuf,
+        #[source]
+        source: image::ImageError,
+    },
+    #[cfg(feature = "color-thief")]
+    #[error("color thief")]
+    ColorThief {
+        #[source]
+        source: color_thief::Error,
+    },
+    #[error("invalid hex color: {value}")]
+    InvalidHex { value: String },
+    #[error("unsupported scheme variant: {variant}")]
+    UnsupportedSchemeVariant { variant: String },
+    #[error("unsupported scheme system: {system}")]
+    UnsupportedSchemeSystem { system: String },
+    #[error("unsupported export format: {name}")]
+    UnsupportedExportFormat { name: String },
+    #[error("empty scheme name")]
+    EmptyName,
+    #[error("invalid slug: {slug}")]
+    InvalidSlug { slug: String },
+    #[cfg(feature = "screen")]
+    #[error("screen capture")]
+    ScreenCapture {
+        #[source]
+        source: xcap::XCapError,
+    },
+    #[cfg(feature = "screen")]
+    #[error("capture region at {},{} is outside of the monitor", region.x, region.y)]
+    CaptureRegionOutsideMonitor { region: ScreenRegion },
+    #[cfg(feature = "screen")]
+    #[error("no monitors found")]
+    NoMonitors,
+    #[error("wallpaper detection: {message}")]
+    Wallpaper {
+        message: String,
+        #[source]
+        source: Option<std::io::Error>,
+    },
+    #[error("tinty: {message}")]
+    Tinty {
+        message: String,
+        #[source]
+        source: Option<std::io::Error>,
+    },
+    #[error("daemon: {message}")]
+    Daemon {
+        message: String,
+        #[source]
+        source: Option<std::io::Error>,
+    },
+    #[error("http server")]
+    Http {
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    #[error("unable to render template")]
+    Template {
+        #[source]
+        source: tinted_builder::TintedBuilderError,
+    },
+    #[error("tui")]
+    Tui {
+        #[source]
+        source: std::io::Error,
+    },
+    #[error(
+        "crop region {}x{} at {},{} doesn't fit in a {width}x{height} image",
+        region.width,
+        region.height,
+        region.x,
+        region.y
+    )]
+    InvalidCropRegion {
+        region: CropRegion,
+        width: u32,
+        height: u32,
+    },
+    #[error("a {columns}x{rows} grid doesn't fit in a {width}x{height} image")]
+    InvalidGrid {
+        columns: u32,
+        rows: u32,
+        width: u32,
+        height: u32,
+    },
+    #[error("{weight} isn't a positive image weight")]
+    InvalidWeight { weight: f32 },
+    #[error("pixel buffer of {len} bytes doesn't match a {width}x{height} RGBA image")]
+    InvalidPixelBuffer { width: u32, height: u32, len: usize },
+    #[error("unknown slot: {slot}")]
+    UnknownSlot { slot: String },
+    #[error("{slot} isn't an accent")]
+    NotAnAccent { slot: String },
+    #[error("{slot} isn't a {system} slot")]
+    SlotNotInSystem { slot: String, system: SchemeSystem },
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SchemeParams {
+    #[cfg_attr(feature = "serde", serde(default))]
     pub image_path: PathBuf,
     pub author: String,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub description: Option<String>,
     pub name: String,
     pub slug: String,
     pub system: SchemeSystem,
     pub variant: SchemeVariant,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub verbose: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub options: ExtractionOptions,
+}
+
+impl SchemeParams {
+    /// Fill in a blank `author` from the environment: the
+    /// `TINTED_SCHEME_EXTRACTOR_AUTHOR` variable, then `GIT_AUTHOR_NAME`,
+    /// then git's `user.name`. The author is left blank when none are set
+    pub fn with_author_from_env(mut self) -> Self {
+        if self.author.trim().is_empty() {
+            if let Some(author) = metadata::author_from_env() {
+                self.author = author;
+            }
+        }
+
+        self
+    }
+
+    /// Set a missing `description` to one saying when, and from which
+    /// image, the scheme was generated, e.g. `Generated from wall.png on
+    /// 2024-06-01 08:30:00 UTC`
+    pub fn with_timestamped_description(mut self) -> Self {
+        if self.description.is_none() {
+            let timestamp = metadata::utc_timestamp(SystemTime::now());
+            let description = match self.image_path.file_name() {
+                Some(file_name) => format!(
+                    "Generated from {} on {}",
+                    file_name.to_string_lossy(),
+                    timestamp
+                ),
+                None => format!("Generated on {}", timestamp),
+            };
+
+            self.description = Some(description);
+        }
+
+        self
+    }
+
+    /// Check the params without doing any image work. This is done by every
+    /// extraction function, but can be called early to report mistakes
+    /// before an image is picked or captured
+    ///
+    /// The name must not be blank, the slug must only contain lowercase
+    /// ASCII letters, digits and inner hyphens, and only the Base16 and
+    /// Base24 systems and the dark and light variants are supported. Every
+    /// slot in `ExtractionOptions::slot_adjustments` must be part of the
+    /// system
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.name.trim().is_empty() {
+            return Err(Error::EmptyName);
+        }
+
+        let is_valid_slug = !self.slug.is_empty()
+            && !self.slug.starts_with('-')
+            && !self.slug.ends_with('-')
+            && self
+                .slug
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+
+        if !is_valid_slug {
+            return Err(Error::InvalidSlug {
+                slug: self.slug.clone(),
+            });
+        }
+
+        match &self.system {
+            SchemeSystem::Base16 | SchemeSystem::Base24 => {}
+            system => {
+                return Err(Error::UnsupportedSchemeSystem {
+                    system: system.to_string(),
+                })
+            }
+        }
+
+        let slots = scheme_slots(&self.system);
+
+        if let Some(slot) = self
+            .options
+            .slot_adjustments
+            .keys()
+            .find(|slot| !slots.contains(slot))
+        {
+            return Err(Error::SlotNotInSystem {
+                slot: slot.clone(),
+                system: self.system.clone(),
+            });
+        }
+
+        match &self.variant {
+            SchemeVariant::Dark | SchemeVariant::Light => Ok(()),
+            variant => Err(Error::UnsupportedSchemeVariant {
+                variant: variant.to_string(),
+            }),
+        }
+    }
+}
+
+/// Information about how a scheme was extracted, for tools that want to
+/// report on it rather than parse `verbose` output
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostics {
+    /// Number of passes needed to find the light anchor color, or 0 when it
+    /// was picked by the `AnchorSelector`
+    pub light_passes: u32,
+    /// Number of passes needed to find the dark anchor color, or 0 when it
+    /// was picked by the `AnchorSelector`
+    pub dark_passes: u32,
+    /// The pass of the light ladder that matched, or `None` when the most
+    /// dominant color was used
+    pub light_pass: Option<ColorPass>,
+    /// The pass of the dark ladder that matched, or `None` when the most
+    /// dominant color was used
+    pub dark_pass: Option<ColorPass>,
+    /// Whether color_thief failed, found too few colors or is disabled, so
+    /// the palette was built from the pixels closest to each pure color
+    /// instead
+    pub color_thief_fallback: bool,
+    /// Whether the images are a single flat color, so the documented
+    /// single-color palette was used instead of the usual heuristics
+    pub single_color: bool,
+    /// Whether the images are grayscale or have almost no color, so the
+    /// single-color palette was used with their average gray
+    pub monochrome: bool,
+    /// Whether the images' hue entropy is below
+    /// `ExtractionOptions::min_hue_entropy`, so the single-color palette
+    /// was used with their mean color
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub low_diversity: bool,
+    /// How colorful the images are
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub diversity: ColorDiversity,
+    /// The color of the light neutralized by
+    /// `ExtractionOptions::white_balance`, with its brightest channel at
+    /// 255, or `None` without white balance
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub color_cast: Option<Srgb<u8>>,
+    /// The hue of the image's most dominant noticeably colored color, in
+    /// degrees
+    pub dominant_hue: Option<f32>,
+    /// How much every slot can be trusted, from 0.0 to 1.0, based on the
+    /// ladder passes, how close and how dominant each accent's color is and
+    /// whether color_thief was used. Accents that had to be synthesized
+    /// have 0.0
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub confidence: BTreeMap<String, f32>,
+    /// Where the color of every slot came from, for YAML comments from
+    /// `scheme_yaml_with_provenance`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub provenance: BTreeMap<String, SlotProvenance>,
+}
+
+/// The labelled summary printed by `verbose`
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Light passes: {}", self.light_passes)?;
+        writeln!(f, "Dark passes: {}", self.dark_passes)?;
+        writeln!(f, "color_thief fallback: {}", self.color_thief_fallback)?;
+
+        match self.dominant_hue {
+            Some(hue) => write!(f, "Dominant hue: {:.0}°", hue),
+            None => write!(f, "Dominant hue: none"),
+        }
+    }
+}
+
+/// A generated scheme along with its extraction diagnostics
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Extraction {
+    pub scheme: Base16Scheme,
+    pub diagnostics: Diagnostics,
+    /// The UI surface colors, when `ExtractionOptions::surfaces` is set
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub surfaces: Option<SurfaceColors>,
+    /// The contrast of every slot, when
+    /// `ExtractionOptions::accessibility_report` is set
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub accessibility: Option<AccessibilityReport>,
+    /// A human-readable report of the extraction, when
+    /// `ExtractionOptions::report` is set
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub report: Option<String>,
 }
 
 pub fn create_scheme_from_image(params: SchemeParams) -> Result<Base16Scheme, Error> {
+    extract_scheme_from_image(params).map(|extraction| extraction.scheme)
+}
+
+/// Create a scheme from an image that has already been decoded, e.g. one
+/// returned by `capture_screen`. `params.image_path` is not read.
+pub fn create_scheme_from_dynamic_image(
+    image: &DynamicImage,
+    params: SchemeParams,
+) -> Result<Base16Scheme, Error> {
+    extract_scheme_from_dynamic_image(image, params).map(|extraction| extraction.scheme)
+}
+
+/// Create a scheme from raw RGBA pixels, e.g. from a browser canvas, without
+/// any file or decoder involved. `params.image_path` is not read.
+///
+/// # Arguments
+/// * `width` - The width of the image in pixels
+/// * `height` - The height of the image in pixels
+/// * `pixels` - Row-major RGBA bytes, 4 per pixel
+/// * `params` - The SchemeParams to create the scheme with
+pub fn create_scheme_from_rgba(
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    params: SchemeParams,
+) -> Result<Base16Scheme, Error> {
+    let len = pixels.len();
+    let image = RgbaImage::from_raw(width, height, pixels)
+        .filter(|_| len as u64 == width as u64 * height as u64 * 4)
+        .ok_or(Error::InvalidPixelBuffer { width, height, len })?;
+
+    create_scheme_from_dynamic_image(&DynamicImage::ImageRgba8(image), params)
+}
+
+/// Like `create_scheme_from_image`, but also returns extraction diagnostics
+pub fn extract_scheme_from_image(params: SchemeParams) -> Result<Extraction, Error> {
+    let image_paths = [params.image_path.clone()];
+
+    extract_scheme_from_images(&image_paths, params)
+}
+
+/// Like `create_scheme_from_dynamic_image`, but also returns extraction
+/// diagnostics
+pub fn extract_scheme_from_dynamic_image(
+    image: &DynamicImage,
+    params: SchemeParams,
+) -> Result<Extraction, Error> {
+    extract_scheme(&[image], params)
+}
+
+/// Create a single scheme from the combined pixels of several images, e.g.
+/// a multi-monitor wallpaper set. `params.image_path` is not read.
+pub fn create_scheme_from_images(
+    image_paths: &[PathBuf],
+    params: SchemeParams,
+) -> Result<Base16Scheme, Error> {
+    extract_scheme_from_images(image_paths, params).map(|extraction| extraction.scheme)
+}
+
+/// Like `create_scheme_from_images`, but also returns extraction diagnostics
+pub fn extract_scheme_from_images(
+    image_paths: &[PathBuf],
+    params: SchemeParams,
+) -> Result<Extraction, Error> {
+    params.validate()?;
+
+    let options = params.options.clone();
+
+    extract_scheme_from_loaded_images(image_paths, params, || {
+        Ok(image_paths
+            .iter()
+            .map(|path| load_image_frames(path, &options))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flatten()
+            .collect())
+    })
+}
+
+/// Extract a scheme from the images `load` decodes from `image_paths`, or
+/// from the cache, which may not need them decoded
+#[cfg_attr(not(feature = "cache"), allow(unused_variables))]
+pub(crate) fn extract_scheme_from_loaded_images<F>(
+    image_paths: &[PathBuf],
+    params: SchemeParams,
+    load: F,
+) -> Result<Extraction, Error>
+where
+    F: FnOnce() -> Result<Vec<DynamicImage>, Error>,
+{
+    #[cfg(feature = "cache")]
+    if params.options.cache
+        && params.options.anchor_selector.is_none()
+        && params.options.report.is_none()
+    {
+        return cache::extract_scheme_cached(image_paths, params, load);
+    }
+
+    extract_scheme_from_dynamic_images(&load()?, params)
+}
+
+/// Like `extract_scheme_from_images`, but with images that have already been
+/// decoded
+pub fn extract_scheme_from_dynamic_images(
+    images: &[DynamicImage],
+    params: SchemeParams,
+) -> Result<Extraction, Error> {
+    let images: Vec<&DynamicImage> = images.iter().collect();
+
+    extract_scheme(&images, params)
+}
+
+fn extract_scheme(images: &[&DynamicImage], params: SchemeParams) -> Result<Extraction, Error> {
+    params.validate()?;
+
     let SchemeParams {
         image_path,
         author,
@@ -50,168 +660,397 @@ pub fn create_scheme_from_image(params: SchemeParams) -> Result<Base16Scheme, Er
         system,
         variant,
         verbose,
+        options,
     } = params;
-    let image = load_image(&image_path);
-    let initial_palette: Vec<Color> = find_closest_palette(&image);
-    let inital_inverse_palette: Vec<Color> = find_closest_palette(&image)
+
+    if images.is_empty() {
+        return Err(Error::NoImages);
+    }
+
+    let is_grayscale = images.iter().all(|image| !image.color().has_color());
+    let images: Vec<Cow<RgbaImage>> = images
         .iter()
-        .map(|color| color.get_inverse())
-        .collect();
-    let curated_palette =
-        create_palette_with_inverse_colors(&initial_palette, &inital_inverse_palette);
-    let color_thief_palette: Vec<Srgb<u8>> = color_thief::get_palette(
-        image.to_rgba8().into_raw().as_slice(),
-        color_thief::ColorFormat::Rgba,
-        1,
-        15,
-    )
-    .map_err(|err| Error::GenerateColors(err.to_string()))?
-    .iter()
-    .map(|c| Srgb::new(c.r, c.g, c.b))
-    .collect();
-    let combined_palette =
-        create_palette_with_color_thief_colors(&curated_palette, &color_thief_palette)?;
+        .map(|image| prepare_image(image, &options))
+        .collect::<Result<_, _>>()?;
+    let light = options
+        .white_balance
+        .and_then(|method| estimate_light(&images, method));
+    let images = match light {
+        Some(light) => images
+            .iter()
+            .map(|image| Cow::Owned(correct_white_balance(image, light)))
+            .collect(),
+        None => images,
+    };
+    let color_cast = light.map(color_cast);
+    let (diversity, mean_color) = color_diversity(&images);
+    let flat_color = match single_color(&images) {
+        Some(color) => Some((
+            color,
+            Diagnostics {
+                single_color: true,
+                ..Diagnostics::default()
+            },
+        )),
+        None => monochrome_color(&images, is_grayscale)
+            .map(|color| {
+                (
+                    color,
+                    Diagnostics {
+                        monochrome: true,
+                        ..Diagnostics::default()
+                    },
+                )
+            })
+            .or_else(|| {
+                options
+                    .min_hue_entropy
+                    .filter(|min_hue_entropy| diversity.hue_entropy < *min_hue_entropy)
+                    .map(|_| {
+                        (
+                            mean_color,
+                            Diagnostics {
+                                low_diversity: true,
+                                ..Diagnostics::default()
+                            },
+                        )
+                    })
+            }),
+    };
+
+    if let Some((color, mut diagnostics)) = flat_color {
+        let mut palette =
+            single_color_palette(color, &system, &variant, options.base0f.pure_color())?;
+
+        diagnostics.dominant_hue = dominant_hue(&[color]);
+        diagnostics.diversity = diversity;
+        diagnostics.color_cast = color_cast;
+
+        if let (true, Some(hue)) = (options.duotone, diagnostics.dominant_hue) {
+            duotone_accents(&mut palette, &system, &variant, duotone_hues(hue, &[]))?;
+        }
+
+        if let Some(background) = options.background {
+            let (red, green, blue) = palette["base07"].rgb;
+
+            lock_background(
+                &mut palette,
+                background,
+                Srgb::new(red, green, blue),
+                options.contrast_ramp.as_ref(),
+            );
+        }
+
+        finish_palette(&mut palette, &system, &diagnostics, &options)?;
+        diagnostics.confidence = flat_confidence(&palette);
+        diagnostics.provenance = flat_provenance(&palette, &diagnostics.confidence);
+
+        let description =
+            scheme_description(description, &image_path, &diagnostics, &variant, &options);
+        let scheme = Base16Scheme {
+            author,
+            description,
+            name,
+            slug,
+            palette,
+            system,
+            variant,
+        };
+
+        return finish_extraction(
+            scheme,
+            diagnostics,
+            &options,
+            images.first().map(AsRef::as_ref),
+        );
+    }
+
+    let scan = scan_pixels(&images, &options);
+    let initial_palette: Vec<Color> = scan.closest_palette;
+    let inital_inverse_palette: Vec<Color> = match options.inverse_colors {
+        InverseColors::Always => initial_palette
+            .iter()
+            .map(|color| color.get_inverse())
+            .collect(),
+        InverseColors::Never => Vec::new(),
+        InverseColors::Present(max_distance) => colors_in_images(
+            &images,
+            initial_palette.iter().map(|color| color.get_inverse()),
+            max_distance,
+            options.sample_every,
+        ),
+    };
+    let curated_palette = create_palette_with_inverse_colors(
+        &initial_palette,
+        &inital_inverse_palette,
+        options.max_color_distance,
+    );
+    let color_thief_palette = get_color_thief_palette(&scan.color_thief_pixels);
+    let color_thief_fallback = color_thief_palette.is_none();
+    let color_thief_palette =
+        color_thief_palette.unwrap_or_else(|| fallback_palette(&initial_palette));
+    // The dominant colors of the subject, when the accents should prefer
+    // them, and without most skin tones, when they should be left out
+    let accent_pixels = options
+        .subject_bias
+        .map(|bias| subject_pixels(&images, bias));
+    let accent_pixels = match options.skin_tone_weight {
+        Some(weight) => Some(without_skin_tones(
+            accent_pixels.as_deref().unwrap_or(&scan.color_thief_pixels),
+            weight,
+        )),
+        None => accent_pixels,
+    };
+    let subject_palette = accent_pixels.and_then(|pixels| get_color_thief_palette(&pixels));
+    let accent_palette = subject_palette.as_deref().unwrap_or(&color_thief_palette);
+    let combined_palette = create_palette_with_color_thief_colors(
+        &curated_palette,
+        accent_palette,
+        options.max_delta_e,
+    )?;
     let color_thief_pallette_as_rgb_vec: Vec<Rgb> = color_thief_palette
-        .clone()
         .iter()
-        .map(|c| {
-            Rgb::new(
-                c.red as f32 / 255.0,
-                c.green as f32 / 255.0,
-                c.blue as f32 / 255.0,
-            )
-        })
+        .map(|c| c.into_format())
         .collect();
-    let light = light_color(&color_thief_pallette_as_rgb_vec, verbose)?;
-    let dark = dark_color(&color_thief_pallette_as_rgb_vec, verbose)?;
+    let selector = options.anchor_selector.as_deref();
+    let (light, light_passes, light_pass) =
+        match selector.and_then(|selector| selector.light(&color_thief_palette)) {
+            Some(light) => (light.into_format(), 0, None),
+            None => anchor_color(
+                &color_thief_pallette_as_rgb_vec,
+                options.light_ladder_or_default(),
+            )?,
+        };
+    let (dark, dark_passes, dark_pass) =
+        match selector.and_then(|selector| selector.dark(&color_thief_palette)) {
+            Some(dark) => (dark.into_format(), 0, None),
+            None => anchor_color(
+                &color_thief_pallette_as_rgb_vec,
+                options.dark_ladder_or_default(),
+            )?,
+        };
+    let mut diagnostics = Diagnostics {
+        light_passes,
+        dark_passes,
+        light_pass,
+        dark_pass,
+        color_thief_fallback,
+        single_color: false,
+        monochrome: false,
+        low_diversity: false,
+        diversity,
+        color_cast,
+        dominant_hue: dominant_hue(&color_thief_palette),
+        confidence: BTreeMap::new(),
+        provenance: BTreeMap::new(),
+    };
+
+    let profile = options
+        .profile
+        .unwrap_or_else(|| VariantProfile::for_variant(&variant));
     let (background, foreground) = match &variant {
-        SchemeVariant::Dark | SchemeVariant::Light => Ok(fix_colors(dark, light, &variant)),
-        variant => Err(Error::UnsupportedSchemeVariant(variant.to_string())),
+        SchemeVariant::Dark | SchemeVariant::Light => {
+            Ok(fix_colors(dark, light, &variant, &profile))
+        }
+        variant => Err(Error::UnsupportedSchemeVariant {
+            variant: variant.to_string(),
+        }),
     }?;
-    let gradient = generate_gradient(Srgb::from(background), Srgb::from(foreground), 8);
+    let (background, foreground) = (Srgb::from(background), Srgb::from(foreground));
+    let gradient = match &options.contrast_ramp {
+        Some(targets) => generate_contrast_ramp(background, foreground, targets),
+        None => generate_gradient(background, foreground, 8),
+    };
 
     let mut scheme_palette: HashMap<String, SchemeColor> = HashMap::new();
 
     for (index, rgb) in gradient.iter().enumerate() {
-        scheme_palette.entry(format!("base0{}", index)).or_insert(
-            SchemeColor::new(format!("{:02X}{:02X}{:02X}", rgb.red, rgb.green, rgb.blue))
-                .map_err(|err| Error::GenerateColors(err.to_string()))?,
+        scheme_palette
+            .entry(format!("base0{}", index))
+            .or_insert(scheme_color_from_srgb(*rgb));
+    }
+
+    if let Some(background) = options.background {
+        lock_background(
+            &mut scheme_palette,
+            background,
+            gradient[7],
+            options.contrast_ramp.as_ref(),
         );
     }
 
+    let base0f_color = options.base0f.pure_color();
+    // Move the extracted accents towards the profile's visibility, and
+    // desaturate them for Base24's bright accents, unless they're kept as
+    // extracted
+    let visible_accent = |color: &Color| {
+        if options.raw_accents {
+            *color
+        } else {
+            color.add_lightness(get_lightness_weight_difference(
+                color,
+                profile.accent_visibility,
+                &variant,
+            ))
+        }
+    };
+    let bright_accent = |color: Color| {
+        if options.raw_accents {
+            color
+        } else {
+            color.to_saturated(0.7)
+        }
+    };
+
+    if let Base0FPolicy::SecondDominantHue = options.base0f {
+        if let Some(value) = second_dominant_color(accent_palette) {
+            let color = visible_accent(&Color::new(base0f_color, value));
+
+            scheme_palette.insert("base0F".to_string(), scheme_color(color.to_hex())?);
+
+            if let SchemeSystem::Base24 = system {
+                scheme_palette.insert(
+                    "base17".to_string(),
+                    scheme_color(bright_accent(color).to_hex())?,
+                );
+            }
+        }
+    }
+
     for color in &combined_palette {
-        let diff = get_lightness_weight_difference(color, 0.7);
-        let color = color.add_lightness(diff);
+        let color = visible_accent(color);
 
         match color.associated_pure_color.as_str() {
             "red" => {
-                scheme_palette.entry("base08".to_string()).or_insert(
-                    SchemeColor::new(color.to_hex())
-                        .map_err(|err| Error::GenerateColors(err.to_string()))?,
-                );
+                scheme_palette
+                    .entry("base08".to_string())
+                    .or_insert(scheme_color(color.to_hex())?);
             }
             "orange" => {
-                scheme_palette.entry("base09".to_string()).or_insert(
-                    SchemeColor::new(color.to_hex())
-                        .map_err(|err| Error::GenerateColors(err.to_string()))?,
-                );
+                scheme_palette
+                    .entry("base09".to_string())
+                    .or_insert(scheme_color(color.to_hex())?);
             }
             "yellow" => {
-                scheme_palette.entry("base0A".to_string()).or_insert(
-                    SchemeColor::new(color.to_hex())
-                        .map_err(|err| Error::GenerateColors(err.to_string()))?,
-                );
+                scheme_palette
+                    .entry("base0A".to_string())
+                    .or_insert(scheme_color(color.to_hex())?);
             }
             "green" => {
-                scheme_palette.entry("base0B".to_string()).or_insert(
-                    SchemeColor::new(color.to_hex())
-                        .map_err(|err| Error::GenerateColors(err.to_string()))?,
-                );
+                scheme_palette
+                    .entry("base0B".to_string())
+                    .or_insert(scheme_color(color.to_hex())?);
             }
             "cyan" => {
-                scheme_palette.entry("base0C".to_string()).or_insert(
-                    SchemeColor::new(color.to_hex())
-                        .map_err(|err| Error::GenerateColors(err.to_string()))?,
-                );
+                scheme_palette
+                    .entry("base0C".to_string())
+                    .or_insert(scheme_color(color.to_hex())?);
             }
             "blue" => {
-                scheme_palette.entry("base0D".to_string()).or_insert(
-                    SchemeColor::new(color.to_hex())
-                        .map_err(|err| Error::GenerateColors(err.to_string()))?,
-                );
+                scheme_palette
+                    .entry("base0D".to_string())
+                    .or_insert(scheme_color(color.to_hex())?);
             }
             "purple" => {
-                scheme_palette.entry("base0E".to_string()).or_insert(
-                    SchemeColor::new(color.to_hex())
-                        .map_err(|err| Error::GenerateColors(err.to_string()))?,
-                );
+                scheme_palette
+                    .entry("base0E".to_string())
+                    .or_insert(scheme_color(color.to_hex())?);
             }
-            "brown" => {
-                scheme_palette.entry("base0F".to_string()).or_insert(
-                    SchemeColor::new(color.to_hex())
-                        .map_err(|err| Error::GenerateColors(err.to_string()))?,
-                );
+            pure_color if pure_color == base0f_color.as_str() => {
+                scheme_palette
+                    .entry("base0F".to_string())
+                    .or_insert(scheme_color(color.to_hex())?);
             }
             _ => {}
         }
 
         if let SchemeSystem::Base24 = system {
-            let updated_color = color.to_saturated(0.7);
+            let updated_color = bright_accent(color);
 
             match updated_color.associated_pure_color.as_str() {
                 "red" => {
-                    scheme_palette.entry("base10".to_string()).or_insert(
-                        SchemeColor::new(updated_color.to_hex())
-                            .map_err(|err| Error::GenerateColors(err.to_string()))?,
-                    );
+                    scheme_palette
+                        .entry("base10".to_string())
+                        .or_insert(scheme_color(updated_color.to_hex())?);
                 }
                 "orange" => {
-                    scheme_palette.entry("base11".to_string()).or_insert(
-                        SchemeColor::new(updated_color.to_hex())
-                            .map_err(|err| Error::GenerateColors(err.to_string()))?,
-                    );
+                    scheme_palette
+                        .entry("base11".to_string())
+                        .or_insert(scheme_color(updated_color.to_hex())?);
                 }
                 "yellow" => {
-                    scheme_palette.entry("base12".to_string()).or_insert(
-                        SchemeColor::new(updated_color.to_hex())
-                            .map_err(|err| Error::GenerateColors(err.to_string()))?,
-                    );
+                    scheme_palette
+                        .entry("base12".to_string())
+                        .or_insert(scheme_color(updated_color.to_hex())?);
                 }
                 "green" => {
-                    scheme_palette.entry("base13".to_string()).or_insert(
-                        SchemeColor::new(updated_color.to_hex())
-                            .map_err(|err| Error::GenerateColors(err.to_string()))?,
-                    );
+                    scheme_palette
+                        .entry("base13".to_string())
+                        .or_insert(scheme_color(updated_color.to_hex())?);
                 }
                 "cyan" => {
-                    scheme_palette.entry("base14".to_string()).or_insert(
-                        SchemeColor::new(updated_color.to_hex())
-                            .map_err(|err| Error::GenerateColors(err.to_string()))?,
-                    );
+                    scheme_palette
+                        .entry("base14".to_string())
+                        .or_insert(scheme_color(updated_color.to_hex())?);
                 }
                 "blue" => {
-                    scheme_palette.entry("base15".to_string()).or_insert(
-                        SchemeColor::new(updated_color.to_hex())
-                            .map_err(|err| Error::GenerateColors(err.to_string()))?,
-                    );
+                    scheme_palette
+                        .entry("base15".to_string())
+                        .or_insert(scheme_color(updated_color.to_hex())?);
                 }
                 "purple" => {
-                    scheme_palette.entry("base16".to_string()).or_insert(
-                        SchemeColor::new(updated_color.to_hex())
-                            .map_err(|err| Error::GenerateColors(err.to_string()))?,
-                    );
+                    scheme_palette
+                        .entry("base16".to_string())
+                        .or_insert(scheme_color(updated_color.to_hex())?);
                 }
-                "brown" => {
-                    scheme_palette.entry("base17".to_string()).or_insert(
-                        SchemeColor::new(updated_color.to_hex())
-                            .map_err(|err| Error::GenerateColors(err.to_string()))?,
-                    );
+                pure_color if pure_color == base0f_color.as_str() => {
+                    scheme_palette
+                        .entry("base17".to_string())
+                        .or_insert(scheme_color(updated_color.to_hex())?);
                 }
                 _ => {}
             }
         }
     }
 
+    fill_missing_accents(&mut scheme_palette, &system, base0f_color)?;
+
+    if let (true, Some(hue)) = (options.duotone, diagnostics.dominant_hue) {
+        duotone_accents(
+            &mut scheme_palette,
+            &system,
+            &variant,
+            duotone_hues(hue, accent_palette),
+        )?;
+    }
+
+    finish_palette(&mut scheme_palette, &system, &diagnostics, &options)?;
+    diagnostics.confidence = slot_confidence(
+        &scheme_palette,
+        &system,
+        &variant,
+        &diagnostics,
+        &combined_palette,
+        accent_palette,
+        &options,
+    );
+    diagnostics.provenance = slot_provenance(
+        &scheme_palette,
+        &system,
+        &variant,
+        &diagnostics,
+        &combined_palette,
+        accent_palette,
+        (dark.into_format(), light.into_format()),
+        &options,
+    );
+
+    if verbose {
+        println!("{}", diagnostics);
+    }
+
+    let description =
+        scheme_description(description, &image_path, &diagnostics, &variant, &options);
     let scheme = Base16Scheme {
         author,
         description,
@@ -221,18 +1060,104 @@ pub fn create_scheme_from_image(params: SchemeParams) -> Result<Base16Scheme, Er
         variant,
         palette: scheme_palette,
     };
+    finish_extraction(
+        scheme,
+        diagnostics,
+        &options,
+        images.first().map(AsRef::as_ref),
+    )
+}
+
+/// Attach what the options ask for besides the scheme. The image, when
+/// there is one, is shown in the report
+pub(crate) fn finish_extraction(
+    scheme: Base16Scheme,
+    diagnostics: Diagnostics,
+    options: &ExtractionOptions,
+    image: Option<&RgbaImage>,
+) -> Result<Extraction, Error> {
+    let surfaces = options
+        .surfaces
+        .then(|| surface_colors(&scheme, diagnostics.dominant_hue))
+        .transpose()?;
+    let accessibility = options
+        .accessibility_report
+        .then(|| accessibility_report(&scheme))
+        .transpose()?;
+
+    let mut extraction = Extraction {
+        scheme,
+        diagnostics,
+        surfaces,
+        accessibility,
+        report: None,
+    };
 
-    Ok(scheme)
+    if let Some(format) = options.report {
+        extraction.report = Some(render_report(&extraction, image, options, format)?);
+    }
+
+    Ok(extraction)
+}
+
+/// Apply the options that adjust a finished palette: the gray tint, which
+/// leaves a locked background alone, the accents' hue ranges and chroma
+/// floor, the legibility of yellows on light backgrounds and the accents'
+/// distance from base00
+fn finish_palette(
+    palette: &mut HashMap<String, SchemeColor>,
+    system: &SchemeSystem,
+    diagnostics: &Diagnostics,
+    options: &ExtractionOptions,
+) -> Result<(), Error> {
+    if let (Some(chroma), Some(hue)) = (options.gray_tint, diagnostics.dominant_hue) {
+        let base00 = palette.get("base00").cloned();
+
+        tint_grays(palette, hue, chroma)?;
+
+        if let (Some(_), Some(base00)) = (options.background, base00) {
+            palette.insert("base00".to_string(), base00);
+        }
+    }
+
+    if let Some(ranges) = &options.accent_hue_ranges {
+        constrain_accent_hues(palette, system, ranges)?;
+    }
+
+    if let Some(min_chroma) = options.min_accent_chroma {
+        saturate_accents(palette, system, min_chroma, options.base0f.pure_color())?;
+    }
+
+    if !options.raw_accents {
+        legible_yellows(palette, system)?;
+    }
+
+    if let Some(min_delta_e) = options.min_accent_delta_e_or_default() {
+        separate_accents(palette, system, min_delta_e)?;
+    }
+
+    for (slot, adjustment) in &options.slot_adjustments {
+        adjust_palette_color(palette, slot, *adjustment)?;
+    }
+
+    Ok(())
 }
 
-fn get_lightness_weight_difference(color: &Color, threshold: f32) -> f32 {
+/// Get how much lightness to add to a color to reach the threshold's
+/// visibility, only lightening for dark schemes and only darkening for
+/// light schemes
+fn get_lightness_weight_difference(color: &Color, threshold: f32, variant: &SchemeVariant) -> f32 {
     let color: Hsl = Hsl::from_color(color.value.into_format::<f32>());
     let alpha = 0.5; // Weight for saturation
     let beta = 1.0; // Weight for lightness
 
     let visibility_metric = alpha * color.saturation + beta * color.lightness;
 
-    let value = ((threshold - visibility_metric) / beta).clamp(0.0, 1.0);
+    let value = (threshold - visibility_metric) / beta;
+    let value = match variant {
+        SchemeVariant::Light => value.clamp(-1.0, 0.0),
+        _ => value.clamp(0.0, 1.0),
+    };
 
     value / 2.0
 }
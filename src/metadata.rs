@@ -0,0 +1,186 @@
+use std::{
+    env,
+    path::Path,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use tinted_builder::SchemeVariant;
+
+use crate::{Diagnostics, ExtractionOptions};
+
+/// The environment variable checked first for the scheme author
+pub(crate) const AUTHOR_ENV_VAR: &str = "TINTED_SCHEME_EXTRACTOR_AUTHOR";
+
+/// Find the author from `TINTED_SCHEME_EXTRACTOR_AUTHOR`, then
+/// `GIT_AUTHOR_NAME`, then git's `user.name`
+pub(crate) fn author_from_env() -> Option<String> {
+    [AUTHOR_ENV_VAR, "GIT_AUTHOR_NAME"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .chain(git_user_name())
+        .map(|author| author.trim().to_string())
+        .find(|author| !author.is_empty())
+}
+
+fn git_user_name() -> Option<String> {
+    let output = Command::new("git")
+        .args(["config", "--get", "user.name"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    String::from_utf8(output.stdout).ok()
+}
+
+/// Get the scheme's description, which is the summary from
+/// `summary_description` when none was given and
+/// `ExtractionOptions::summary_description` is set
+pub(crate) fn scheme_description(
+    description: Option<String>,
+    image_path: &Path,
+    diagnostics: &Diagnostics,
+    variant: &SchemeVariant,
+    options: &ExtractionOptions,
+) -> Option<String> {
+    description.or_else(|| {
+        options
+            .summary_description
+            .then(|| summary_description(image_path, diagnostics, variant, options))
+    })
+}
+
+/// Summarize how a scheme was extracted, e.g. `Source: wall.png; dominant
+/// hue: 147°; variant: dark; settings: 5c1e0f3a9b2d4e67`, so a published
+/// scheme can be traced back to its image and settings
+pub(crate) fn summary_description(
+    image_path: &Path,
+    diagnostics: &Diagnostics,
+    variant: &SchemeVariant,
+    options: &ExtractionOptions,
+) -> String {
+    let mut parts = Vec::new();
+
+    if let Some(file_name) = image_path.file_name() {
+        parts.push(format!("Source: {}", file_name.to_string_lossy()));
+    }
+
+    if let Some(hue) = diagnostics.dominant_hue {
+        parts.push(format!("dominant hue: {:.0}°", hue));
+    }
+
+    parts.push(format!("variant: {}", variant));
+    parts.push(format!("settings: {}", settings_hash(options)));
+
+    parts.join("; ")
+}
+
+/// Hash the crate version and the extraction options with 64-bit FNV-1a,
+/// which is stable across Rust versions and platforms
+pub(crate) fn settings_hash(options: &ExtractionOptions) -> String {
+    let settings = format!("{}:{:?}", env!("CARGO_PKG_VERSION"), options);
+    let hash = settings
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+
+    format!("{:016x}", hash)
+}
+
+/// Format a time as `YYYY-MM-DD HH:MM:SS UTC`
+pub(crate) fn utc_timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    let seconds_of_day = seconds % 86_400;
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60
+    )
+}
+
+/// Convert days since 1970-01-01 to a proleptic Gregorian date, see
+/// <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_summary_description() {
+        let diagnostics = Diagnostics {
+            dominant_hue: Some(147.4),
+            ..Diagnostics::default()
+        };
+        let options = ExtractionOptions::default();
+        let description = summary_description(
+            Path::new("walls/wall.png"),
+            &diagnostics,
+            &SchemeVariant::Dark,
+            &options,
+        );
+
+        assert_eq!(
+            description,
+            format!(
+                "Source: wall.png; dominant hue: 147°; variant: dark; settings: {}",
+                settings_hash(&options)
+            )
+        );
+        assert_ne!(
+            settings_hash(&options),
+            settings_hash(&ExtractionOptions {
+                sample_every: 2,
+                ..ExtractionOptions::default()
+            })
+        );
+        assert!(summary_description(
+            Path::new(""),
+            &Diagnostics::default(),
+            &SchemeVariant::Light,
+            &options
+        )
+        .starts_with("variant: light; settings: "));
+    }
+
+    #[test]
+    fn test_utc_timestamp() {
+        assert_eq!(utc_timestamp(UNIX_EPOCH), "1970-01-01 00:00:00 UTC");
+        assert_eq!(
+            utc_timestamp(UNIX_EPOCH + Duration::from_secs(951_827_696)),
+            "2000-02-29 12:34:56 UTC"
+        );
+        assert_eq!(
+            utc_timestamp(UNIX_EPOCH + Duration::from_secs(1_735_689_599)),
+            "2024-12-31 23:59:59 UTC"
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt::Write;
+
+use tinted_builder::{Base16Scheme, SchemeVariant};
+
+use crate::{
+    adjust::scheme_slots,
+    export::{single_line, slot_hex},
+    xterm::ansi_slots,
+    Error,
+};
+
+/// The highlight groups with their foreground, background and attributes,
+/// after base16-vim
+const HIGHLIGHTS: &[(&str, &str, &str, &str)] = &[
+    // Editor
+    ("Normal", "base05", "base00", ""),
+    ("Bold", "", "", "bold = true"),
+    ("Italic", "", "", "italic = true"),
+    ("Underlined", "base08", "", ""),
+    ("Debug", "base08", "", ""),
+    ("Directory", "base0D", "", ""),
+    ("Error", "base00", "base08", ""),
+    ("ErrorMsg", "base08", "base00", ""),
+    ("Exception", "base08", "", ""),
+    ("FoldColumn", "base0C", "base01", ""),
+    ("Folded", "base03", "base01", ""),
+    ("IncSearch", "base01", "base09", ""),
+    ("Search", "base01", "base0A", ""),
+    ("Substitute", "base01", "base0A", ""),
+    ("Macro", "base08", "", ""),
+    ("MatchParen", "", "base03", ""),
+    ("ModeMsg", "base0B", "", ""),
+    ("MoreMsg", "base0B", "", ""),
+    ("Question", "base0D", "", ""),
+    ("SpecialKey", "base03", "", ""),
+    ("TooLong", "base08", "", ""),
+    ("Visual", "", "base02", ""),
+    ("WarningMsg", "base08", "", ""),
+    ("WildMenu", "base08", "base0A", ""),
+    ("Title", "base0D", "", ""),
+    ("Conceal", "base0D", "base00", ""),
+    ("Cursor", "base00", "base05", ""),
+    ("NonText", "base03", "", ""),
+    ("LineNr", "base03", "base01", ""),
+    ("SignColumn", "base03", "base01", ""),
+    ("StatusLine", "base04", "base02", ""),
+    ("StatusLineNC", "base03", "base01", ""),
+    ("VertSplit", "base02", "base02", ""),
+    ("WinSeparator", "base02", "base02", ""),
+    ("ColorColumn", "", "base01", ""),
+    ("CursorColumn", "", "base01", ""),
+    ("CursorLine", "", "base01", ""),
+    ("CursorLineNr", "base04", "base01", ""),
+    ("QuickFixLine", "", "base01", ""),
+    ("Pmenu", "base05", "base01", ""),
+    ("PmenuSel", "base01", "base05", ""),
+    ("NormalFloat", "base05", "base01", ""),
+    ("FloatBorder", "base05", "base01", ""),
+    ("TabLine", "base03", "base01", ""),
+    ("TabLineFill", "base03", "base01", ""),
+    ("TabLineSel", "base0B", "base01", ""),
+    // Syntax
+    ("Boolean", "base09", "", ""),
+    ("Character", "base08", "", ""),
+    ("Comment", "base03", "", "italic = true"),
+    ("Conditional", "base0E", "", ""),
+    ("Constant", "base09", "", ""),
+    ("Define", "base0E", "", ""),
+    ("Delimiter", "base0F", "", ""),
+    ("Float", "base09", "", ""),
+    ("Function", "base0D", "", ""),
+    ("Identifier", "base08", "", ""),
+    ("Include", "base0D", "", ""),
+    ("Keyword", "base0E", "", ""),
+    ("Label", "base0A", "", ""),
+    ("Number", "base09", "", ""),
+    ("Operator", "base05", "", ""),
+    ("PreProc", "base0A", "", ""),
+    ("Repeat", "base0A", "", ""),
+    ("Special", "base0C", "", ""),
+    ("SpecialChar", "base0F", "", ""),
+    ("Statement", "base08", "", ""),
+    ("StorageClass", "base0A", "", ""),
+    ("String", "base0B", "", ""),
+    ("Structure", "base0E", "", ""),
+    ("Tag", "base0A", "", ""),
+    ("Todo", "base0A", "base01", ""),
+    ("Type", "base0A", "", ""),
+    ("Typedef", "base0A", "", ""),
+    // Diffs
+    ("DiffAdd", "base0B", "base01", ""),
+    ("DiffChange", "base03", "base01", ""),
+    ("DiffDelete", "base08", "base01", ""),
+    ("DiffText", "base0D", "base01", ""),
+    ("DiffAdded", "base0B", "base00", ""),
+    ("DiffFile", "base08", "base00", ""),
+    ("DiffNewFile", "base0B", "base00", ""),
+    ("DiffLine", "base0D", "base00", ""),
+    ("DiffRemoved", "base08", "base00", ""),
+    // Diagnostics and spelling
+    ("DiagnosticError", "base08", "", ""),
+    ("DiagnosticWarn", "base0E", "", ""),
+    ("DiagnosticInfo", "base05", "", ""),
+    ("DiagnosticHint", "base0C", "", ""),
+    ("SpellBad", "", "", "undercurl = true, sp = colors.base08"),
+    ("SpellLocal", "", "", "undercurl = true, sp = colors.base0C"),
+    ("SpellCap", "", "", "undercurl = true, sp = colors.base0D"),
+    ("SpellRare", "", "", "undercurl = true, sp = colors.base0E"),
+];
+
+/// Render a Neovim colorscheme in Lua, ready to be saved as
+/// `colors/<slug>.lua` or sourced, with the highlight groups mapped after
+/// base16-vim and the terminal's ANSI colors
+///
+/// # Arguments
+/// * `scheme` - A reference to the generated Base16Scheme
+pub fn neovim_colorscheme(scheme: &Base16Scheme) -> Result<String, Error> {
+    let background = match scheme.variant {
+        SchemeVariant::Light => "light",
+        _ => "dark",
+    };
+    let mut colorscheme = format!(
+        "-- {} by {}\nvim.cmd('highlight clear')\nif vim.fn.exists('syntax_on') == 1 then\n  vim.cmd('syntax reset')\nend\nvim.o.background = '{}'\nvim.o.termguicolors = true\nvim.g.colors_name = '{}'\n\nlocal colors = {{\n",
+        single_line(&scheme.name),
+        single_line(&scheme.author),
+        background,
+        scheme.slug
+    );
+
+    for slot in scheme_slots(&scheme.system) {
+        let _ = writeln!(colorscheme, "  {} = '{}',", slot, slot_hex(scheme, &slot)?);
+    }
+
+    colorscheme.push_str("}\n\n");
+
+    for (index, slot) in ansi_slots(&scheme.system).iter().enumerate() {
+        let _ = writeln!(
+            colorscheme,
+            "vim.g.terminal_color_{} = colors.{}",
+            index, slot
+        );
+    }
+
+    colorscheme.push_str("\nlocal highlights = {\n");
+
+    for (group, foreground, background, attributes) in HIGHLIGHTS {
+        let spec: Vec<String> = [("fg", foreground), ("bg", background)]
+            .iter()
+            .filter(|(_, slot)| !slot.is_empty())
+            .map(|(key, slot)| format!("{} = colors.{}", key, slot))
+            .chain((!attributes.is_empty()).then(|| attributes.to_string()))
+            .collect();
+        let _ = writeln!(colorscheme, "  {} = {{ {} }},", group, spec.join(", "));
+    }
+
+    colorscheme.push_str(
+        "}\n\nfor group, spec in pairs(highlights) do\n  vim.api.nvim_set_hl(0, group, spec)\nend\n",
+    );
+
+    Ok(colorscheme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::test_scheme;
+    use tinted_builder::SchemeSystem;
+
+    #[test]
+    fn test_neovim_colorscheme() {
+        let colorscheme = neovim_colorscheme(&test_scheme(SchemeSystem::Base16)).unwrap();
+
+        assert!(colorscheme.starts_with("-- Test \"Scheme\" by Author\n"));
+        assert!(colorscheme.contains("vim.g.colors_name = 'test-scheme'\n"));
+        assert!(colorscheme.contains("\nlocal colors = {\n  base00 = '#000000',\n"));
+        assert!(colorscheme.contains("vim.g.terminal_color_1 = colors.base08\n"));
+        assert!(colorscheme.contains("  Normal = { fg = colors.base05, bg = colors.base00 },\n"));
+        assert!(colorscheme.contains("  Comment = { fg = colors.base03, italic = true },\n"));
+        assert!(colorscheme.contains("  Bold = { bold = true },\n"));
+        assert!(colorscheme.ends_with("  vim.api.nvim_set_hl(0, group, spec)\nend\n"));
+    }
+}
@@ -0,0 +1,108 @@
+use std::fmt::Write;
+
+use tinted_builder::Base16Scheme;
+
+use crate::{
+    export::{single_line, slot_hex},
+    Error,
+};
+
+/// The background, foreground and frame slots of low, normal and critical
+/// notifications: low ones are muted, normal ones framed in yellow and
+/// critical ones in red
+const URGENCIES: [(&str, &str, &str); 3] = [
+    ("base01", "base04", "base03"),
+    ("base01", "base05", "base0A"),
+    ("base01", "base05", "base08"),
+];
+
+/// Render the colors of dunst's `dunstrc` for each urgency, with base0D
+/// highlighting progress bars
+///
+/// # Arguments
+/// * `scheme` - A reference to the generated Base16Scheme
+pub fn dunst_config(scheme: &Base16Scheme) -> Result<String, Error> {
+    let mut config = format!(
+        "# {} by {}\n[global]\n    separator_color = frame\n    highlight = \"{}\"\n",
+        single_line(&scheme.name),
+        single_line(&scheme.author),
+        slot_hex(scheme, "base0D")?
+    );
+
+    for (name, (background, foreground, frame)) in
+        ["low", "normal", "critical"].iter().zip(URGENCIES)
+    {
+        let _ = write!(
+            config,
+            "\n[urgency_{}]\n    background = \"{}\"\n    foreground = \"{}\"\n    frame_color = \"{}\"\n",
+            name,
+            slot_hex(scheme, background)?,
+            slot_hex(scheme, foreground)?,
+            slot_hex(scheme, frame)?
+        );
+    }
+
+    Ok(config)
+}
+
+/// Render the colors of mako's config for each urgency, with base02 for
+/// progress bars
+///
+/// # Arguments
+/// * `scheme` - A reference to the generated Base16Scheme
+pub fn mako_config(scheme: &Base16Scheme) -> Result<String, Error> {
+    let mut config = format!(
+        "# {} by {}\nprogress-color=over {}\n",
+        single_line(&scheme.name),
+        single_line(&scheme.author),
+        slot_hex(scheme, "base02")?
+    );
+    let [low, normal, critical] = URGENCIES;
+
+    // Normal notifications are mako's defaults, the other urgencies are
+    // criteria sections
+    for (section, (background, foreground, frame)) in [
+        ("", normal),
+        ("\n[urgency=low]\n", low),
+        ("\n[urgency=critical]\n", critical),
+    ] {
+        let _ = write!(
+            config,
+            "{}background-color={}\ntext-color={}\nborder-color={}\n",
+            section,
+            slot_hex(scheme, background)?,
+            slot_hex(scheme, foreground)?,
+            slot_hex(scheme, frame)?
+        );
+    }
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::test_scheme;
+    use tinted_builder::SchemeSystem;
+
+    #[test]
+    fn test_notification_configs() {
+        let scheme = test_scheme(SchemeSystem::Base16);
+        let dunst = dunst_config(&scheme).unwrap();
+
+        assert!(dunst.starts_with("# Test \"Scheme\" by Author\n[global]\n"));
+        assert!(dunst.contains("    highlight = \"#0000ff\"\n"));
+        assert!(dunst.contains(
+            "\n[urgency_low]\n    background = \"#242424\"\n    foreground = \"#929292\"\n    frame_color = \"#6d6d6d\"\n"
+        ));
+        assert!(dunst.ends_with("    frame_color = \"#ff0000\"\n"));
+
+        let mako = mako_config(&scheme).unwrap();
+
+        assert!(mako.contains(
+            "progress-color=over #494949\nbackground-color=#242424\ntext-color=#b6b6b6\nborder-color=#ffff00\n"
+        ));
+        assert!(mako.contains("\n[urgency=low]\nbackground-color=#242424\n"));
+        assert!(mako.ends_with("\n[urgency=critical]\nbackground-color=#242424\ntext-color=#b6b6b6\nborder-color=#ff0000\n"));
+    }
+}
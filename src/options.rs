@@ -0,0 +1,880 @@
+#[cfg(feature = "exporters")]
+use std::str::FromStr;
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+use palette::Srgb;
+use tinted_builder::SchemeVariant;
+
+#[cfg(feature = "exporters")]
+use crate::Error;
+use crate::{adjust::HslAdjustment, color::PureColor};
+
+/// A rectangle in image pixel coordinates
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CropRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Caps on what decoding an image file may use, so untrusted input can't
+/// exhaust memory. Exceeding a limit returns `Error::LimitsExceeded`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DecodeLimits {
+    /// The maximum image width in pixels
+    pub max_width: Option<u32>,
+    /// The maximum image height in pixels
+    pub max_height: Option<u32>,
+    /// The maximum number of bytes the decoder may allocate at once.
+    /// Defaults to the image crate's 512MiB
+    pub max_alloc: Option<u64>,
+}
+
+/// The format schemes are returned in by the daemon and HTTP server
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum OutputFormat {
+    /// The scheme YAML file
+    #[default]
+    Yaml,
+    /// The scheme as a JSON object
+    Json,
+    /// The scheme exported for an app, e.g. `"tailwind"`
+    #[cfg(feature = "exporters")]
+    #[cfg_attr(feature = "serde", serde(untagged))]
+    Export(ExportFormat),
+}
+
+/// The formats `export_scheme` renders schemes in, for apps and tools
+/// without a tinted-builder template at hand
+#[cfg(feature = "exporters")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ExportFormat {
+    /// A Tailwind CSS config extending `theme.extend.colors`
+    Tailwind,
+    /// A VS Code color theme
+    #[cfg_attr(feature = "serde", serde(rename = "vscode"))]
+    VsCode,
+    /// A Zed theme family
+    Zed,
+    /// A Neovim colorscheme in Lua
+    Neovim,
+    /// tmux options for the status line, windows and panes
+    Tmux,
+    /// A rofi theme
+    Rofi,
+    /// The colors of a dunst config
+    Dunst,
+    /// The colors of a mako config
+    Mako,
+    /// GTK3 and GTK4 `@define-color` overrides
+    Gtk,
+    /// A qt5ct and qt6ct color scheme
+    Qt,
+    /// A Hyprland config snippet with border and group colors
+    Hyprland,
+    /// A Waybar `colors.css` with derived hover and warning colors
+    Waybar,
+}
+
+#[cfg(feature = "exporters")]
+impl ExportFormat {
+    /// Every export format
+    pub const ALL: [ExportFormat; 12] = [
+        ExportFormat::Tailwind,
+        ExportFormat::VsCode,
+        ExportFormat::Zed,
+        ExportFormat::Neovim,
+        ExportFormat::Tmux,
+        ExportFormat::Rofi,
+        ExportFormat::Dunst,
+        ExportFormat::Mako,
+        ExportFormat::Gtk,
+        ExportFormat::Qt,
+        ExportFormat::Hyprland,
+        ExportFormat::Waybar,
+    ];
+
+    /// The name the format is selected by, e.g. `tailwind`
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Tailwind => "tailwind",
+            ExportFormat::VsCode => "vscode",
+            ExportFormat::Zed => "zed",
+            ExportFormat::Neovim => "neovim",
+            ExportFormat::Tmux => "tmux",
+            ExportFormat::Rofi => "rofi",
+            ExportFormat::Dunst => "dunst",
+            ExportFormat::Mako => "mako",
+            ExportFormat::Gtk => "gtk",
+            ExportFormat::Qt => "qt",
+            ExportFormat::Hyprland => "hyprland",
+            ExportFormat::Waybar => "waybar",
+        }
+    }
+
+    /// The media type of the exported file, e.g. for HTTP responses
+    pub fn media_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Tailwind => "text/javascript",
+            ExportFormat::VsCode | ExportFormat::Zed => "application/json",
+            ExportFormat::Neovim => "text/x-lua",
+            ExportFormat::Tmux => "text/plain",
+            ExportFormat::Rofi => "text/plain",
+            ExportFormat::Dunst => "text/plain",
+            ExportFormat::Mako => "text/plain",
+            ExportFormat::Gtk => "text/css",
+            ExportFormat::Qt => "text/plain",
+            ExportFormat::Hyprland => "text/plain",
+            ExportFormat::Waybar => "text/css",
+        }
+    }
+}
+
+#[cfg(feature = "exporters")]
+impl FromStr for ExportFormat {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        ExportFormat::ALL
+            .into_iter()
+            .find(|format| format.name() == name)
+            .ok_or_else(|| Error::UnsupportedExportFormat {
+                name: name.to_string(),
+            })
+    }
+}
+
+/// Which frame of an animated GIF, APNG or WebP file colors are extracted
+/// from. Still images always use their only frame
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FrameSelection {
+    /// The first frame, the same as decoding the file as a still image
+    #[default]
+    First,
+    /// The frame in the middle of the animation
+    Middle,
+    /// The frame at this index, or the last frame for shorter animations
+    Index(usize),
+    /// The pixel-wise average of up to this many frames spread evenly over
+    /// the animation
+    Average(usize),
+    /// Up to this many frames spread evenly over the animation, whose pixels
+    /// are combined like those of several images, so the scheme represents
+    /// the whole animation
+    Sample(usize),
+}
+
+/// Which frame of a video file colors are extracted from
+#[cfg(feature = "video")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum VideoFrame {
+    /// The first frame at or after this many seconds into the video
+    Timestamp(f64),
+    /// The frame at this index, starting at 0
+    Index(u64),
+}
+
+#[cfg(feature = "video")]
+impl Default for VideoFrame {
+    fn default() -> Self {
+        VideoFrame::Timestamp(0.0)
+    }
+}
+
+/// What base0F, traditionally brown, represents
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Base0FPolicy {
+    /// The color closest to brown
+    #[default]
+    Brown,
+    /// The color closest to magenta, so pink images keep their pink
+    Magenta,
+    /// The most dominant color whose hue is noticeably different from the
+    /// dominant hue, or brown when the image has no second hue
+    SecondDominantHue,
+}
+
+impl Base0FPolicy {
+    /// The pure color base0F is matched to, and synthesized at when missing
+    pub(crate) fn pure_color(&self) -> PureColor {
+        match self {
+            Base0FPolicy::Magenta => PureColor::Magenta,
+            Base0FPolicy::Brown | Base0FPolicy::SecondDominantHue => PureColor::Brown,
+        }
+    }
+}
+
+/// How the subject of a photo is estimated, to prefer its colors for the
+/// accents. The strength is between 0.0 (no preference) and 1.0
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SubjectBias {
+    /// The subject is in the middle of the image
+    Center(f32),
+    /// The subject is what stands out from the image's average color, e.g.
+    /// a bird against a large sky
+    Contrast(f32),
+}
+
+/// When the inverse of a pure color's match may be used instead of the
+/// match, see `ExtractionOptions::max_color_distance`
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum InverseColors {
+    /// Unless the match is farther than `max_color_distance` from its pure
+    /// color and still closer to it than the inverse's match
+    #[default]
+    Always,
+    /// Never, so every color comes from the image
+    Never,
+    /// Only when a pixel of the image is within this Euclidean distance of
+    /// the inverse, between 8-bit sRGB colors, e.g. `30.0`
+    Present(f64),
+}
+
+/// How the color of the light is estimated to correct the white balance
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum WhiteBalance {
+    /// The average color of the image is taken to be gray, which suits
+    /// photos with many different colors
+    GrayWorld,
+    /// The brightest colors of the image, at the 99th percentile of each
+    /// channel, are taken to be white, which suits photos with highlights
+    WhitePatch,
+}
+
+/// The format of the report rendered by `quality_report`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ReportFormat {
+    /// Markdown, e.g. to attach to a pull request
+    Markdown,
+    /// A standalone HTML page
+    Html,
+}
+
+/// How an image is smoothed before its pixels are scanned
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Denoise {
+    /// A Gaussian blur with this standard deviation in pixels, e.g. `1.0`,
+    /// which softens sensor noise
+    Gaussian(f32),
+    /// Replace every channel by its median within this many pixels, e.g.
+    /// `1` for a 3x3 window, which removes outliers such as JPEG artifacts
+    /// while keeping edges
+    Median(u32),
+}
+
+/// A range of HSL hues in degrees, from `start` up to `end`, wrapping past
+/// 360, so `HueRange { start: 340.0, end: 15.0 }` covers the reds
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HueRange {
+    pub start: f32,
+    pub end: f32,
+}
+
+impl HueRange {
+    /// Whether a hue is within the range
+    ///
+    /// # Arguments
+    /// * `hue` - The hue in degrees
+    pub fn contains(&self, hue: f32) -> bool {
+        (hue - self.start).rem_euclid(360.0) <= (self.end - self.start).rem_euclid(360.0)
+    }
+
+    /// Get the hue itself when it's within the range, otherwise the edge of
+    /// the range closest to it, between 0.0 and 360.0
+    ///
+    /// # Arguments
+    /// * `hue` - The hue in degrees
+    pub fn clamp(&self, hue: f32) -> f32 {
+        if self.contains(hue) {
+            return hue.rem_euclid(360.0);
+        }
+
+        let distance = |edge: f32| {
+            let distance = (hue - edge).rem_euclid(360.0);
+
+            distance.min(360.0 - distance)
+        };
+
+        if distance(self.start) <= distance(self.end) {
+            self.start.rem_euclid(360.0)
+        } else {
+            self.end.rem_euclid(360.0)
+        }
+    }
+}
+
+/// Bounds for the background (base00) and foreground (base05) colors of a
+/// scheme variant, and how visible its accents are. Luma is the relative
+/// luminance, between 0.0 and 1.0
+///
+/// For dark schemes the background luma is a maximum and the foreground
+/// luma a minimum, for light schemes it's the other way around.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VariantProfile {
+    /// How dark a dark background, or how light a light background, has to
+    /// be at least
+    pub background_luma: f32,
+    /// The largest HSL saturation of the background
+    pub background_max_saturation: f32,
+    /// How light a dark scheme's foreground, or how dark a light scheme's
+    /// foreground, has to be at least
+    pub foreground_luma: f32,
+    /// The largest HSL saturation of the foreground
+    pub foreground_max_saturation: f32,
+    /// The visibility extracted accents are moved towards, as their HSL
+    /// lightness plus half their saturation. Dark schemes' accents below it
+    /// are lightened and light schemes' accents above it are darkened
+    pub accent_visibility: f32,
+}
+
+impl VariantProfile {
+    /// The default profile for dark schemes
+    pub fn dark() -> Self {
+        VariantProfile {
+            background_luma: 0.02,
+            background_max_saturation: 0.6,
+            foreground_luma: 0.6,
+            foreground_max_saturation: 0.15,
+            accent_visibility: 0.7,
+        }
+    }
+
+    /// The default profile for light schemes
+    pub fn light() -> Self {
+        VariantProfile {
+            background_luma: 0.75,
+            background_max_saturation: 0.15,
+            foreground_luma: 0.015,
+            foreground_max_saturation: 0.65,
+            accent_visibility: 0.6,
+        }
+    }
+
+    /// The default profile for a variant
+    ///
+    /// # Arguments
+    /// * `variant` - The SchemeVariant to get the profile of
+    pub fn for_variant(variant: &SchemeVariant) -> Self {
+        match variant {
+            SchemeVariant::Light => VariantProfile::light(),
+            _ => VariantProfile::dark(),
+        }
+    }
+}
+
+/// The bounds a color has to be within to be picked as an anchor by one
+/// pass of a ladder. Luma is the relative luminance and saturation the HSL
+/// saturation, both between 0.0 and 1.0. `None` leaves a side unbounded
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ColorPass {
+    pub min_luma: Option<f32>,
+    pub max_luma: Option<f32>,
+    pub min_saturation: Option<f32>,
+    pub max_saturation: Option<f32>,
+}
+
+/// The passes tried in order to find the light anchor color. When none
+/// matches, the most dominant color is used and lightened later
+pub const DEFAULT_LIGHT_LADDER: [ColorPass; 7] = [
+    // A nice light color with low saturation
+    ColorPass {
+        min_luma: Some(0.6),
+        max_luma: None,
+        min_saturation: None,
+        max_saturation: Some(0.4),
+    },
+    // Saturated colors, as long as they're very bright
+    ColorPass {
+        min_luma: Some(0.7),
+        max_luma: None,
+        min_saturation: None,
+        max_saturation: Some(0.85),
+    },
+    // Same as the first, but a little more permissive
+    ColorPass {
+        min_luma: Some(0.5),
+        max_luma: None,
+        min_saturation: None,
+        max_saturation: Some(0.5),
+    },
+    // More saturated colors
+    ColorPass {
+        min_luma: Some(0.6),
+        max_luma: None,
+        min_saturation: None,
+        max_saturation: Some(0.85),
+    },
+    // Darker colors, as long as they're not saturated
+    ColorPass {
+        min_luma: Some(0.32),
+        max_luma: None,
+        min_saturation: None,
+        max_saturation: Some(0.4),
+    },
+    // Even more saturated colors
+    ColorPass {
+        min_luma: Some(0.4),
+        max_luma: None,
+        min_saturation: None,
+        max_saturation: None,
+    },
+    // Darker colors
+    ColorPass {
+        min_luma: Some(0.3),
+        max_luma: None,
+        min_saturation: None,
+        max_saturation: None,
+    },
+];
+
+/// The passes tried in order to find the dark anchor color. When none
+/// matches, the most dominant color is used and darkened later
+pub const DEFAULT_DARK_LADDER: [ColorPass; 3] = [
+    // A nice darkish color with at least a bit of color
+    ColorPass {
+        min_luma: Some(0.012),
+        max_luma: Some(0.1),
+        min_saturation: Some(0.18),
+        max_saturation: Some(0.9),
+    },
+    // Any saturation, as long as they're dark but not very dark
+    ColorPass {
+        min_luma: Some(0.012),
+        max_luma: Some(0.1),
+        min_saturation: None,
+        max_saturation: None,
+    },
+    // Darker colors too
+    ColorPass {
+        min_luma: None,
+        max_luma: Some(0.1),
+        min_saturation: None,
+        max_saturation: None,
+    },
+];
+
+/// Picks the dark and light anchor colors the grays are built from,
+/// instead of the built-in ladders
+///
+/// The candidates are the image's dominant colors, most dominant first.
+/// Returning `None` falls back to the ladder.
+pub trait AnchorSelector: Send + Sync {
+    /// Pick the dark anchor color
+    ///
+    /// # Arguments
+    /// * `candidates` - The dominant colors, most dominant first
+    fn dark(&self, candidates: &[Srgb<u8>]) -> Option<Srgb<u8>>;
+
+    /// Pick the light anchor color
+    ///
+    /// # Arguments
+    /// * `candidates` - The dominant colors, most dominant first
+    fn light(&self, candidates: &[Srgb<u8>]) -> Option<Srgb<u8>>;
+}
+
+impl fmt::Debug for dyn AnchorSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AnchorSelector")
+    }
+}
+
+/// The default `ExtractionOptions::max_color_distance`. Larger distances
+/// use the inverses' matches for more of the pure colors
+pub const DEFAULT_MAX_COLOR_DISTANCE: f64 = 100.0;
+
+/// The default `ExtractionOptions::max_delta_e`
+pub const DEFAULT_MAX_DELTA_E: f32 = 30.0;
+
+/// Contrast ratios against base00 for base01 to base07, suited to how
+/// templates use the grays: selections and highlights first, then
+/// comments, and text last. Use it with `ExtractionOptions::contrast_ramp`
+pub const DEFAULT_CONTRAST_RAMP: [f32; 7] = [1.2, 1.5, 2.0, 3.0, 4.5, 7.0, 10.0];
+
+/// Hue ranges for base08 to base0F that keep their meaning in syntax
+/// highlighting, e.g. base08 red for errors and base0B green for
+/// additions. base0F has none, since it follows `ExtractionOptions::base0f`.
+/// Use it with `ExtractionOptions::accent_hue_ranges`
+pub const DEFAULT_ACCENT_HUE_RANGES: [Option<HueRange>; 8] = [
+    Some(HueRange {
+        start: 340.0,
+        end: 15.0,
+    }),
+    Some(HueRange {
+        start: 15.0,
+        end: 45.0,
+    }),
+    Some(HueRange {
+        start: 45.0,
+        end: 70.0,
+    }),
+    Some(HueRange {
+        start: 80.0,
+        end: 160.0,
+    }),
+    Some(HueRange {
+        start: 160.0,
+        end: 200.0,
+    }),
+    Some(HueRange {
+        start: 200.0,
+        end: 250.0,
+    }),
+    Some(HueRange {
+        start: 250.0,
+        end: 330.0,
+    }),
+    None,
+];
+
+/// The smallest difference between a locked background and the accents,
+/// unless `ExtractionOptions::min_accent_delta_e` is set
+const LOCKED_MIN_DELTA_E: f32 = 20.0;
+
+/// Settings that tune how colors are extracted from an image
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ExtractionOptions {
+    /// Only extract colors from this part of the image
+    pub crop: Option<CropRegion>,
+    /// How strongly pixels near the center of the image are preferred over
+    /// pixels near the edges, between 0.0 (no preference) and 1.0
+    pub center_weight: f32,
+    /// Prefer the colors of the photo's subject for the accents, leaving
+    /// the background and foreground to the whole image. Unset by default
+    pub subject_bias: Option<SubjectBias>,
+    /// How much skin-tone pixels count when electing the accents' dominant
+    /// colors, between 0.0 (left out) and 1.0, e.g. so faces in portraits
+    /// don't become the red, orange and brown accents. Unset by default
+    pub skin_tone_weight: Option<f32>,
+    /// Only look at every Nth pixel, trading a little accuracy for speed on
+    /// large images. `0` and `1` look at every pixel
+    pub sample_every: usize,
+    /// Downscale images whose width or height is larger than this before
+    /// extracting, keeping the aspect ratio. Colors are analysed at this
+    /// resolution, which is much faster for very large photos. JPEGs, SVGs,
+    /// PDFs and videos are decoded at a reduced size when there's no `crop`
+    pub max_dimension: Option<u32>,
+    /// Smooth the image before scanning it, so noisy outlier pixels aren't
+    /// picked as the closest to a pure color. Applied after `max_dimension`
+    /// and before `superpixels`. Unset by default
+    pub denoise: Option<Denoise>,
+    /// Neutralize the color cast of the light, e.g. so warmly lit photos
+    /// don't turn every accent orange and brown. The estimated cast is
+    /// returned in `Diagnostics::color_cast`. The cast is estimated over
+    /// all images together, after the other preprocessing. Unset by default
+    pub white_balance: Option<WhiteBalance>,
+    /// Spread the image's tones over the whole range with histogram
+    /// equalization of its luma before extracting, so the anchors and
+    /// accents of flat or hazy images don't clump in the midtones. Hues are
+    /// kept. Applied after `denoise`
+    pub equalize: bool,
+    /// Group the pixels into about this many superpixels, regions of
+    /// similar color, and use each region's mean color for all of its
+    /// pixels. This removes single-pixel noise such as film grain so
+    /// coherent regions decide the colors, weighted by their area. Combine
+    /// it with `max_dimension` for large photos. Unset by default
+    pub superpixels: Option<u32>,
+    /// Limits applied when decoding image files. Without them the `image`
+    /// crate's defaults are used, which cap allocations at 512MiB
+    pub limits: Option<DecodeLimits>,
+    /// Which frame of an animated image file to use
+    pub frames: FrameSelection,
+    /// The length of the longer side SVG files are rasterized at, e.g.
+    /// `512`. Defaults to the SVG's own size
+    #[cfg(feature = "svg")]
+    pub svg_size: Option<u32>,
+    /// The page of PDF files to use, starting at 0
+    #[cfg(feature = "pdf")]
+    pub pdf_page: u16,
+    /// The frame of video files to use. Defaults to the first frame
+    #[cfg(feature = "video")]
+    pub video_frame: VideoFrame,
+    /// How far the pixel closest to a pure color has to be from it before
+    /// it's kept instead of the pixel closest to the inverse pure color, as
+    /// the Euclidean distance between 8-bit sRGB colors (0.0 to about
+    /// 441.7). Farther matches are only kept when they're also closer than
+    /// the inverse's match. Defaults to `DEFAULT_MAX_COLOR_DISTANCE`
+    pub max_color_distance: f64,
+    /// Whether inverses of the matches, which may not be in the image at
+    /// all, can replace them. Defaults to `InverseColors::Always`
+    pub inverse_colors: InverseColors,
+    /// How far a dominant color may be from an accent's pure color to be
+    /// used for it, as the CIEDE2000 difference, which treats every hue
+    /// alike. Raise it for photos with muted palettes, whose dominant
+    /// colors are otherwise rejected. Defaults to `DEFAULT_MAX_DELTA_E`
+    pub max_delta_e: f32,
+    /// The bounds for the background and foreground colors, e.g. for a
+    /// softer dark background. Defaults to `VariantProfile::for_variant`
+    pub profile: Option<VariantProfile>,
+    /// The passes tried in order to find the light anchor color, which the
+    /// foreground of dark schemes and the background of light schemes are
+    /// based on. Defaults to `DEFAULT_LIGHT_LADDER`
+    pub light_ladder: Option<Vec<ColorPass>>,
+    /// The passes tried in order to find the dark anchor color. Defaults to
+    /// `DEFAULT_DARK_LADDER`
+    pub dark_ladder: Option<Vec<ColorPass>>,
+    /// The WCAG contrast ratios against base00 to place base01 to base07
+    /// at, between the background and the foreground, e.g.
+    /// `DEFAULT_CONTRAST_RAMP`. Ratios the foreground doesn't reach use the
+    /// foreground. Without it the grays are evenly spaced
+    pub contrast_ramp: Option<[f32; 7]>,
+    /// Lock base00 to this color, e.g. pure black for OLED screens. The
+    /// grays are built from it towards a foreground with at least a 7:1
+    /// contrast ratio, and the accents are kept `min_accent_delta_e` away
+    /// from it, or 20.0 when that's unset
+    pub background: Option<Srgb<u8>>,
+    /// Tint base00 to base07 towards the image's dominant hue with this
+    /// LCh chroma, e.g. `8.0` for subtly colored grays. Each gray keeps
+    /// its lightness. Unset by default, which keeps the grays between the
+    /// background and the foreground
+    pub gray_tint: Option<f32>,
+    /// Set a missing `SchemeParams::description` to a summary of the
+    /// extraction: the image's file name, the dominant hue, the variant and
+    /// a hash of these options
+    pub summary_description: bool,
+    /// Also check the contrast of every slot, returned in
+    /// `Extraction::accessibility`
+    pub accessibility_report: bool,
+    /// Also render a human-readable report of the extraction in this
+    /// format, returned in `Extraction::report`. Unset by default
+    pub report: Option<ReportFormat>,
+    /// Also derive UI surface colors from the grays and the dominant hue,
+    /// returned in `Extraction::surfaces`
+    pub surfaces: bool,
+    /// The smallest CIEDE2000 difference allowed between each accent and
+    /// base00, e.g. `20.0`. Closer accents are lightened or darkened away
+    /// from base00 first, and have their hue rotated last, so dark blue
+    /// accents stay visible on dark blue backgrounds. Unset by default
+    pub min_accent_delta_e: Option<f32>,
+    /// The lowest LCh chroma of base08 to base0F and Base24's bright
+    /// accents, e.g. `30.0`, so accents from muted images still read as
+    /// colors in syntax highlighting. Weaker accents keep their lightness
+    /// and hue. Unset by default
+    pub min_accent_chroma: Option<f32>,
+    /// Build the palette from the images' mean color with synthesized
+    /// accents, like for monochrome images, when their hue entropy in
+    /// `Diagnostics::diversity` is below this, e.g. `0.3` for images whose
+    /// colors all share a hue or two. Unset by default
+    pub min_hue_entropy: Option<f32>,
+    /// Build all the accents from two hues, the dominant hue and the second
+    /// dominant one or its complement, as lightness and chroma variations
+    /// of each, for minimalist duotone schemes. Applied before
+    /// `accent_hue_ranges` and `min_accent_delta_e`
+    pub duotone: bool,
+    /// Use the extracted accents exactly as they are, without lightening
+    /// them to be visible, darkening yellows on light backgrounds or
+    /// desaturating Base24's bright accents, e.g. for templates that
+    /// handle contrast themselves. Accents that had to
+    /// be synthesized and the other accent options still apply
+    pub raw_accents: bool,
+    /// What base0F represents. Defaults to brown
+    pub base0f: Base0FPolicy,
+    /// The hue range of each of base08 to base0F, e.g.
+    /// `DEFAULT_ACCENT_HUE_RANGES`. Accents outside their range are rotated
+    /// to its closest edge, keeping their saturation and lightness. Base24's
+    /// bright accents use the range of their base accent. Unset by default
+    pub accent_hue_ranges: Option<[Option<HueRange>; 8]>,
+    /// Nudge these slots' colors once everything else is applied, e.g.
+    /// `base0D` to a warmer blue. Every slot must be part of the scheme
+    /// system. Empty by default
+    pub slot_adjustments: BTreeMap<String, HslAdjustment>,
+    /// Picks the anchor colors instead of the ladders. The cache isn't used
+    /// while this is set, since the selector can't be part of its key
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub anchor_selector: Option<Arc<dyn AnchorSelector>>,
+    /// Reuse the palette extracted from the same image files with the same
+    /// settings, stored in `extraction_cache_dir`. Only applies when
+    /// extracting from image paths, and not while `report` is set, since
+    /// the report shows the decoded image
+    #[cfg(feature = "cache")]
+    pub cache: bool,
+}
+
+impl ExtractionOptions {
+    /// The light ladder in use
+    pub(crate) fn light_ladder_or_default(&self) -> &[ColorPass] {
+        self.light_ladder
+            .as_deref()
+            .unwrap_or(&DEFAULT_LIGHT_LADDER)
+    }
+
+    /// The smallest difference allowed between the accents and base00, if
+    /// any
+    pub(crate) fn min_accent_delta_e_or_default(&self) -> Option<f32> {
+        self.min_accent_delta_e
+            .or(self.background.map(|_| LOCKED_MIN_DELTA_E))
+    }
+
+    /// The dark ladder in use
+    pub(crate) fn dark_ladder_or_default(&self) -> &[ColorPass] {
+        self.dark_ladder.as_deref().unwrap_or(&DEFAULT_DARK_LADDER)
+    }
+}
+
+impl Default for ExtractionOptions {
+    fn default() -> Self {
+        ExtractionOptions {
+            crop: None,
+            center_weight: 0.0,
+            subject_bias: None,
+            skin_tone_weight: None,
+            sample_every: 0,
+            max_dimension: None,
+            denoise: None,
+            white_balance: None,
+            equalize: false,
+            superpixels: None,
+            limits: None,
+            frames: FrameSelection::default(),
+            #[cfg(feature = "svg")]
+            svg_size: None,
+            #[cfg(feature = "pdf")]
+            pdf_page: 0,
+            #[cfg(feature = "video")]
+            video_frame: VideoFrame::default(),
+            max_color_distance: DEFAULT_MAX_COLOR_DISTANCE,
+            inverse_colors: InverseColors::Always,
+            max_delta_e: DEFAULT_MAX_DELTA_E,
+            profile: None,
+            light_ladder: None,
+            dark_ladder: None,
+            contrast_ramp: None,
+            background: None,
+            gray_tint: None,
+            min_accent_delta_e: None,
+            min_accent_chroma: None,
+            min_hue_entropy: None,
+            duotone: false,
+            raw_accents: false,
+            base0f: Base0FPolicy::default(),
+            accent_hue_ranges: None,
+            slot_adjustments: BTreeMap::new(),
+            summary_description: false,
+            accessibility_report: false,
+            report: None,
+            surfaces: false,
+            anchor_selector: None,
+            #[cfg(feature = "cache")]
+            cache: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{extract_scheme_from_dynamic_image, Error, SchemeParams};
+    use image::{DynamicImage, Rgb, RgbImage};
+    use std::path::PathBuf;
+    use tinted_builder::SchemeSystem;
+
+    /// Picks the least dominant color as the dark anchor, and leaves the
+    /// light anchor to the ladder
+    struct LeastDominantDark;
+
+    impl AnchorSelector for LeastDominantDark {
+        fn dark(&self, candidates: &[Srgb<u8>]) -> Option<Srgb<u8>> {
+            candidates.last().copied()
+        }
+
+        fn light(&self, _candidates: &[Srgb<u8>]) -> Option<Srgb<u8>> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_anchor_selector() {
+        let image = DynamicImage::ImageRgb8(RgbImage::from_fn(64, 64, |x, y| {
+            Rgb([(x * 4) as u8, (y * 4) as u8, 90])
+        }));
+        let params = SchemeParams {
+            image_path: PathBuf::new(),
+            author: "Author".to_string(),
+            description: None,
+            name: "Name".to_string(),
+            slug: "name".to_string(),
+            system: SchemeSystem::Base16,
+            variant: SchemeVariant::Dark,
+            verbose: false,
+            options: ExtractionOptions {
+                anchor_selector: Some(Arc::new(LeastDominantDark)),
+                ..Default::default()
+            },
+        };
+
+        let diagnostics = extract_scheme_from_dynamic_image(&image, params)
+            .unwrap()
+            .diagnostics;
+
+        assert_eq!((diagnostics.dark_passes, diagnostics.dark_pass), (0, None));
+        assert!(diagnostics.light_passes > 0);
+    }
+
+    #[test]
+    fn test_slot_adjustments() {
+        let image = DynamicImage::ImageRgb8(RgbImage::from_fn(64, 64, |x, y| {
+            Rgb([(x * 4) as u8, (y * 4) as u8, 90])
+        }));
+        let params = |system: SchemeSystem, slot: &str| SchemeParams {
+            image_path: PathBuf::new(),
+            author: "Author".to_string(),
+            description: None,
+            name: "Name".to_string(),
+            slug: "name".to_string(),
+            system,
+            variant: SchemeVariant::Dark,
+            verbose: false,
+            options: ExtractionOptions {
+                slot_adjustments: BTreeMap::from([(
+                    slot.to_string(),
+                    HslAdjustment {
+                        lightness: 1.0,
+                        ..Default::default()
+                    },
+                )]),
+                ..Default::default()
+            },
+        };
+
+        let scheme =
+            extract_scheme_from_dynamic_image(&image, params(SchemeSystem::Base16, "base0D"))
+                .unwrap()
+                .scheme;
+
+        assert_eq!(scheme.palette["base0D"].rgb, (255, 255, 255));
+        assert!(params(SchemeSystem::Base24, "base10").validate().is_ok());
+        assert!(matches!(
+            params(SchemeSystem::Base16, "base10").validate(),
+            Err(Error::SlotNotInSystem { slot, system: SchemeSystem::Base16 }) if slot == "base10"
+        ));
+    }
+}
@@ -0,0 +1,76 @@
+use image::{
+    error::{DecodingError, ImageFormatHint},
+    ImageError, ImageResult, Limits, RgbaImage,
+};
+use pdfium_render::prelude::{PdfRenderConfig, Pdfium};
+
+/// The most pixels either side of a rendered page has, plenty to find its
+/// colors without rendering large pages at print resolution
+const MAX_RENDER_SIZE: i32 = 2048;
+
+/// Whether data is a PDF file
+pub(crate) fn is_pdf(data: &[u8]) -> bool {
+    data.starts_with(b"%PDF-")
+}
+
+/// Render one page of a PDF file with the system's PDFium library, e.g.
+/// `libpdfium.so`, which is loaded when needed
+///
+/// # Arguments
+/// * `data` - The PDF file's contents
+/// * `page` - The index of the page, starting at 0
+/// * `max_size` - The longest either side can be, below `MAX_RENDER_SIZE`
+/// * `limits` - The decoding limits, enforced on the rendered page
+pub(crate) fn rasterize_pdf_page(
+    data: &[u8],
+    page: u16,
+    max_size: Option<u32>,
+    limits: &Limits,
+) -> ImageResult<RgbaImage> {
+    let pdfium = Pdfium::new(Pdfium::bind_to_system_library().map_err(pdf_error)?);
+    let document = pdfium
+        .load_pdf_from_byte_slice(data, None)
+        .map_err(pdf_error)?;
+    let pages = document.pages();
+
+    if page >= pages.len() {
+        return Err(pdf_error(format!(
+            "page {} doesn't exist, the PDF has {} pages",
+            page,
+            pages.len()
+        )));
+    }
+
+    let render_size = max_size.map_or(MAX_RENDER_SIZE, |max_size| {
+        MAX_RENDER_SIZE.min(i32::try_from(max_size).unwrap_or(i32::MAX))
+    });
+    let config = PdfRenderConfig::new()
+        .set_maximum_width(render_size)
+        .set_maximum_height(render_size);
+    let page = pages.get(page).map_err(pdf_error)?;
+    let bitmap = page.render_with_config(&config).map_err(pdf_error)?;
+    let (width, height) = (bitmap.width() as u32, bitmap.height() as u32);
+
+    limits.check_dimensions(width, height)?;
+
+    RgbaImage::from_raw(width, height, bitmap.as_rgba_bytes())
+        .ok_or_else(|| pdf_error("Unexpected PDF bitmap size"))
+}
+
+fn pdf_error(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> ImageError {
+    ImageError::Decoding(DecodingError::new(
+        ImageFormatHint::Name("PDF".to_string()),
+        err,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_pdf() {
+        assert!(is_pdf(b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n"));
+        assert!(!is_pdf(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>"));
+    }
+}
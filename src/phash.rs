@@ -0,0 +1,91 @@
+use std::f32::consts::PI;
+
+use image::{imageops::FilterType, DynamicImage};
+
+/// The side of the grayscale thumbnail the hash is computed from
+const SIZE: usize = 32;
+/// The side of the block of lowest frequencies that makes up the hash
+const HASH_SIZE: usize = 8;
+
+/// Get a 64-bit perceptual hash of an image, which barely changes when the
+/// image is re-encoded, resized or slightly recolored. The number of
+/// differing bits, `(a ^ b).count_ones()`, tells how alike two images look
+///
+/// This is the DCT hash: the image is shrunk to 32x32 grayscale, and every
+/// bit says whether one of the 8x8 lowest frequencies of its discrete
+/// cosine transform is above their median.
+///
+/// # Arguments
+/// * `image` - A reference to the image to hash
+pub fn perceptual_hash(image: &DynamicImage) -> u64 {
+    let gray = image
+        .resize_exact(SIZE as u32, SIZE as u32, FilterType::Triangle)
+        .into_luma8();
+    let cosines: Vec<Vec<f32>> = (0..HASH_SIZE)
+        .map(|frequency| {
+            (0..SIZE)
+                .map(|x| ((2 * x + 1) as f32 * frequency as f32 * PI / (2 * SIZE) as f32).cos())
+                .collect()
+        })
+        .collect();
+    // The transform is separable, so transform the rows, then the columns
+    let mut rows = vec![[0.0_f32; HASH_SIZE]; SIZE];
+
+    for (y, row) in rows.iter_mut().enumerate() {
+        for (u, value) in row.iter_mut().enumerate() {
+            *value = (0..SIZE)
+                .map(|x| gray.get_pixel(x as u32, y as u32)[0] as f32 * cosines[u][x])
+                .sum();
+        }
+    }
+
+    let mut coefficients = [0.0_f32; HASH_SIZE * HASH_SIZE];
+
+    for (index, coefficient) in coefficients.iter_mut().enumerate() {
+        let (v, u) = (index / HASH_SIZE, index % HASH_SIZE);
+
+        *coefficient = (0..SIZE).map(|y| rows[y][u] * cosines[v][y]).sum();
+    }
+
+    // The first coefficient is the average brightness, which would skew
+    // the median
+    let mut sorted = coefficients[1..].to_vec();
+
+    sorted.sort_by(f32::total_cmp);
+
+    let median = sorted[sorted.len() / 2];
+
+    coefficients
+        .iter()
+        .enumerate()
+        .filter(|(_, coefficient)| **coefficient > median)
+        .fold(0, |hash, (index, _)| hash | 1 << index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    #[test]
+    fn test_perceptual_hash() {
+        let image = RgbImage::from_fn(120, 80, |x, y| {
+            let value = ((x as f32 / 9.0).sin() * (y as f32 / 13.0).cos() * 100.0 + 128.0) as u8;
+
+            Rgb([value, value / 2, 255 - value])
+        });
+        let hash = perceptual_hash(&DynamicImage::ImageRgb8(image.clone()));
+
+        // A smaller, slightly brighter copy hashes almost the same
+        let copy = DynamicImage::ImageRgb8(image.clone())
+            .resize_exact(60, 40, FilterType::Lanczos3)
+            .brighten(6);
+
+        assert!((hash ^ perceptual_hash(&copy)).count_ones() <= 8);
+
+        // A mirrored image doesn't
+        let mirrored = DynamicImage::ImageRgb8(image::imageops::flip_horizontal(&image));
+
+        assert!((hash ^ perceptual_hash(&mirrored)).count_ones() > 16);
+    }
+}
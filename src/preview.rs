@@ -0,0 +1,310 @@
+use std::{collections::BTreeMap, env, fmt::Write};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::{imageops::FilterType, DynamicImage, Rgba, RgbaImage};
+use tinted_builder::Base16Scheme;
+
+/// About how many pixels wide a terminal cell is, used to size sixel
+/// images, which can't be scaled to a number of columns
+const CELL_WIDTH: u32 = 8;
+/// The widest thumbnail sent with the kitty graphics protocol, which the
+/// terminal scales to the requested columns
+const MAX_KITTY_WIDTH: u32 = 512;
+/// The most base64 bytes in one kitty graphics escape sequence
+const KITTY_CHUNK_SIZE: usize = 4096;
+/// How many levels each channel has in the sixel palette, a 6x6x6 cube
+const SIXEL_LEVELS: u32 = 6;
+
+/// How a preview is drawn in a terminal
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PreviewProtocol {
+    /// The kitty graphics protocol, also supported by WezTerm and Ghostty
+    Kitty,
+    /// DEC sixel graphics, e.g. in foot, mlterm or xterm with sixel enabled
+    Sixel,
+    /// Truecolor half blocks, two pixels per cell, for any other terminal
+    Ansi,
+}
+
+impl PreviewProtocol {
+    /// Guess the protocol the terminal supports from its environment
+    /// variables, falling back to `Ansi`
+    pub fn detect() -> Self {
+        let term = env::var("TERM").unwrap_or_default();
+        let program = env::var("TERM_PROGRAM").unwrap_or_default();
+
+        if env::var_os("KITTY_WINDOW_ID").is_some()
+            || term.contains("kitty")
+            || term.contains("ghostty")
+            || program == "WezTerm"
+        {
+            PreviewProtocol::Kitty
+        } else if term.contains("sixel")
+            || term.starts_with("foot")
+            || term.starts_with("mlterm")
+            || term == "yaft-256color"
+        {
+            PreviewProtocol::Sixel
+        } else {
+            PreviewProtocol::Ansi
+        }
+    }
+}
+
+/// Render a thumbnail of the source image with a strip of the scheme's
+/// swatches below it, ready to print to the terminal
+///
+/// # Arguments
+/// * `image` - A reference to the image the scheme was extracted from
+/// * `scheme` - A reference to the generated Base16Scheme
+/// * `protocol` - How to draw the preview, e.g. `PreviewProtocol::detect()`
+/// * `columns` - How many terminal columns the preview is wide
+pub fn render_preview(
+    image: &DynamicImage,
+    scheme: &Base16Scheme,
+    protocol: PreviewProtocol,
+    columns: u32,
+) -> String {
+    let columns = columns.max(1);
+    let width = match protocol {
+        PreviewProtocol::Kitty => (columns * CELL_WIDTH).min(MAX_KITTY_WIDTH),
+        PreviewProtocol::Sixel => columns * CELL_WIDTH,
+        PreviewProtocol::Ansi => columns,
+    };
+    let preview = preview_image(image, scheme, width);
+
+    match protocol {
+        PreviewProtocol::Kitty => kitty_image(&preview, columns),
+        PreviewProtocol::Sixel => sixel_image(&preview),
+        PreviewProtocol::Ansi => ansi_image(&preview),
+    }
+}
+
+/// Stack a thumbnail `width` pixels wide on a strip of the slots' colors
+/// in slot order
+fn preview_image(image: &DynamicImage, scheme: &Base16Scheme, width: u32) -> RgbaImage {
+    let height = (image.height() as u64 * width as u64 / image.width().max(1) as u64).max(1) as u32;
+    let thumbnail = image
+        .resize_exact(width, height, FilterType::Triangle)
+        .into_rgba8();
+    let swatches: Vec<Rgba<u8>> = scheme
+        .palette
+        .iter()
+        .collect::<BTreeMap<_, _>>()
+        .into_values()
+        .map(|color| {
+            let (red, green, blue) = color.rgb;
+
+            Rgba([red, green, blue, 255])
+        })
+        .collect();
+    let strip_height = (width / swatches.len().max(1) as u32).clamp(2, 64);
+    let mut preview = RgbaImage::new(width, height + strip_height);
+
+    image::imageops::replace(&mut preview, &thumbnail, 0, 0);
+
+    for (x, y, pixel) in preview.enumerate_pixels_mut() {
+        if y >= height {
+            let slot = x as usize * swatches.len() / width as usize;
+
+            if let Some(swatch) = swatches.get(slot) {
+                *pixel = *swatch;
+            }
+        }
+    }
+
+    preview
+}
+
+/// Encode an image with the kitty graphics protocol as raw RGBA, split
+/// into chunks, scaled by the terminal to `columns`
+fn kitty_image(image: &RgbaImage, columns: u32) -> String {
+    let data = STANDARD.encode(image.as_raw());
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+    let mut output = String::new();
+
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(index + 1 < chunks.len());
+        // Chunks are base64, so always valid UTF-8
+        let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+
+        if index == 0 {
+            let _ = write!(
+                output,
+                "\x1b_Ga=T,q=2,f=32,s={},v={},c={},m={};{}\x1b\\",
+                image.width(),
+                image.height(),
+                columns,
+                more,
+                chunk
+            );
+        } else {
+            let _ = write!(output, "\x1b_Gm={};{}\x1b\\", more, chunk);
+        }
+    }
+
+    output.push('\n');
+    output
+}
+
+/// Encode an image as sixels, with its colors reduced to a 6x6x6 cube
+fn sixel_image(image: &RgbaImage) -> String {
+    let (width, height) = image.dimensions();
+    let level = |channel: u8| (channel as u32 * (SIXEL_LEVELS - 1) + 127) / 255;
+    let registers: Vec<u32> = image
+        .pixels()
+        .map(|pixel| {
+            let [red, green, blue, _] = pixel.0;
+
+            (level(red) * SIXEL_LEVELS + level(green)) * SIXEL_LEVELS + level(blue)
+        })
+        .collect();
+    let mut output = format!("\x1bPq\"1;1;{};{}", width, height);
+    let percent = |level: u32| level * 100 / (SIXEL_LEVELS - 1);
+
+    for register in 0..SIXEL_LEVELS.pow(3) {
+        let _ = write!(
+            output,
+            "#{};2;{};{};{}",
+            register,
+            percent(register / (SIXEL_LEVELS * SIXEL_LEVELS)),
+            percent(register / SIXEL_LEVELS % SIXEL_LEVELS),
+            percent(register % SIXEL_LEVELS)
+        );
+    }
+
+    // Every band of six rows is drawn once per color in it, going back to
+    // the start of the band in between
+    for band in (0..height).step_by(6) {
+        let rows = band..(band + 6).min(height);
+        let mut colors: Vec<u32> = rows
+            .clone()
+            .flat_map(|y| &registers[(y * width) as usize..((y + 1) * width) as usize])
+            .copied()
+            .collect();
+
+        colors.sort_unstable();
+        colors.dedup();
+
+        for (index, color) in colors.iter().enumerate() {
+            if index > 0 {
+                output.push('$');
+            }
+
+            let _ = write!(output, "#{}", color);
+            let sixels = (0..width).map(|x| {
+                let bits = rows
+                    .clone()
+                    .filter(|y| registers[(y * width + x) as usize] == *color)
+                    .fold(0, |bits, y| bits | 1 << (y - band));
+
+                char::from(63 + bits as u8)
+            });
+
+            push_run_length(&mut output, sixels);
+        }
+
+        output.push('-');
+    }
+
+    output.push_str("\x1b\\\n");
+    output
+}
+
+/// Append sixel characters, with repeats of more than three written as
+/// `!<count><sixel>`
+fn push_run_length(output: &mut String, sixels: impl Iterator<Item = char>) {
+    let mut run: Option<(char, usize)> = None;
+    let flush = |output: &mut String, run: Option<(char, usize)>| match run {
+        Some((sixel, count)) if count > 3 => {
+            let _ = write!(output, "!{}{}", count, sixel);
+        }
+        Some((sixel, count)) => output.extend(std::iter::repeat_n(sixel, count)),
+        None => {}
+    };
+
+    for sixel in sixels {
+        run = match run {
+            Some((previous, count)) if previous == sixel => Some((sixel, count + 1)),
+            _ => {
+                flush(output, run);
+
+                Some((sixel, 1))
+            }
+        };
+    }
+
+    flush(output, run);
+}
+
+/// Draw an image with upper half blocks, the foreground color the upper
+/// pixel and the background color the lower one
+fn ansi_image(image: &RgbaImage) -> String {
+    let (width, height) = image.dimensions();
+    let mut output = String::new();
+
+    for y in (0..height).step_by(2) {
+        for x in 0..width {
+            let [red, green, blue, _] = image.get_pixel(x, y).0;
+            let [lower_red, lower_green, lower_blue, _] =
+                image.get_pixel(x, (y + 1).min(height - 1)).0;
+
+            let _ = write!(
+                output,
+                "\x1b[38;2;{};{};{};48;2;{};{};{}m\u{2580}",
+                red, green, blue, lower_red, lower_green, lower_blue
+            );
+        }
+
+        output.push_str("\x1b[0m\n");
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tinted_builder::{Color, SchemeSystem, SchemeVariant};
+
+    #[test]
+    fn test_render_preview() {
+        let palette: HashMap<String, Color> = [("base00", "000000"), ("base08", "ff0000")]
+            .into_iter()
+            .map(|(slot, hex)| (slot.to_string(), Color::new(hex.to_string()).unwrap()))
+            .collect();
+        let scheme = Base16Scheme {
+            system: SchemeSystem::Base16,
+            name: "Preview".to_string(),
+            slug: "preview".to_string(),
+            author: "Preview".to_string(),
+            description: None,
+            variant: SchemeVariant::Dark,
+            palette,
+        };
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_pixel(8, 4, Rgba([0, 0, 255, 255])));
+
+        // 4 columns: a 4x2 thumbnail on a strip 2 pixels high
+        let ansi = render_preview(&image, &scheme, PreviewProtocol::Ansi, 4);
+
+        assert_eq!(ansi.lines().count(), 2);
+        assert!(ansi.starts_with("\x1b[38;2;0;0;255;48;2;0;0;255m\u{2580}"));
+        assert!(ansi
+            .lines()
+            .nth(1)
+            .unwrap()
+            .ends_with("\x1b[38;2;255;0;0;48;2;255;0;0m\u{2580}\x1b[0m"));
+
+        let kitty = render_preview(&image, &scheme, PreviewProtocol::Kitty, 4);
+
+        assert!(kitty.starts_with("\x1b_Ga=T,q=2,f=32,s=32,v=32,c=4,m=1;"));
+
+        let sixel = render_preview(&image, &scheme, PreviewProtocol::Sixel, 4);
+
+        assert!(sixel.starts_with("\x1bPq\"1;1;32;32#0;2;0;0;0"));
+        assert!(sixel.ends_with("-\x1b\\\n"));
+        // The blue thumbnail fills the first band in one run
+        assert!(sixel.contains("#5!32~"));
+    }
+}
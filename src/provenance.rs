@@ -0,0 +1,328 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use palette::{FromColor, Hsl, Srgb};
+use tinted_builder::{Color as SchemeColor, SchemeSystem, SchemeVariant};
+
+use crate::{
+    color::Color, confidence::ACCENT_PURE_COLORS, utils::second_dominant_color, Base0FPolicy,
+    Diagnostics, Extraction, ExtractionOptions,
+};
+
+/// Lightness shifts smaller than this are left out of comments
+const MIN_LIGHTNESS_SHIFT: f32 = 0.005;
+
+/// Where the color of a slot came from, for reviewers deciding which
+/// colors are safe to tune by hand
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SlotProvenance {
+    /// What the color was picked from, e.g. `color_thief cluster 2` or
+    /// `a synthesized color`
+    pub source: String,
+    /// The pure color the extracted color was matched to, e.g. `blue` for
+    /// base0D, for accents found in the image
+    pub pure_color: Option<String>,
+    /// The CIEDE2000 difference between the extracted color and its pure
+    /// color
+    pub delta_e: Option<f32>,
+    /// How much the HSL lightness changed from the extracted color to the
+    /// slot's, from -1.0 to 1.0, e.g. to keep accents visible or the
+    /// background dark enough. Grays between base00 and base07 have none
+    pub lightness_shift: Option<f32>,
+}
+
+impl fmt::Display for SlotProvenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "from {}", self.source)?;
+
+        if let (Some(pure_color), Some(delta_e)) = (&self.pure_color, self.delta_e) {
+            write!(f, ", ΔE {:.0} to pure {}", delta_e, pure_color)?;
+        }
+
+        match self.lightness_shift {
+            Some(shift) if shift.abs() >= MIN_LIGHTNESS_SHIFT => {
+                write!(f, ", lightness {:+.2}", shift)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Render the scheme YAML of an extraction with a comment after every slot
+/// saying where its color came from, e.g. `# from color_thief cluster 2,
+/// ΔE 12 to pure blue, lightness +0.18`
+///
+/// # Arguments
+/// * `extraction` - A reference to the extraction, whose
+///   `Diagnostics::provenance` fills the comments
+pub fn scheme_yaml_with_provenance(extraction: &Extraction) -> String {
+    let provenance = &extraction.diagnostics.provenance;
+    let mut yaml = String::new();
+
+    for line in extraction.scheme.to_string().lines() {
+        yaml.push_str(line);
+
+        let slot = line.trim_start().split(':').next().unwrap_or_default();
+
+        if let Some(slot_provenance) = provenance.get(slot) {
+            yaml.push_str(" # ");
+            yaml.push_str(&slot_provenance.to_string());
+        }
+
+        yaml.push('\n');
+    }
+
+    yaml
+}
+
+/// Work out where the color of every slot came from
+///
+/// base00 and base07 come from the anchors, or from the locked background,
+/// and the grays between them from the gradient. Accents come from the
+/// extracted colors matched to their pure color, and are synthesized when
+/// none matched, or from the duotone hues. The lightness shifts are
+/// measured against the anchors and the extracted colors.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn slot_provenance(
+    palette: &HashMap<String, SchemeColor>,
+    system: &SchemeSystem,
+    variant: &SchemeVariant,
+    diagnostics: &Diagnostics,
+    accents: &[Color],
+    color_thief_palette: &[Srgb<u8>],
+    anchors: (Srgb<u8>, Srgb<u8>),
+    options: &ExtractionOptions,
+) -> BTreeMap<String, SlotProvenance> {
+    let (dark, light) = anchors;
+    let anchor = |name: &str, passes: u32, pass_matched: bool| match (passes, pass_matched) {
+        (0, _) => format!("the {} anchor picked by the AnchorSelector", name),
+        (passes, true) => format!("the {} anchor, ladder pass {}", name, passes),
+        (_, false) => format!("the {} anchor, the most dominant color", name),
+    };
+    let dark_source = anchor(
+        "dark",
+        diagnostics.dark_passes,
+        diagnostics.dark_pass.is_some(),
+    );
+    let light_source = anchor(
+        "light",
+        diagnostics.light_passes,
+        diagnostics.light_pass.is_some(),
+    );
+    let ((background_source, background), (foreground_source, foreground)) = match variant {
+        SchemeVariant::Light => ((light_source, light), (dark_source, dark)),
+        _ => ((dark_source, dark), (light_source, light)),
+    };
+    let mut provenance = BTreeMap::new();
+
+    if let Some(slot) = palette.get("base00") {
+        let (source, shift) = match options.background {
+            Some(_) => ("the locked background".to_string(), None),
+            None => (background_source, Some(lightness_shift(background, slot))),
+        };
+
+        provenance.insert("base00".to_string(), provenance_of(source, shift));
+    }
+
+    for index in 1..7 {
+        provenance.insert(
+            format!("base0{}", index),
+            provenance_of("the gradient between base00 and base07".to_string(), None),
+        );
+    }
+
+    if let Some(slot) = palette.get("base07") {
+        provenance.insert(
+            "base07".to_string(),
+            provenance_of(foreground_source, Some(lightness_shift(foreground, slot))),
+        );
+    }
+
+    let offsets: &[usize] = match system {
+        SchemeSystem::Base24 => &[0x08, 0x10],
+        _ => &[0x08],
+    };
+    let cluster =
+        |value: Srgb<u8>| match color_thief_palette.iter().position(|color| *color == value) {
+            Some(rank) => format!("color_thief cluster {}", rank + 1),
+            None => "the image's pixels".to_string(),
+        };
+    let second_hue = match options.base0f {
+        Base0FPolicy::SecondDominantHue => second_dominant_color(color_thief_palette),
+        _ => None,
+    };
+    let duotone = options.duotone && diagnostics.dominant_hue.is_some();
+    let pure_colors = ACCENT_PURE_COLORS
+        .iter()
+        .copied()
+        .chain([options.base0f.pure_color()]);
+
+    for (index, pure_color) in pure_colors.enumerate() {
+        let extracted = match second_hue.filter(|_| index == 7 && !duotone) {
+            Some(value) => Some((
+                format!("second dominant hue, {}", cluster(value)),
+                value,
+                None,
+            )),
+            None => accents
+                .iter()
+                .filter(|_| !duotone)
+                .find(|color| color.associated_pure_color == pure_color)
+                .map(|color| {
+                    let delta_e =
+                        Color::get_delta_e(&color.associated_pure_color.get_rgb(), &color.value);
+
+                    (cluster(color.value), color.value, Some(delta_e))
+                }),
+        };
+
+        for offset in offsets {
+            let slot = format!("base{:02X}", offset + index);
+            let Some(color) = palette.get(&slot) else {
+                continue;
+            };
+            let slot_provenance = match &extracted {
+                Some((source, value, delta_e)) => SlotProvenance {
+                    source: source.clone(),
+                    pure_color: delta_e.map(|_| pure_color.to_string()),
+                    delta_e: *delta_e,
+                    lightness_shift: Some(lightness_shift(*value, color)),
+                },
+                None if duotone => provenance_of("the duotone hues".to_string(), None),
+                None => provenance_of("a synthesized color".to_string(), None),
+            };
+
+            provenance.insert(slot, slot_provenance);
+        }
+    }
+
+    provenance.retain(|slot, _| palette.contains_key(slot));
+    provenance
+}
+
+/// The provenance of a single-color palette, whose grays are built from
+/// one color and whose accents are all synthesized
+pub(crate) fn flat_provenance(
+    palette: &HashMap<String, SchemeColor>,
+    confidence: &BTreeMap<String, f32>,
+) -> BTreeMap<String, SlotProvenance> {
+    palette
+        .keys()
+        .map(|slot| {
+            let source = match confidence.get(slot) {
+                Some(confidence) if *confidence > 0.0 => "the image's single color",
+                _ => "a synthesized color",
+            };
+
+            (slot.clone(), provenance_of(source.to_string(), None))
+        })
+        .collect()
+}
+
+fn provenance_of(source: String, lightness_shift: Option<f32>) -> SlotProvenance {
+    SlotProvenance {
+        source,
+        pure_color: None,
+        delta_e: None,
+        lightness_shift,
+    }
+}
+
+/// The change in HSL lightness from an extracted color to a slot's color
+fn lightness_shift(extracted: Srgb<u8>, slot: &SchemeColor) -> f32 {
+    let (red, green, blue) = slot.rgb;
+    let lightness = |color: Srgb<u8>| Hsl::from_color(color.into_format::<f32>()).lightness;
+
+    lightness(Srgb::new(red, green, blue)) - lightness(extracted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::PureColor, utils::scheme_color};
+    use tinted_builder::Base16Scheme;
+
+    #[test]
+    fn test_slot_provenance() {
+        let palette: HashMap<String, SchemeColor> = [
+            ("base00", "202020"),
+            ("base05", "c0c0c0"),
+            ("base07", "f0f0f0"),
+            ("base08", "e01010"),
+            ("base0D", "3060e0"),
+        ]
+        .iter()
+        .map(|(slot, hex)| (slot.to_string(), scheme_color(hex.to_string()).unwrap()))
+        .collect();
+        let red = Srgb::new(200, 10, 10);
+        let diagnostics = Diagnostics {
+            light_passes: 2,
+            light_pass: Some(Default::default()),
+            dark_passes: 7,
+            dark_pass: None,
+            ..Diagnostics::default()
+        };
+        let provenance = slot_provenance(
+            &palette,
+            &SchemeSystem::Base16,
+            &SchemeVariant::Dark,
+            &diagnostics,
+            &[Color::new(PureColor::Red, red)],
+            &[Srgb::new(10, 10, 10), red],
+            (Srgb::new(0, 0, 0), Srgb::new(240, 240, 240)),
+            &ExtractionOptions::default(),
+        );
+
+        assert_eq!(provenance.len(), 5);
+        assert_eq!(
+            provenance["base00"].to_string(),
+            "from the dark anchor, the most dominant color, lightness +0.13"
+        );
+        assert_eq!(
+            provenance["base05"].to_string(),
+            "from the gradient between base00 and base07"
+        );
+        assert_eq!(
+            provenance["base07"].to_string(),
+            "from the light anchor, ladder pass 2"
+        );
+        assert!(provenance["base08"]
+            .to_string()
+            .starts_with("from color_thief cluster 2, ΔE "));
+        assert!(provenance["base08"]
+            .to_string()
+            .ends_with(" to pure red, lightness +0.06"));
+        assert_eq!(provenance["base0D"].to_string(), "from a synthesized color");
+
+        let diagnostics = Diagnostics {
+            provenance,
+            ..Diagnostics::default()
+        };
+
+        let extraction = Extraction {
+            scheme: Base16Scheme {
+                name: "Test".to_string(),
+                author: "Author".to_string(),
+                slug: "test".to_string(),
+                description: None,
+                system: SchemeSystem::Base16,
+                variant: SchemeVariant::Dark,
+                palette,
+            },
+            diagnostics,
+            surfaces: None,
+            accessibility: None,
+            report: None,
+        };
+        let yaml = scheme_yaml_with_provenance(&extraction);
+
+        assert!(yaml
+            .lines()
+            .any(|line| line.trim_start().starts_with("base0D:")
+                && line.ends_with(" # from a synthesized color")));
+        assert_eq!(yaml.matches(" # from ").count(), 5);
+    }
+}
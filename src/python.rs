@@ -0,0 +1,146 @@
+use std::{path::PathBuf, str::FromStr};
+
+use pyo3::{create_exception, exceptions::PyException, prelude::*, types::PyDict};
+
+use crate::{
+    utils::error_chain, CropRegion, Error, ExtractionOptions, SchemeParams, SchemeSystem,
+    SchemeVariant,
+};
+
+create_exception!(
+    tinted_scheme_extractor,
+    ExtractionError,
+    PyException,
+    "Raised when a scheme can't be extracted from an image"
+);
+
+impl From<Error> for PyErr {
+    fn from(err: Error) -> PyErr {
+        ExtractionError::new_err(error_chain(&err))
+    }
+}
+
+/// Python view of `ExtractionOptions`
+#[pyclass(name = "ExtractionOptions")]
+#[derive(Clone, Default)]
+struct PyExtractionOptions {
+    /// `(x, y, width, height)` of the part of the image to use
+    #[pyo3(get, set)]
+    crop: Option<(u32, u32, u32, u32)>,
+    #[pyo3(get, set)]
+    center_weight: f32,
+    #[pyo3(get, set)]
+    sample_every: usize,
+    #[pyo3(get, set)]
+    max_dimension: Option<u32>,
+}
+
+#[pymethods]
+impl PyExtractionOptions {
+    #[new]
+    #[pyo3(signature = (crop = None, center_weight = 0.0, sample_every = 0, max_dimension = None))]
+    fn new(
+        crop: Option<(u32, u32, u32, u32)>,
+        center_weight: f32,
+        sample_every: usize,
+        max_dimension: Option<u32>,
+    ) -> Self {
+        PyExtractionOptions {
+            crop,
+            center_weight,
+            sample_every,
+            max_dimension,
+        }
+    }
+}
+
+impl From<PyExtractionOptions> for ExtractionOptions {
+    fn from(options: PyExtractionOptions) -> Self {
+        ExtractionOptions {
+            crop: options.crop.map(|(x, y, width, height)| CropRegion {
+                x,
+                y,
+                width,
+                height,
+            }),
+            center_weight: options.center_weight,
+            sample_every: options.sample_every,
+            max_dimension: options.max_dimension,
+            ..ExtractionOptions::default()
+        }
+    }
+}
+
+/// Create a scheme from an image file, returning a dict with the scheme's
+/// metadata and a `palette` dict of `#rrggbb` colors by slot
+#[pyfunction]
+#[pyo3(signature = (
+    image_path,
+    name,
+    slug,
+    author,
+    system = "base16",
+    variant = "dark",
+    description = None,
+    options = None
+))]
+#[allow(clippy::too_many_arguments)]
+fn create_scheme_from_image<'py>(
+    py: Python<'py>,
+    image_path: PathBuf,
+    name: String,
+    slug: String,
+    author: String,
+    system: &str,
+    variant: &str,
+    description: Option<String>,
+    options: Option<PyExtractionOptions>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let params = SchemeParams {
+        image_path,
+        author,
+        description,
+        name,
+        slug,
+        system: SchemeSystem::from_str(system).map_err(|_| Error::UnsupportedSchemeSystem {
+            system: system.to_string(),
+        })?,
+        variant: SchemeVariant::from_str(variant).map_err(|_| Error::UnsupportedSchemeVariant {
+            variant: variant.to_string(),
+        })?,
+        verbose: false,
+        options: options.unwrap_or_default().into(),
+    };
+    let scheme = py.allow_threads(|| crate::create_scheme_from_image(params))?;
+    let palette = PyDict::new(py);
+    let mut slots: Vec<_> = scheme.palette.iter().collect();
+
+    slots.sort_by_key(|(slot, _)| *slot);
+
+    for (slot, color) in slots {
+        let (red, green, blue) = color.rgb;
+
+        palette.set_item(slot, format!("#{:02x}{:02x}{:02x}", red, green, blue))?;
+    }
+
+    let dict = PyDict::new(py);
+
+    dict.set_item("system", scheme.system.to_string())?;
+    dict.set_item("variant", scheme.variant.to_string())?;
+    dict.set_item("name", scheme.name)?;
+    dict.set_item("slug", scheme.slug)?;
+    dict.set_item("author", scheme.author)?;
+    dict.set_item("description", scheme.description)?;
+    dict.set_item("palette", palette)?;
+
+    Ok(dict)
+}
+
+#[pymodule]
+fn tinted_scheme_extractor(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyExtractionOptions>()?;
+    module.add_function(wrap_pyfunction!(create_scheme_from_image, module)?)?;
+    module.add("ExtractionError", module.py().get_type::<ExtractionError>())?;
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use tinted_builder::Base16Scheme;
+
+use crate::{
+    export::{single_line, slot_hex},
+    Error,
+};
+
+/// The roles of QPalette in the order of qt5ct's and qt6ct's color lists,
+/// with their active and disabled slots
+const ROLES: [(&str, &str, &str); 21] = [
+    ("WindowText", "base05", "base03"),
+    ("Button", "base01", "base01"),
+    ("Light", "base03", "base02"),
+    ("Midlight", "base02", "base02"),
+    ("Dark", "base00", "base00"),
+    ("Mid", "base02", "base01"),
+    ("Text", "base05", "base03"),
+    ("BrightText", "base07", "base04"),
+    ("ButtonText", "base05", "base03"),
+    ("Base", "base00", "base01"),
+    ("Window", "base01", "base01"),
+    ("Shadow", "base00", "base00"),
+    ("Highlight", "base0D", "base02"),
+    ("HighlightedText", "base00", "base03"),
+    ("Link", "base0D", "base03"),
+    ("LinkVisited", "base0E", "base03"),
+    ("AlternateBase", "base01", "base01"),
+    ("NoRole", "base00", "base00"),
+    ("ToolTipBase", "base02", "base02"),
+    ("ToolTipText", "base05", "base03"),
+    ("PlaceholderText", "base03", "base02"),
+];
+
+/// Render a qt5ct or qt6ct color scheme, to save in
+/// `~/.config/qt5ct/colors` or `~/.config/qt6ct/colors`. Active and
+/// inactive windows share their colors, disabled widgets use the darker
+/// grays
+///
+/// # Arguments
+/// * `scheme` - A reference to the generated Base16Scheme
+pub fn qt_color_scheme(scheme: &Base16Scheme) -> Result<String, Error> {
+    let mut active = Vec::with_capacity(ROLES.len());
+    let mut disabled = Vec::with_capacity(ROLES.len());
+
+    for (_, active_slot, disabled_slot) in ROLES {
+        active.push(slot_hex(scheme, active_slot)?);
+        disabled.push(slot_hex(scheme, disabled_slot)?);
+    }
+
+    // QSettings reads lines starting with a semicolon as comments
+    Ok(format!(
+        "; {} by {}\n[ColorScheme]\nactive_colors={}\ndisabled_colors={}\ninactive_colors={}\n",
+        single_line(&scheme.name),
+        single_line(&scheme.author),
+        active.join(", "),
+        disabled.join(", "),
+        active.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::test_scheme;
+    use tinted_builder::SchemeSystem;
+
+    #[test]
+    fn test_qt_color_scheme() {
+        let colors = qt_color_scheme(&test_scheme(SchemeSystem::Base16)).unwrap();
+        let lines: Vec<&str> = colors.lines().collect();
+
+        assert_eq!(lines[0], "; Test \"Scheme\" by Author");
+        assert_eq!(lines[1], "[ColorScheme]");
+        assert!(lines[2].starts_with("active_colors=#b6b6b6, #242424, "));
+        assert_eq!(lines[2].split(", ").count(), 21);
+        assert!(lines[3].starts_with("disabled_colors=#6d6d6d, "));
+        assert_eq!(
+            lines[4].strip_prefix("inactive_colors="),
+            lines[2].strip_prefix("active_colors=")
+        );
+        assert_eq!(lines.len(), 5);
+    }
+}
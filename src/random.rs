@@ -0,0 +1,81 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use image::ImageFormat;
+
+use crate::Error;
+
+const ASPECT_RATIO_TOLERANCE: f32 = 0.01;
+
+/// Constraints an image must satisfy to be picked by `pick_random_image`
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RandomImageFilter {
+    /// Width divided by height, e.g. `16.0 / 9.0`. Matched within 1%
+    pub aspect_ratio: Option<f32>,
+    pub min_width: Option<u32>,
+    pub min_height: Option<u32>,
+}
+
+impl RandomImageFilter {
+    fn matches(&self, path: &Path) -> bool {
+        if self.aspect_ratio.is_none() && self.min_width.is_none() && self.min_height.is_none() {
+            return true;
+        }
+
+        // Only the image header is read here
+        let Ok((width, height)) = image::image_dimensions(path) else {
+            return false;
+        };
+
+        if height == 0 {
+            return false;
+        }
+
+        let aspect_ratio_check = match self.aspect_ratio {
+            Some(aspect_ratio) => {
+                let ratio = width as f32 / height as f32;
+
+                (ratio - aspect_ratio).abs() <= aspect_ratio * ASPECT_RATIO_TOLERANCE
+            }
+            None => true,
+        };
+        let width_check = self.min_width.is_none_or(|min| width >= min);
+        let height_check = self.min_height.is_none_or(|min| height >= min);
+
+        aspect_ratio_check && width_check && height_check
+    }
+}
+
+/// Pick a random image from a directory
+/// Only files with an extension the `image` crate can decode are considered.
+/// The directory isn't searched recursively.
+///
+/// # Arguments
+/// * `dir` - A reference to the directory to pick from
+/// * `filter` - A reference to a RandomImageFilter the picked image must match
+pub fn pick_random_image(dir: &Path, filter: &RandomImageFilter) -> Result<PathBuf, Error> {
+    let entries = fs::read_dir(dir).map_err(|source| Error::NoImagesInDir {
+        dir: dir.to_path_buf(),
+        source: Some(source),
+    })?;
+    let mut candidates: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && ImageFormat::from_path(path).is_ok())
+        .filter(|path| filter.matches(path))
+        .collect();
+
+    if candidates.is_empty() {
+        return Err(Error::NoImagesInDir {
+            dir: dir.to_path_buf(),
+            source: None,
+        });
+    }
+
+    let index = fastrand::usize(..candidates.len());
+
+    Ok(candidates.swap_remove(index))
+}
@@ -0,0 +1,202 @@
+use std::path::Path;
+
+use image::{
+    error::{DecodingError, ImageFormatHint},
+    ImageError, ImageResult, Limits, Rgba, RgbaImage,
+};
+use palette::{LinSrgb, Srgb};
+use rawloader::{RawImage, RawImageData};
+
+/// The file extensions of camera RAW files
+const RAW_EXTENSIONS: [&str; 15] = [
+    "3fr", "arw", "cr2", "crw", "dcr", "dng", "erf", "kdc", "mrw", "nef", "nrw", "orf", "pef",
+    "raf", "rw2",
+];
+/// Linear sRGB to CIE XYZ under D65, the white point of the camera matrices
+const SRGB_TO_XYZ: [[f32; 3]; 3] = [
+    [0.412_456_4, 0.357_576_1, 0.180_437_5],
+    [0.212_672_9, 0.715_152_2, 0.072_175],
+    [0.019_333_9, 0.119_192, 0.950_304_1],
+];
+const IDENTITY: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+/// Whether a path has the extension of a camera RAW file
+pub(crate) fn is_raw(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| RAW_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+/// Develop a camera RAW file with rawloader
+///
+/// Every 2x2 block of a Bayer sensor becomes one pixel, which halves the
+/// resolution but needs no interpolation. The sensor values are scaled
+/// between the black and white levels, multiplied by the camera's white
+/// balance, converted to sRGB with the camera's color matrix and clipped.
+///
+/// # Arguments
+/// * `path` - The path of the RAW file
+/// * `limits` - The decoding limits, enforced on the developed image
+pub(crate) fn decode_raw(path: &Path, limits: &Limits) -> ImageResult<RgbaImage> {
+    let raw = rawloader::decode_file(path).map_err(|err| raw_error(err.to_string()))?;
+    let [top, right, bottom, left] = raw.crops;
+    let block = if raw.cpp == 1 { 2 } else { 1 };
+    let width = raw.width.saturating_sub(left + right) / block;
+    let height = raw.height.saturating_sub(top + bottom) / block;
+
+    limits.check_dimensions(width as u32, height as u32)?;
+
+    if width == 0 || height == 0 {
+        return Err(raw_error("The RAW image has no area"));
+    }
+
+    let white_balance = white_balance(raw.wb_coeffs);
+    let camera_to_srgb = camera_to_srgb(&raw.xyz_to_cam);
+
+    Ok(RgbaImage::from_fn(width as u32, height as u32, |x, y| {
+        let camera = block_color(
+            &raw,
+            top + y as usize * block,
+            left + x as usize * block,
+            block,
+        );
+        // Clipping after the white balance keeps blown highlights white
+        let balanced = [0, 1, 2].map(|color| (camera[color] * white_balance[color]).min(1.0));
+        let [red, green, blue] = camera_to_srgb.map(|row| {
+            row.iter()
+                .zip(balanced)
+                .map(|(weight, value)| weight * value)
+                .sum::<f32>()
+                .clamp(0.0, 1.0)
+        });
+        let color: Srgb<u8> = Srgb::from_linear(LinSrgb::new(red, green, blue));
+
+        Rgba([color.red, color.green, color.blue, 255])
+    }))
+}
+
+/// Get the mean red, green and blue sensor values of a block of pixels,
+/// between 0.0 at the black level and 1.0 at the white level
+fn block_color(raw: &RawImage, row: usize, column: usize, block: usize) -> [f32; 3] {
+    let mut sums = [0.0; 3];
+    let mut counts = [0.0_f32; 3];
+
+    for row in row..row + block {
+        for column in column..column + block {
+            for channel in 0..raw.cpp {
+                let color = match raw.cpp {
+                    1 => raw.cfa.color_at(row, column),
+                    _ => channel,
+                };
+                // The fourth color of RGBE and similar sensors is closest
+                // to green
+                let color = color.min(3);
+                let level = if color == 3 { 1 } else { color };
+                let index = (row * raw.width + column) * raw.cpp + channel;
+                let value = match &raw.data {
+                    RawImageData::Integer(data) => data[index] as f32,
+                    RawImageData::Float(data) => data[index],
+                };
+                let black = raw.blacklevels[color] as f32;
+                let white = raw.whitelevels[color] as f32;
+
+                sums[level] += ((value - black) / (white - black).max(1.0)).clamp(0.0, 1.0);
+                counts[level] += 1.0;
+            }
+        }
+    }
+
+    [0, 1, 2].map(|color| sums[color] / counts[color].max(1.0))
+}
+
+/// The white balance multipliers relative to green, or none when the
+/// camera didn't record usable ones
+fn white_balance(coefficients: [f32; 4]) -> [f32; 3] {
+    let [red, green, blue, _] = coefficients;
+
+    if [red, green, blue]
+        .iter()
+        .all(|coefficient| coefficient.is_finite() && *coefficient > 0.0)
+    {
+        [red / green, 1.0, blue / green]
+    } else {
+        [1.0; 3]
+    }
+}
+
+/// The matrix converting white balanced camera values to linear sRGB, the
+/// inverse of the camera's sRGB to camera matrix with its rows scaled so
+/// white stays white. Unknown cameras keep their values
+fn camera_to_srgb(xyz_to_camera: &[[f32; 3]; 4]) -> [[f32; 3]; 3] {
+    let mut srgb_to_camera = [[0.0; 3]; 3];
+
+    for (row, camera_row) in srgb_to_camera.iter_mut().zip(xyz_to_camera) {
+        for (column, value) in row.iter_mut().enumerate() {
+            *value = (0..3)
+                .map(|index| camera_row[index] * SRGB_TO_XYZ[index][column])
+                .sum();
+        }
+
+        let sum: f32 = row.iter().sum();
+
+        if sum.abs() > f32::EPSILON {
+            row.iter_mut().for_each(|value| *value /= sum);
+        }
+    }
+
+    invert(srgb_to_camera).unwrap_or(IDENTITY)
+}
+
+/// Invert a 3x3 matrix, or `None` when it's singular
+fn invert(m: [[f32; 3]; 3]) -> Option<[[f32; 3]; 3]> {
+    let cofactor = |row: usize, column: usize| {
+        let (r1, r2) = ((row + 1) % 3, (row + 2) % 3);
+        let (c1, c2) = ((column + 1) % 3, (column + 2) % 3);
+
+        m[r1][c1] * m[r2][c2] - m[r1][c2] * m[r2][c1]
+    };
+    let determinant: f32 = (0..3)
+        .map(|column| m[0][column] * cofactor(0, column))
+        .sum();
+
+    if determinant.abs() <= f32::EPSILON {
+        return None;
+    }
+
+    // The inverse is the transposed cofactor matrix over the determinant
+    Some([0, 1, 2].map(|row| [0, 1, 2].map(|column| cofactor(column, row) / determinant)))
+}
+
+fn raw_error(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> ImageError {
+    ImageError::Decoding(DecodingError::new(
+        ImageFormatHint::Name("RAW".to_string()),
+        err,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_camera_to_srgb() {
+        assert!(is_raw(Path::new("IMG_0001.CR2")));
+        assert!(is_raw(Path::new("photo.dng")));
+        assert!(!is_raw(Path::new("photo.jpg")));
+
+        // A camera that sees sRGB as it is converts back to the identity
+        let mut xyz_to_camera = [[0.0; 3]; 4];
+
+        xyz_to_camera[..3].copy_from_slice(&invert(SRGB_TO_XYZ).unwrap());
+
+        for (row, identity_row) in camera_to_srgb(&xyz_to_camera).iter().zip(IDENTITY) {
+            for (value, expected) in row.iter().zip(identity_row) {
+                assert!((value - expected).abs() < 1e-3);
+            }
+        }
+
+        assert_eq!(camera_to_srgb(&[[0.0; 3]; 4]), IDENTITY);
+        assert_eq!(white_balance([2.0, 1.0, 1.5, f32::NAN]), [2.0, 1.0, 1.5]);
+        assert_eq!(white_balance([0.0, 0.0, 0.0, 0.0]), [1.0; 3]);
+    }
+}
@@ -0,0 +1,205 @@
+use image::{DynamicImage, GenericImageView};
+
+use crate::{extract_scheme_from_dynamic_image, CropRegion, Error, Extraction, SchemeParams};
+
+/// How an image is split into regions
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RegionLayout {
+    /// Cells of equal size, left to right and then top to bottom, e.g.
+    /// `columns: 2, rows: 1` for two monitors side by side
+    Grid { columns: u32, rows: u32 },
+    /// Regions in image pixel coordinates, e.g. the part of the wallpaper
+    /// each monitor shows
+    Regions(Vec<CropRegion>),
+}
+
+impl RegionLayout {
+    /// Get the regions of an image
+    ///
+    /// # Arguments
+    /// * `width` - The width of the image
+    /// * `height` - The height of the image
+    pub fn regions(&self, width: u32, height: u32) -> Result<Vec<CropRegion>, Error> {
+        match self {
+            RegionLayout::Grid { columns, rows } => {
+                if *columns == 0 || *rows == 0 || *columns > width || *rows > height {
+                    return Err(Error::InvalidGrid {
+                        columns: *columns,
+                        rows: *rows,
+                        width,
+                        height,
+                    });
+                }
+
+                // Multiply before dividing so the cells cover every pixel
+                let edge = |index: u32, count: u32, size: u32| {
+                    (u64::from(index) * u64::from(size) / u64::from(count)) as u32
+                };
+
+                Ok((0..*rows)
+                    .flat_map(|row| (0..*columns).map(move |column| (column, row)))
+                    .map(|(column, row)| {
+                        let x = edge(column, *columns, width);
+                        let y = edge(row, *rows, height);
+
+                        CropRegion {
+                            x,
+                            y,
+                            width: edge(column + 1, *columns, width) - x,
+                            height: edge(row + 1, *rows, height) - y,
+                        }
+                    })
+                    .collect())
+            }
+            RegionLayout::Regions(regions) => Ok(regions.clone()),
+        }
+    }
+}
+
+/// The scheme extracted from one region of an image
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegionExtraction {
+    pub region: CropRegion,
+    pub extraction: Extraction,
+}
+
+/// The schemes of every region of an image, and of the whole image
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegionExtractions {
+    /// One extraction per region, in the order of the layout
+    pub regions: Vec<RegionExtraction>,
+    /// The extraction from the whole image, or from `options.crop` when set
+    pub combined: Extraction,
+}
+
+/// Extract a scheme for every region of an image, along with the scheme of
+/// the whole image, e.g. for per-monitor accents from a spanned wallpaper
+///
+/// # Arguments
+/// * `image` - The decoded image
+/// * `params` - The SchemeParams used for every scheme. `options.crop` is
+///   replaced by each region, which is in the image's coordinates
+/// * `layout` - How the image is split into regions
+pub fn extract_schemes_by_region(
+    image: &DynamicImage,
+    params: SchemeParams,
+    layout: &RegionLayout,
+) -> Result<RegionExtractions, Error> {
+    params.validate()?;
+
+    let (width, height) = image.dimensions();
+    let regions = layout
+        .regions(width, height)?
+        .into_iter()
+        .map(|region| {
+            let mut region_params = params.clone();
+
+            region_params.options.crop = Some(region);
+
+            extract_scheme_from_dynamic_image(image, region_params)
+                .map(|extraction| RegionExtraction { region, extraction })
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(RegionExtractions {
+        regions,
+        combined: extract_scheme_from_dynamic_image(image, params)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ExtractionOptions;
+    use image::{Rgb, RgbImage};
+    use std::path::PathBuf;
+    use tinted_builder::{SchemeSystem, SchemeVariant};
+
+    #[test]
+    fn test_grid_regions() {
+        let regions = RegionLayout::Grid {
+            columns: 3,
+            rows: 1,
+        }
+        .regions(100, 50)
+        .unwrap();
+
+        assert_eq!(
+            regions,
+            vec![
+                CropRegion {
+                    x: 0,
+                    y: 0,
+                    width: 33,
+                    height: 50
+                },
+                CropRegion {
+                    x: 33,
+                    y: 0,
+                    width: 33,
+                    height: 50
+                },
+                CropRegion {
+                    x: 66,
+                    y: 0,
+                    width: 34,
+                    height: 50
+                },
+            ]
+        );
+        assert!(matches!(
+            RegionLayout::Grid {
+                columns: 0,
+                rows: 1
+            }
+            .regions(100, 50),
+            Err(Error::InvalidGrid { columns: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_extract_schemes_by_region() {
+        let image = RgbImage::from_fn(64, 32, |x, _| {
+            if x < 32 {
+                Rgb([200, 40, 40])
+            } else {
+                Rgb([40, 40, 200])
+            }
+        });
+        let params = SchemeParams {
+            image_path: PathBuf::new(),
+            author: "Author".to_string(),
+            description: None,
+            name: "Regions".to_string(),
+            slug: "regions".to_string(),
+            system: SchemeSystem::Base16,
+            variant: SchemeVariant::Dark,
+            verbose: false,
+            options: ExtractionOptions::default(),
+        };
+        let extractions = extract_schemes_by_region(
+            &DynamicImage::ImageRgb8(image),
+            params,
+            &RegionLayout::Grid {
+                columns: 2,
+                rows: 1,
+            },
+        )
+        .unwrap();
+        let [left, right] = &extractions.regions[..] else {
+            panic!("expected two regions");
+        };
+
+        assert!(left.extraction.diagnostics.single_color);
+        assert!(right.extraction.diagnostics.single_color);
+        assert_ne!(
+            left.extraction.scheme.palette["base00"],
+            right.extraction.scheme.palette["base00"]
+        );
+        assert!(!extractions.combined.diagnostics.single_color);
+    }
+}
@@ -0,0 +1,479 @@
+use std::fmt::Write;
+
+use image::{imageops, DynamicImage, RgbaImage};
+use tinted_builder::Base16Scheme;
+
+use crate::{
+    accessibility_report,
+    adjust::scheme_slots,
+    export::{single_line, slot_hex},
+    Error, Extraction, ExtractionOptions, ReportFormat,
+};
+
+/// The widest or tallest thumbnail of the source image, in pixels. Each run
+/// of equal pixels is one SVG rectangle, so this keeps Markdown reports
+/// under the size of a pull request comment
+const THUMBNAIL_SIZE: u32 = 32;
+/// How many times larger the thumbnail is drawn than it is
+const THUMBNAIL_SCALE: u32 = 10;
+/// The width and height of each swatch
+const SWATCH_SIZE: u32 = 32;
+
+/// A part of a report, rendered the same way in every format
+enum Block {
+    Heading(String),
+    Paragraph(String),
+    Image(&'static str, String),
+    Table(&'static [&'static str], Vec<Vec<String>>),
+    List(Vec<String>),
+    Code(String),
+}
+
+/// Render a human-readable report of an extraction, e.g. to attach to a
+/// pull request adding the scheme to a repository: a thumbnail of the
+/// source image, the swatches with their confidence, the contrast of every
+/// slot, the fallbacks that were triggered and the options used
+///
+/// # Arguments
+/// * `extraction` - A reference to the extraction to report on
+/// * `image` - The image the scheme was extracted from, for the thumbnail
+/// * `options` - A reference to the options the scheme was extracted with
+/// * `format` - The format to render the report in
+pub fn quality_report(
+    extraction: &Extraction,
+    image: Option<&DynamicImage>,
+    options: &ExtractionOptions,
+    format: ReportFormat,
+) -> Result<String, Error> {
+    let image = image.map(|image| image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgba8());
+
+    render_report(extraction, image.as_ref(), options, format)
+}
+
+/// Render a report with an image that's already RGBA, e.g. one prepared
+/// for extraction
+pub(crate) fn render_report(
+    extraction: &Extraction,
+    image: Option<&RgbaImage>,
+    options: &ExtractionOptions,
+    format: ReportFormat,
+) -> Result<String, Error> {
+    let blocks = report_blocks(extraction, image, options)?;
+
+    Ok(match format {
+        ReportFormat::Markdown => markdown(&blocks),
+        ReportFormat::Html => html(&extraction.scheme, &blocks),
+    })
+}
+
+fn report_blocks(
+    extraction: &Extraction,
+    image: Option<&RgbaImage>,
+    options: &ExtractionOptions,
+) -> Result<Vec<Block>, Error> {
+    let Extraction {
+        scheme,
+        diagnostics,
+        ..
+    } = extraction;
+    let mut blocks = vec![
+        Block::Heading(single_line(&scheme.name)),
+        Block::Paragraph(format!(
+            "A {} {} scheme by {}",
+            scheme.variant,
+            scheme.system,
+            single_line(&scheme.author)
+        )),
+        Block::Heading("Source image".to_string()),
+    ];
+
+    blocks.push(match image {
+        Some(image) => Block::Image("Source image", thumbnail_svg(&thumbnail(image))),
+        None => Block::Paragraph("No source image was available".to_string()),
+    });
+    blocks.push(Block::Heading("Swatches".to_string()));
+    blocks.push(Block::Image("Swatches", swatches_svg(scheme)?));
+
+    let swatches = scheme_slots(&scheme.system)
+        .into_iter()
+        .map(|slot| {
+            let confidence = diagnostics
+                .confidence
+                .get(&slot)
+                .map(|confidence| format!("{:.2}", confidence))
+                .unwrap_or_else(|| "-".to_string());
+
+            Ok(vec![slot.clone(), slot_hex(scheme, &slot)?, confidence])
+        })
+        .collect::<Result<_, Error>>()?;
+
+    blocks.push(Block::Table(&["Slot", "Color", "Confidence"], swatches));
+    blocks.push(Block::Heading("Contrast".to_string()));
+
+    let contrast = accessibility_report(scheme)?
+        .slots
+        .into_iter()
+        .map(|slot| {
+            vec![
+                slot.slot,
+                format!("{:.2}", slot.background_contrast),
+                format!("{:.2}", slot.foreground_contrast),
+                slot.level.to_string(),
+                slot.required
+                    .map(|required| required.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                slot.suggestion
+                    .map(|hex| format!("#{}", hex))
+                    .unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+
+    blocks.push(Block::Table(
+        &[
+            "Slot",
+            "vs base00",
+            "vs base05",
+            "Level",
+            "Required",
+            "Suggestion",
+        ],
+        contrast,
+    ));
+    blocks.push(Block::Heading("Fallbacks".to_string()));
+
+    let mut fallbacks = Vec::new();
+
+    if diagnostics.single_color {
+        fallbacks.push(
+            "The image is a single flat color, so the single-color palette was used".to_string(),
+        );
+    }
+    if diagnostics.monochrome {
+        fallbacks.push(
+            "The image has almost no color, so the single-color palette was used with its average gray"
+                .to_string(),
+        );
+    }
+    if diagnostics.low_diversity {
+        fallbacks.push(format!(
+            "The hue entropy of {:.2} is below `min_hue_entropy`, so the single-color palette was used with the mean color",
+            diagnostics.diversity.hue_entropy
+        ));
+    }
+    if diagnostics.color_thief_fallback {
+        fallbacks.push(
+            "color_thief wasn't used, so the palette was built from the pixels closest to each pure color"
+                .to_string(),
+        );
+    }
+    if diagnostics.light_passes > 0 && diagnostics.light_pass.is_none() {
+        fallbacks.push(
+            "No pass of the light ladder matched, so the most dominant color was used".to_string(),
+        );
+    }
+    if diagnostics.dark_passes > 0 && diagnostics.dark_pass.is_none() {
+        fallbacks.push(
+            "No pass of the dark ladder matched, so the most dominant color was used".to_string(),
+        );
+    }
+
+    let synthesized: Vec<&str> = diagnostics
+        .confidence
+        .iter()
+        .filter(|(_, confidence)| **confidence == 0.0)
+        .map(|(slot, _)| slot.as_str())
+        .collect();
+
+    if !synthesized.is_empty() {
+        fallbacks.push(format!(
+            "No color of the image fit these slots, so they were synthesized: {}",
+            synthesized.join(", ")
+        ));
+    }
+
+    if fallbacks.is_empty() {
+        blocks.push(Block::Paragraph("None".to_string()));
+    } else {
+        blocks.push(Block::List(fallbacks));
+    }
+
+    blocks.push(Block::Heading("Settings".to_string()));
+    blocks.push(Block::Code(format!("{:#?}", options)));
+
+    Ok(blocks)
+}
+
+/// Shrink an image to fit `THUMBNAIL_SIZE`
+fn thumbnail(image: &RgbaImage) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let scale = (THUMBNAIL_SIZE as f32 / width.max(height).max(1) as f32).min(1.0);
+
+    imageops::thumbnail(
+        image,
+        ((width as f32 * scale).round() as u32).max(1),
+        ((height as f32 * scale).round() as u32).max(1),
+    )
+}
+
+/// Draw an image as an SVG, with a rectangle per run of equal pixels in
+/// each row
+fn thumbnail_svg(image: &RgbaImage) -> String {
+    let (width, height) = image.dimensions();
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" shape-rendering=\"crispEdges\">",
+        width * THUMBNAIL_SCALE,
+        height * THUMBNAIL_SCALE,
+        width,
+        height
+    );
+
+    for (y, row) in image.rows().enumerate() {
+        let pixels: Vec<[u8; 3]> = row.map(|pixel| [pixel[0], pixel[1], pixel[2]]).collect();
+        let mut x = 0;
+
+        for run in pixels.chunk_by(|a, b| a == b) {
+            let [red, green, blue] = run[0];
+            let _ = write!(
+                svg,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"1\" fill=\"#{:02x}{:02x}{:02x}\"/>",
+                x,
+                y,
+                run.len(),
+                red,
+                green,
+                blue
+            );
+            x += run.len();
+        }
+    }
+
+    svg.push_str("</svg>");
+
+    svg
+}
+
+/// Draw a row of the scheme's swatches as an SVG
+fn swatches_svg(scheme: &Base16Scheme) -> Result<String, Error> {
+    let slots = scheme_slots(&scheme.system);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">",
+        slots.len() as u32 * SWATCH_SIZE,
+        SWATCH_SIZE
+    );
+
+    for (index, slot) in slots.iter().enumerate() {
+        let _ = write!(
+            svg,
+            "<rect x=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"><title>{}</title></rect>",
+            index as u32 * SWATCH_SIZE,
+            SWATCH_SIZE,
+            SWATCH_SIZE,
+            slot_hex(scheme, slot)?,
+            slot
+        );
+    }
+
+    svg.push_str("</svg>");
+
+    Ok(svg)
+}
+
+fn markdown(blocks: &[Block]) -> String {
+    let mut markdown = String::new();
+    let mut level = "#";
+
+    for block in blocks {
+        match block {
+            Block::Heading(heading) => {
+                let _ = writeln!(markdown, "{} {}\n", level, heading);
+                level = "##";
+            }
+            Block::Paragraph(text) => {
+                let _ = writeln!(markdown, "{}\n", text);
+            }
+            Block::Image(alt, svg) => {
+                let _ = writeln!(markdown, "![{}]({})\n", alt, svg_data_uri(svg));
+            }
+            Block::Table(header, rows) => {
+                let _ = writeln!(markdown, "| {} |", header.join(" | "));
+                let _ = writeln!(markdown, "|{}", " --- |".repeat(header.len()));
+
+                for row in rows {
+                    let _ = writeln!(markdown, "| {} |", row.join(" | "));
+                }
+
+                markdown.push('\n');
+            }
+            Block::List(items) => {
+                for item in items {
+                    let _ = writeln!(markdown, "- {}", item);
+                }
+
+                markdown.push('\n');
+            }
+            Block::Code(code) => {
+                let _ = writeln!(markdown, "```text\n{}\n```\n", code);
+            }
+        }
+    }
+
+    markdown.truncate(markdown.trim_end().len());
+    markdown.push('\n');
+
+    markdown
+}
+
+fn html(scheme: &Base16Scheme, blocks: &[Block]) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\nbody {{ font-family: sans-serif; max-width: 60em; margin: 2em auto; }}\ntable {{ border-collapse: collapse; }}\nth, td {{ padding: 0.2em 0.8em; text-align: left; border-bottom: 1px solid #ccc; }}\n</style>\n</head>\n<body>\n",
+        html_escape(&single_line(&scheme.name))
+    );
+    let mut tag = "h1";
+
+    for block in blocks {
+        match block {
+            Block::Heading(heading) => {
+                let _ = writeln!(html, "<{}>{}</{}>", tag, html_escape(heading), tag);
+                tag = "h2";
+            }
+            Block::Paragraph(text) => {
+                let _ = writeln!(html, "<p>{}</p>", html_escape(text));
+            }
+            Block::Image(alt, svg) => {
+                let _ = writeln!(
+                    html,
+                    "<figure role=\"img\" aria-label=\"{}\">{}</figure>",
+                    alt, svg
+                );
+            }
+            Block::Table(header, rows) => {
+                html.push_str("<table>\n<tr>");
+
+                for cell in header.iter() {
+                    let _ = write!(html, "<th>{}</th>", html_escape(cell));
+                }
+
+                html.push_str("</tr>\n");
+
+                for row in rows {
+                    html.push_str("<tr>");
+
+                    for cell in row {
+                        let _ = write!(html, "<td>{}</td>", html_escape(cell));
+                    }
+
+                    html.push_str("</tr>\n");
+                }
+
+                html.push_str("</table>\n");
+            }
+            Block::List(items) => {
+                html.push_str("<ul>\n");
+
+                for item in items {
+                    let _ = writeln!(html, "<li>{}</li>", html_escape(item));
+                }
+
+                html.push_str("</ul>\n");
+            }
+            Block::Code(code) => {
+                let _ = writeln!(html, "<pre>{}</pre>", html_escape(code));
+            }
+        }
+    }
+
+    html.push_str("</body>\n</html>\n");
+
+    html
+}
+
+/// Percent-encode an SVG as a data URI, which Markdown can link to as an
+/// image without a base64 encoder
+fn svg_data_uri(svg: &str) -> String {
+    let mut uri = "data:image/svg+xml,".to_string();
+
+    for byte in svg.bytes() {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'_'
+            | b'.'
+            | b'~'
+            | b'/'
+            | b'='
+            | b':' => uri.push(byte as char),
+            _ => {
+                let _ = write!(uri, "%{:02X}", byte);
+            }
+        }
+    }
+
+    uri
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{export::tests::test_scheme, Diagnostics};
+    use image::Rgba;
+    use tinted_builder::SchemeSystem;
+
+    #[test]
+    fn test_quality_report() {
+        let mut diagnostics = Diagnostics {
+            color_thief_fallback: true,
+            ..Diagnostics::default()
+        };
+
+        diagnostics.confidence.insert("base00".to_string(), 0.75);
+        diagnostics.confidence.insert("base0E".to_string(), 0.0);
+
+        let extraction = Extraction {
+            scheme: test_scheme(SchemeSystem::Base16),
+            diagnostics,
+            surfaces: None,
+            accessibility: None,
+            report: None,
+        };
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_fn(64, 32, |x, _| {
+            if x < 32 {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 0, 255, 255])
+            }
+        }));
+        let options = ExtractionOptions::default();
+        let markdown =
+            quality_report(&extraction, Some(&image), &options, ReportFormat::Markdown).unwrap();
+
+        assert!(markdown.starts_with("# Test \"Scheme\"\n\nA dark base16 scheme by Author\n"));
+        // The thumbnail is 32x16, with a rectangle for each half of a row
+        assert!(markdown.contains("![Source image](data:image/svg+xml,%3Csvg"));
+        assert_eq!(markdown.matches("%3Crect").count(), 2 * 16 + 16);
+        assert!(markdown.contains("| base00 | #000000 | 0.75 |\n"));
+        assert!(markdown.contains("| base01 | #242424 | - |\n"));
+        assert!(markdown.contains("| base03 | 4.06 | 2.55 | AA large | AA large | - |\n"));
+        assert!(markdown.contains("- color_thief wasn't used"));
+        assert!(markdown.contains(
+            "- No color of the image fit these slots, so they were synthesized: base0E\n"
+        ));
+        assert!(markdown.ends_with("}\n```\n"));
+
+        let html = quality_report(&extraction, None, &options, ReportFormat::Html).unwrap();
+
+        assert!(html.contains("<title>Test &quot;Scheme&quot;</title>"));
+        assert!(html.contains("<p>No source image was available</p>"));
+        assert!(html.contains("<td>base00</td><td>#000000</td><td>0.75</td>"));
+        assert!(html.ends_with("</pre>\n</body>\n</html>\n"));
+    }
+}
@@ -0,0 +1,82 @@
+use std::fmt::Write;
+
+use tinted_builder::Base16Scheme;
+
+use crate::{
+    export::{single_line, slot_hex},
+    Error,
+};
+
+/// The properties of rofi's default theme and their slots: normal entries
+/// on the background, the selected entry in base0D, urgent entries in red
+/// and active entries in green
+const PROPERTIES: [(&str, &str); 27] = [
+    ("background", "base00"),
+    ("foreground", "base05"),
+    ("lightbg", "base01"),
+    ("lightfg", "base04"),
+    ("red", "base08"),
+    ("blue", "base0D"),
+    ("background-color", "base00"),
+    ("border-color", "base0D"),
+    ("separatorcolor", "base02"),
+    ("normal-background", "base00"),
+    ("normal-foreground", "base05"),
+    ("alternate-normal-background", "base01"),
+    ("alternate-normal-foreground", "base05"),
+    ("selected-normal-background", "base0D"),
+    ("selected-normal-foreground", "base00"),
+    ("urgent-background", "base00"),
+    ("urgent-foreground", "base08"),
+    ("alternate-urgent-background", "base01"),
+    ("alternate-urgent-foreground", "base08"),
+    ("selected-urgent-background", "base08"),
+    ("selected-urgent-foreground", "base00"),
+    ("active-background", "base00"),
+    ("active-foreground", "base0B"),
+    ("alternate-active-background", "base01"),
+    ("alternate-active-foreground", "base0B"),
+    ("selected-active-background", "base0B"),
+    ("selected-active-foreground", "base00"),
+];
+
+/// Render a rofi `.rasi` theme setting the colors rofi's default theme is
+/// built from: the background, foreground, selected, urgent and active
+/// entries
+///
+/// # Arguments
+/// * `scheme` - A reference to the generated Base16Scheme
+pub fn rofi_theme(scheme: &Base16Scheme) -> Result<String, Error> {
+    let mut theme = format!(
+        "/* {} by {} */\n* {{\n",
+        single_line(&scheme.name).replace("*/", "* /"),
+        single_line(&scheme.author).replace("*/", "* /")
+    );
+
+    for (property, slot) in PROPERTIES {
+        let _ = writeln!(theme, "    {}: {};", property, slot_hex(scheme, slot)?);
+    }
+
+    theme.push_str("}\n");
+
+    Ok(theme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::test_scheme;
+    use tinted_builder::SchemeSystem;
+
+    #[test]
+    fn test_rofi_theme() {
+        let theme = rofi_theme(&test_scheme(SchemeSystem::Base16)).unwrap();
+
+        assert!(
+            theme.starts_with("/* Test \"Scheme\" by Author */\n* {\n    background: #000000;\n")
+        );
+        assert!(theme.contains("    selected-normal-background: #0000ff;\n"));
+        assert!(theme.contains("    urgent-foreground: #ff0000;\n"));
+        assert!(theme.ends_with("    selected-active-foreground: #000000;\n}\n"));
+    }
+}
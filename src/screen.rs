@@ -0,0 +1,69 @@
+use image::DynamicImage;
+use xcap::Monitor;
+
+use crate::Error;
+
+/// A rectangle in global screen coordinates
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScreenRegion {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Capture the screen so a scheme can be generated from it
+///
+/// Without a region the primary monitor is captured. With a region, the
+/// monitor containing the region's top-left corner is used and the region
+/// is clipped to that monitor.
+///
+/// # Arguments
+/// * `region` - An optional ScreenRegion to capture instead of the whole monitor
+pub fn capture_screen(region: Option<ScreenRegion>) -> Result<DynamicImage, Error> {
+    let image = match region {
+        Some(region) => {
+            let monitor = Monitor::from_point(region.x, region.y)
+                .map_err(|source| Error::ScreenCapture { source })?;
+            let monitor_x = monitor
+                .x()
+                .map_err(|source| Error::ScreenCapture { source })?;
+            let monitor_y = monitor
+                .y()
+                .map_err(|source| Error::ScreenCapture { source })?;
+            let monitor_width = monitor
+                .width()
+                .map_err(|source| Error::ScreenCapture { source })?;
+            let monitor_height = monitor
+                .height()
+                .map_err(|source| Error::ScreenCapture { source })?;
+            let x = (region.x - monitor_x).max(0) as u32;
+            let y = (region.y - monitor_y).max(0) as u32;
+            let width = region.width.min(monitor_width.saturating_sub(x));
+            let height = region.height.min(monitor_height.saturating_sub(y));
+
+            if width == 0 || height == 0 {
+                return Err(Error::CaptureRegionOutsideMonitor { region });
+            }
+
+            monitor
+                .capture_region(x, y, width, height)
+                .map_err(|source| Error::ScreenCapture { source })?
+        }
+        None => {
+            let monitors = Monitor::all().map_err(|source| Error::ScreenCapture { source })?;
+            let monitor = monitors
+                .iter()
+                .find(|monitor| monitor.is_primary().unwrap_or(false))
+                .or_else(|| monitors.first())
+                .ok_or(Error::NoMonitors)?;
+
+            monitor
+                .capture_image()
+                .map_err(|source| Error::ScreenCapture { source })?
+        }
+    };
+
+    Ok(DynamicImage::ImageRgba8(image))
+}
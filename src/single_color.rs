@@ -0,0 +1,244 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use image::RgbaImage;
+use palette::{FromColor, Hsl, IntoColor, Srgb};
+use tinted_builder::{Color as SchemeColor, SchemeSystem, SchemeVariant};
+
+use crate::{
+    color::PureColor,
+    utils::{accent_hues, scheme_color_from_srgb},
+    Error,
+};
+
+/// Lightness of the darkest and lightest color of the single-color ramp
+const RAMP_LIGHTNESS: (f32, f32) = (0.08, 0.92);
+/// Highest saturation of the single-color ramp, so the grays stay readable
+const RAMP_MAX_SATURATION: f32 = 0.25;
+
+/// Get the color of the images if all their pixels have the same color,
+/// ignoring alpha
+pub(crate) fn single_color(images: &[Cow<RgbaImage>]) -> Option<Srgb<u8>> {
+    let mut pixels = images.iter().flat_map(|image| image.pixels());
+    let first = pixels.next()?;
+
+    pixels
+        .all(|pixel| pixel.0[..3] == first.0[..3])
+        .then(|| Srgb::new(first.0[0], first.0[1], first.0[2]))
+}
+
+/// The largest difference between the channels of a pixel that still counts
+/// as gray, which leaves room for antialiasing and compression artifacts
+const MAX_GRAY_CHROMA: u8 = 24;
+
+/// Get the average gray of the images if they have no color: either they
+/// were decoded from a grayscale source, or fewer than 1% of their pixels
+/// are further than `MAX_GRAY_CHROMA` from gray
+///
+/// Palette-indexed sources are expanded to their exact palette colors when
+/// decoded, so they are detected the same way as any other source.
+///
+/// # Arguments
+/// * `images` - The prepared images
+/// * `is_grayscale` - Whether all images were decoded from grayscale sources
+pub(crate) fn monochrome_color(images: &[Cow<RgbaImage>], is_grayscale: bool) -> Option<Srgb<u8>> {
+    let pixel_count: u64 = images
+        .iter()
+        .map(|image| image.width() as u64 * image.height() as u64)
+        .sum();
+
+    if pixel_count == 0 {
+        return None;
+    }
+
+    if !is_grayscale {
+        let max_colored = pixel_count / 100;
+        let mut colored = 0;
+
+        for pixel in images.iter().flat_map(|image| image.pixels()) {
+            let [red, green, blue, _] = pixel.0;
+            let chroma = red.max(green).max(blue) - red.min(green).min(blue);
+
+            if chroma > MAX_GRAY_CHROMA {
+                colored += 1;
+
+                if colored > max_colored {
+                    return None;
+                }
+            }
+        }
+    }
+
+    let total: u64 = images
+        .iter()
+        .flat_map(|image| image.pixels())
+        .map(|pixel| pixel.0[..3].iter().map(|c| *c as u64).sum::<u64>())
+        .sum();
+    let gray = (total as f64 / (pixel_count * 3) as f64).round() as u8;
+
+    Some(Srgb::new(gray, gray, gray))
+}
+
+/// Create the palette for an image that is a single flat color, or for a
+/// monochrome image with its average gray, where the usual heuristics have
+/// nothing to choose from
+///
+/// base00 to base07 ramp from a darkened to a lightened version of the color
+/// (reversed for light schemes), keeping its hue with the saturation capped
+/// at 0.25. The accents are synthesized at fixed hues (red, orange, yellow,
+/// green, cyan, blue, purple and brown or magenta) with a saturation of 0.6 and a
+/// lightness of 0.6, or 0.4 for light schemes. Base24's bright accents use
+/// a saturation of 0.7 and are 0.1 further from the background.
+///
+/// # Arguments
+/// * `color` - The Srgb<u8> color of the image
+/// * `system` - A reference to the SchemeSystem to create slots for
+/// * `variant` - A reference to the SchemeVariant deciding the ramp direction
+/// * `base0f` - The pure color base0F represents
+pub(crate) fn single_color_palette(
+    color: Srgb<u8>,
+    system: &SchemeSystem,
+    variant: &SchemeVariant,
+    base0f: PureColor,
+) -> Result<HashMap<String, SchemeColor>, Error> {
+    let hsl: Hsl = Hsl::from_color(color.into_format::<f32>());
+    let to_scheme_color = |hue: f32, saturation: f32, lightness: f32| {
+        let rgb: Srgb = Hsl::new(hue, saturation, lightness).into_color();
+        let rgb: Srgb<u8> = rgb.into_format();
+
+        scheme_color_from_srgb(rgb)
+    };
+    let is_light = matches!(variant, SchemeVariant::Light);
+    let (darkest, lightest) = RAMP_LIGHTNESS;
+    let ramp_saturation = hsl.saturation.min(RAMP_MAX_SATURATION);
+    let mut palette = HashMap::new();
+
+    for index in 0..8 {
+        let step = if is_light { 7 - index } else { index };
+        let lightness = darkest + (lightest - darkest) * step as f32 / 7.0;
+
+        palette.insert(
+            format!("base0{}", index),
+            to_scheme_color(hsl.hue.into_degrees(), ramp_saturation, lightness),
+        );
+    }
+
+    let accent_lightness = if is_light { 0.4 } else { 0.6 };
+    let bright_lightness = if is_light { 0.3 } else { 0.7 };
+
+    for (index, hue) in accent_hues(base0f).iter().enumerate() {
+        palette.insert(
+            format!("base{:02X}", 0x08 + index),
+            to_scheme_color(*hue, 0.6, accent_lightness),
+        );
+
+        if let SchemeSystem::Base24 = system {
+            palette.insert(
+                format!("base{:02X}", 0x10 + index),
+                to_scheme_color(*hue, 0.7, bright_lightness),
+            );
+        }
+    }
+
+    Ok(palette)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{extract_scheme_from_dynamic_image, ExtractionOptions, SchemeParams};
+    use image::{DynamicImage, Rgb, RgbImage};
+    use std::path::PathBuf;
+
+    fn params(system: SchemeSystem, variant: SchemeVariant) -> SchemeParams {
+        SchemeParams {
+            image_path: PathBuf::new(),
+            author: "Author".to_string(),
+            description: None,
+            name: "Name".to_string(),
+            slug: "name".to_string(),
+            system,
+            variant,
+            verbose: false,
+            options: ExtractionOptions::default(),
+        }
+    }
+
+    #[test]
+    fn test_single_color() {
+        let flat = Cow::Owned(RgbaImage::from_pixel(4, 4, image::Rgba([10, 20, 30, 255])));
+        let mut mixed = RgbaImage::from_pixel(4, 4, image::Rgba([10, 20, 30, 255]));
+
+        mixed.put_pixel(3, 3, image::Rgba([10, 20, 31, 255]));
+
+        assert_eq!(
+            single_color(std::slice::from_ref(&flat)),
+            Some(Srgb::new(10, 20, 30))
+        );
+        assert_eq!(single_color(&[flat, Cow::Owned(mixed)]), None);
+    }
+
+    #[test]
+    fn test_single_color_scheme() {
+        let small = DynamicImage::ImageRgb8(RgbImage::from_pixel(1, 1, Rgb([128, 128, 128])));
+        let large = DynamicImage::ImageRgb8(RgbImage::from_pixel(300, 200, Rgb([128, 128, 128])));
+        let dark = extract_scheme_from_dynamic_image(
+            &small,
+            params(SchemeSystem::Base16, SchemeVariant::Dark),
+        )
+        .unwrap();
+        let dark_large = extract_scheme_from_dynamic_image(
+            &large,
+            params(SchemeSystem::Base16, SchemeVariant::Dark),
+        )
+        .unwrap();
+        let light = extract_scheme_from_dynamic_image(
+            &small,
+            params(SchemeSystem::Base24, SchemeVariant::Light),
+        )
+        .unwrap();
+
+        assert!(dark.diagnostics.single_color);
+        assert_eq!(dark.scheme.palette, dark_large.scheme.palette);
+        assert_eq!(dark.scheme.palette.len(), 16);
+        assert_eq!(dark.scheme.palette["base00"].rgb, (20, 20, 20));
+        assert_eq!(dark.scheme.palette["base07"].rgb, (235, 235, 235));
+        assert_eq!(dark.scheme.palette["base08"].rgb, (214, 92, 92));
+        assert_eq!(light.scheme.palette.len(), 24);
+        assert_eq!(light.scheme.palette["base00"].rgb, (235, 235, 235));
+        assert_eq!(light.scheme.palette["base08"].rgb, (163, 41, 41));
+        assert_eq!(light.scheme.palette["base10"].rgb, (130, 23, 23));
+    }
+
+    #[test]
+    fn test_monochrome_scheme() {
+        let grayscale = DynamicImage::ImageLuma8(image::GrayImage::from_fn(64, 64, |x, _| {
+            image::Luma([(x * 4) as u8])
+        }));
+        let mut antialiased = RgbImage::from_fn(100, 100, |x, _| {
+            if x < 50 {
+                Rgb([20, 20, 20])
+            } else {
+                Rgb([230, 230, 230])
+            }
+        });
+
+        // A few colored fringe pixels, like subpixel antialiasing
+        for y in 0..50 {
+            antialiased.put_pixel(50, y, Rgb([200, 40, 40]));
+        }
+
+        let antialiased = DynamicImage::ImageRgb8(antialiased);
+
+        for image in [grayscale, antialiased] {
+            let extraction = extract_scheme_from_dynamic_image(
+                &image,
+                params(SchemeSystem::Base16, SchemeVariant::Dark),
+            )
+            .unwrap();
+
+            assert!(extraction.diagnostics.monochrome);
+            assert_eq!(extraction.scheme.palette["base00"].rgb, (20, 20, 20));
+            assert_eq!(extraction.scheme.palette["base08"].rgb, (214, 92, 92));
+        }
+    }
+}
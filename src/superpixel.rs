@@ -0,0 +1,315 @@
+use image::{Rgba, RgbaImage};
+use palette::{FromColor, Lab, Srgb};
+
+/// How strongly superpixels keep to a compact shape rather than following
+/// color edges, the `m` of the SLIC paper
+const COMPACTNESS: f32 = 10.0;
+/// Rounds of assigning pixels to the closest center and moving the centers
+const ITERATIONS: usize = 10;
+/// The smallest area of a superpixel, relative to the grid cell area
+const MIN_AREA: f32 = 0.25;
+/// The label of pixels that belong to no superpixel
+const UNASSIGNED: usize = usize::MAX;
+
+/// The mean color and position of a superpixel
+#[derive(Clone, Copy, Default)]
+struct Center {
+    l: f32,
+    a: f32,
+    b: f32,
+    x: f32,
+    y: f32,
+}
+
+/// Replace every pixel by the mean color of its superpixel, so single-pixel
+/// noise such as film grain disappears and coherent regions count by area
+///
+/// The superpixels are found with SLIC: centers start on a regular grid and
+/// pixels are repeatedly assigned to the closest center within twice the
+/// grid interval, by a distance combining the Lab difference and the
+/// spatial distance. Connected parts smaller than a quarter of a grid cell
+/// then join the superpixel next to them. Transparent pixels are left
+/// alone.
+///
+/// # Arguments
+/// * `image` - The RGBA image to cluster
+/// * `count` - About how many superpixels to group the pixels into
+pub(crate) fn superpixel_image(image: &RgbaImage, count: u32) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let pixel_count = width as usize * height as usize;
+
+    if count < 2 || pixel_count <= count as usize {
+        return image.clone();
+    }
+
+    let interval = (pixel_count as f32 / count as f32).sqrt();
+    let lab: Vec<Lab> = image
+        .pixels()
+        .map(|pixel| {
+            let [red, green, blue, _] = pixel.0;
+
+            Lab::from_color(Srgb::new(red, green, blue).into_format::<f32>())
+        })
+        .collect();
+    let mut centers = seed_centers(&lab, width, interval);
+
+    for _ in 0..ITERATIONS {
+        let labels = assign_pixels(image, &lab, &centers, interval);
+
+        centers = mean_centers(&lab, width, &labels, centers.len())
+            .into_iter()
+            .filter(|(_, area)| *area > 0.0)
+            .map(|(center, _)| center)
+            .collect();
+    }
+
+    let labels = assign_pixels(image, &lab, &centers, interval);
+    let (labels, count) =
+        enforce_connectivity(&labels, width, (interval * interval * MIN_AREA) as usize);
+    let colors: Vec<Srgb<u8>> = mean_centers(&lab, width, &labels, count)
+        .iter()
+        .map(|(center, _)| Srgb::from_color(Lab::new(center.l, center.a, center.b)).into_format())
+        .collect();
+    let mut clustered = image.clone();
+
+    for (pixel, label) in clustered.pixels_mut().zip(labels) {
+        if let Some(color) = colors.get(label) {
+            *pixel = Rgba([color.red, color.green, color.blue, pixel[3]]);
+        }
+    }
+
+    clustered
+}
+
+fn difference(a: Lab, b: Lab) -> f32 {
+    (a.l - b.l).powi(2) + (a.a - b.a).powi(2) + (a.b - b.b).powi(2)
+}
+
+/// Place a center in every grid cell, on the smoothest of the 3x3 pixels
+/// in its middle so no center starts on an edge or a noisy pixel
+fn seed_centers(lab: &[Lab], width: u32, interval: f32) -> Vec<Center> {
+    let height = (lab.len() / width as usize) as u32;
+    let at = |x: u32, y: u32| {
+        lab[y.min(height - 1) as usize * width as usize + x.min(width - 1) as usize]
+    };
+    let gradient = |(x, y): (u32, u32)| {
+        difference(at(x + 1, y), at(x.saturating_sub(1), y))
+            + difference(at(x, y + 1), at(x, y.saturating_sub(1)))
+    };
+    let mut centers = Vec::new();
+    let mut y = interval / 2.0;
+
+    while y < height as f32 {
+        let mut x = interval / 2.0;
+
+        while x < width as f32 {
+            let (cell_x, cell_y) = (x as u32, y as u32);
+            let (seed_x, seed_y) = (cell_x.saturating_sub(1)..=(cell_x + 1).min(width - 1))
+                .flat_map(|x| {
+                    (cell_y.saturating_sub(1)..=(cell_y + 1).min(height - 1)).map(move |y| (x, y))
+                })
+                .min_by(|a, b| gradient(*a).total_cmp(&gradient(*b)))
+                .unwrap_or((cell_x, cell_y));
+            let color = at(seed_x, seed_y);
+
+            centers.push(Center {
+                l: color.l,
+                a: color.a,
+                b: color.b,
+                x: seed_x as f32,
+                y: seed_y as f32,
+            });
+            x += interval;
+        }
+
+        y += interval;
+    }
+
+    centers
+}
+
+/// Label every opaque pixel with the closest center within twice the grid
+/// interval
+fn assign_pixels(image: &RgbaImage, lab: &[Lab], centers: &[Center], interval: f32) -> Vec<usize> {
+    let (width, height) = image.dimensions();
+    let spatial_weight = (COMPACTNESS / interval).powi(2);
+    let mut labels = vec![UNASSIGNED; lab.len()];
+    let mut distances = vec![f32::INFINITY; lab.len()];
+
+    for (label, center) in centers.iter().enumerate() {
+        let center_color = Lab::new(center.l, center.a, center.b);
+        let x_range = (center.x - 2.0 * interval).max(0.0) as u32
+            ..((center.x + 2.0 * interval) as u32).min(width);
+        let y_range = (center.y - 2.0 * interval).max(0.0) as u32
+            ..((center.y + 2.0 * interval) as u32).min(height);
+
+        for y in y_range {
+            for x in x_range.clone() {
+                let index = y as usize * width as usize + x as usize;
+
+                if image.get_pixel(x, y)[3] == 0 {
+                    continue;
+                }
+
+                let spatial_distance =
+                    (x as f32 - center.x).powi(2) + (y as f32 - center.y).powi(2);
+                let distance =
+                    difference(lab[index], center_color) + spatial_distance * spatial_weight;
+
+                if distance < distances[index] {
+                    distances[index] = distance;
+                    labels[index] = label;
+                }
+            }
+        }
+    }
+
+    labels
+}
+
+/// Get the mean color and position of the pixels of every label, along
+/// with their area
+fn mean_centers(lab: &[Lab], width: u32, labels: &[usize], count: usize) -> Vec<(Center, f32)> {
+    let mut sums = vec![(Center::default(), 0.0_f32); count];
+
+    for (index, label) in labels.iter().enumerate() {
+        let Some((sum, area)) = sums.get_mut(*label) else {
+            continue;
+        };
+        let color = lab[index];
+
+        sum.l += color.l;
+        sum.a += color.a;
+        sum.b += color.b;
+        sum.x += (index % width as usize) as f32;
+        sum.y += (index / width as usize) as f32;
+        *area += 1.0;
+    }
+
+    sums.into_iter()
+        .map(|(sum, area)| {
+            let divisor = area.max(1.0);
+
+            (
+                Center {
+                    l: sum.l / divisor,
+                    a: sum.a / divisor,
+                    b: sum.b / divisor,
+                    x: sum.x / divisor,
+                    y: sum.y / divisor,
+                },
+                area,
+            )
+        })
+        .collect()
+}
+
+/// Relabel the pixels by connected part of each label, merging parts
+/// smaller than `min_area` into a part next to them, preferably the one
+/// left of or above them. Returns the new labels and how many there are
+fn enforce_connectivity(labels: &[usize], width: u32, min_area: usize) -> (Vec<usize>, usize) {
+    let width = width as usize;
+    let height = labels.len() / width;
+    let neighbours = |index: usize| {
+        let (x, y) = (index % width, index / width);
+
+        [
+            (x > 0).then(|| index - 1),
+            (y > 0).then(|| index - width),
+            (x + 1 < width).then(|| index + 1),
+            (y + 1 < height).then(|| index + width),
+        ]
+        .into_iter()
+        .flatten()
+    };
+    let mut parts = vec![UNASSIGNED; labels.len()];
+    let mut count = 0;
+    let mut stack = Vec::new();
+    // Small parts at the top left edge, with no part next to them yet
+    let mut orphans = Vec::new();
+
+    for start in 0..labels.len() {
+        if parts[start] != UNASSIGNED || labels[start] == UNASSIGNED {
+            continue;
+        }
+
+        // Only the left and upper neighbours can have a part yet
+        let adjacent = neighbours(start).find(|index| parts[*index] != UNASSIGNED);
+        let mut members = Vec::new();
+
+        stack.push(start);
+        parts[start] = count;
+
+        while let Some(index) = stack.pop() {
+            members.push(index);
+
+            for neighbour in neighbours(index) {
+                if parts[neighbour] == UNASSIGNED && labels[neighbour] == labels[start] {
+                    parts[neighbour] = count;
+                    stack.push(neighbour);
+                }
+            }
+        }
+
+        if members.len() >= min_area {
+            count += 1;
+        } else if let Some(adjacent) = adjacent {
+            let part = parts[adjacent];
+
+            for index in &members {
+                parts[*index] = part;
+            }
+        } else {
+            let outside = members
+                .iter()
+                .flat_map(|index| neighbours(*index))
+                .find(|index| labels[*index] != labels[start]);
+
+            orphans.push((members, outside));
+            count += 1;
+        }
+    }
+
+    for (members, outside) in orphans {
+        if let Some(part) = outside.map(|index| parts[index]) {
+            for index in members {
+                parts[index] = part;
+            }
+        }
+    }
+
+    (parts, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_superpixel_image() {
+        // Two halves with single-pixel noise sprinkled over them
+        let image = RgbaImage::from_fn(40, 20, |x, y| {
+            let noisy = (x.wrapping_mul(2_654_435_761) ^ y.wrapping_mul(40_503)) % 29 == 0;
+
+            match (x < 20, noisy) {
+                (true, false) => Rgba([200, 40, 40, 255]),
+                (false, false) => Rgba([40, 40, 200, 255]),
+                _ => Rgba([255, 255, 255, 255]),
+            }
+        });
+        let clustered = superpixel_image(&image, 8);
+
+        assert!(!clustered
+            .pixels()
+            .any(|pixel| pixel.0 == [255, 255, 255, 255]));
+
+        let [red, _, blue, _] = clustered.get_pixel(5, 10).0;
+
+        assert!(red > blue);
+
+        let [red, _, blue, _] = clustered.get_pixel(35, 10).0;
+
+        assert!(blue > red);
+        assert_eq!(superpixel_image(&image, 0), image);
+    }
+}
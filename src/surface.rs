@@ -0,0 +1,132 @@
+use palette::{FromColor, Lch, Srgb};
+use tinted_builder::{Base16Scheme, Color as SchemeColor, SchemeVariant};
+
+use crate::{
+    gamut::to_srgb_within_gamut,
+    utils::{interpolate_color, scheme_color_from_srgb},
+    Error,
+};
+
+/// The chroma of the selection color
+const SELECTION_CHROMA: f32 = 24.0;
+/// The smallest chroma of the hover color
+const HOVER_CHROMA: f32 = 6.0;
+/// The chroma of the shadow color
+const SHADOW_CHROMA: f32 = 4.0;
+
+/// Colors for UI toolkit surfaces, e.g. for GTK or Qt themes, derived from
+/// a scheme's grays and a hue
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SurfaceColors {
+    /// The window background, base00
+    pub surface: SchemeColor,
+    /// Cards, popovers and menus above the surface: halfway to base01 for
+    /// dark schemes, and halfway to white for light schemes
+    pub elevated_surface: SchemeColor,
+    /// Hovered rows and buttons: base01 with at least a little of the hue
+    pub hover: SchemeColor,
+    /// Borders and separators, base02
+    pub border: SchemeColor,
+    /// Drop shadows, a darker base00 with a little of the hue
+    pub shadow: SchemeColor,
+    /// Selected text and rows, the hue at base02's lightness
+    pub selection: SchemeColor,
+}
+
+/// Derive UI surface colors from a scheme's grays
+///
+/// # Arguments
+/// * `scheme` - A reference to the scheme, which needs base00 to base02
+/// * `hue` - The hue in degrees to tint the hover, shadow and selection
+///   colors with, e.g. `Diagnostics::dominant_hue`. Defaults to base00's hue
+pub fn surface_colors(scheme: &Base16Scheme, hue: Option<f32>) -> Result<SurfaceColors, Error> {
+    let gray = |slot: &str| -> Result<Srgb<u8>, Error> {
+        let (red, green, blue) = scheme
+            .palette
+            .get(slot)
+            .ok_or_else(|| Error::UnknownSlot {
+                slot: slot.to_string(),
+            })?
+            .rgb;
+
+        Ok(Srgb::new(red, green, blue))
+    };
+    let to_lch = |color: Srgb<u8>| Lch::from_color(color.into_format::<f32>());
+    let base00 = gray("base00")?;
+    let base01 = gray("base01")?;
+    let base02 = gray("base02")?;
+    let hue = hue.unwrap_or_else(|| to_lch(base00).hue.into_positive_degrees());
+    let elevated_surface = match scheme.variant {
+        SchemeVariant::Light => interpolate_color(base00, Srgb::new(255, 255, 255), 0.5),
+        _ => interpolate_color(base00, base01, 0.5),
+    };
+    let hover = to_lch(base01);
+    let shadow_lightness = match scheme.variant {
+        SchemeVariant::Light => to_lch(base00).l * 0.7,
+        _ => to_lch(base00).l * 0.4,
+    };
+
+    Ok(SurfaceColors {
+        surface: scheme_color_from_srgb(base00),
+        elevated_surface: scheme_color_from_srgb(elevated_surface),
+        hover: lch_color(hover.l, hover.chroma.max(HOVER_CHROMA), hue),
+        border: scheme_color_from_srgb(base02),
+        shadow: lch_color(shadow_lightness, SHADOW_CHROMA, hue),
+        selection: lch_color(to_lch(base02).l, SELECTION_CHROMA, hue),
+    })
+}
+
+fn lch_color(lightness: f32, chroma: f32, hue: f32) -> SchemeColor {
+    scheme_color_from_srgb(to_srgb_within_gamut(Lch::new(lightness, chroma, hue)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::scheme_color;
+    use std::collections::HashMap;
+    use tinted_builder::SchemeSystem;
+
+    fn scheme(variant: SchemeVariant, grays: [&str; 3]) -> Base16Scheme {
+        Base16Scheme {
+            author: String::new(),
+            description: None,
+            name: "Surfaces".to_string(),
+            slug: "surfaces".to_string(),
+            system: SchemeSystem::Base16,
+            variant,
+            palette: ["base00", "base01", "base02"]
+                .iter()
+                .zip(grays)
+                .map(|(slot, hex)| (slot.to_string(), scheme_color(hex.to_string()).unwrap()))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn test_surface_colors() {
+        let dark = surface_colors(
+            &scheme(SchemeVariant::Dark, ["202020", "303030", "404040"]),
+            Some(220.0),
+        )
+        .unwrap();
+
+        assert_eq!(dark.surface.rgb, (0x20, 0x20, 0x20));
+        assert_eq!(dark.elevated_surface.rgb, (0x28, 0x28, 0x28));
+        assert_eq!(dark.border.rgb, (0x40, 0x40, 0x40));
+
+        let (red, _, blue) = dark.selection.rgb;
+
+        assert!(blue > red);
+
+        let light = surface_colors(
+            &scheme(SchemeVariant::Light, ["E0E0E0", "D0D0D0", "C0C0C0"]),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(light.elevated_surface.rgb, (0xF0, 0xF0, 0xF0));
+        assert!(light.shadow.rgb.0 < 0xE0);
+    }
+}
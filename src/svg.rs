@@ -0,0 +1,127 @@
+use image::{
+    error::{DecodingError, ImageFormatHint, LimitError, LimitErrorKind},
+    ImageError, ImageResult, Limits, RgbaImage,
+};
+use resvg::{tiny_skia, usvg};
+
+use crate::utils::SNIFF_LENGTH;
+
+/// Whether data looks like an SVG or a gzipped SVGZ file, which the image
+/// crate can't guess the format of
+pub(crate) fn is_svg(data: &[u8]) -> bool {
+    if data.starts_with(&[0x1f, 0x8b]) {
+        return true;
+    }
+
+    let start = String::from_utf8_lossy(&data[..data.len().min(SNIFF_LENGTH)]);
+
+    start
+        .trim_start_matches('\u{feff}')
+        .trim_start()
+        .starts_with('<')
+        && start.contains("<svg")
+}
+
+/// Rasterize an SVG or SVGZ file, with text rendered using the system's
+/// fonts
+///
+/// # Arguments
+/// * `data` - The SVG file's contents
+/// * `size` - The length of the longer side of the image, or the SVG's own
+///   size when `None`
+/// * `max_size` - The longest the longer side can be, rendering smaller
+///   rather than downscaling afterwards
+/// * `limits` - The decoding limits, enforced before rendering
+pub(crate) fn rasterize_svg(
+    data: &[u8],
+    size: Option<u32>,
+    max_size: Option<u32>,
+    limits: &Limits,
+) -> ImageResult<RgbaImage> {
+    let mut options = usvg::Options::default();
+
+    options.fontdb_mut().load_system_fonts();
+
+    let tree = usvg::Tree::from_data(data, &options).map_err(svg_error)?;
+    let tree_size = tree.size();
+    let longer_side = tree_size.width().max(tree_size.height());
+    let scale = match size {
+        Some(size) => size as f32 / longer_side,
+        None => 1.0,
+    };
+    let scale = match max_size {
+        Some(max_size) => scale.min(max_size as f32 / longer_side),
+        None => scale,
+    };
+    let width = (tree_size.width() * scale).round().max(1.0) as u32;
+    let height = (tree_size.height() * scale).round().max(1.0) as u32;
+
+    limits.check_dimensions(width, height)?;
+
+    if limits
+        .max_alloc
+        .is_some_and(|max_alloc| u64::from(width) * u64::from(height) * 4 > max_alloc)
+    {
+        return Err(ImageError::Limits(LimitError::from_kind(
+            LimitErrorKind::InsufficientMemory,
+        )));
+    }
+
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| svg_error("The SVG has no area to render"))?;
+
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+
+    // tiny-skia stores premultiplied alpha
+    let pixels = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+
+    RgbaImage::from_raw(width, height, pixels)
+        .ok_or_else(|| svg_error("Unexpected SVG buffer size"))
+}
+
+fn svg_error(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> ImageError {
+    ImageError::Decoding(DecodingError::new(
+        ImageFormatHint::Name("SVG".to_string()),
+        err,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rasterize_svg() {
+        let data = br##"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10">
+            <rect width="10" height="10" fill="#ff0000"/>
+            <rect x="10" width="10" height="10" fill="#0000ff"/>
+        </svg>"##;
+
+        assert!(is_svg(data));
+        assert!(!is_svg(b"\x89PNG\r\n\x1a\n"));
+
+        let image = rasterize_svg(data, Some(40), None, &Limits::default()).unwrap();
+
+        assert_eq!(image.dimensions(), (40, 20));
+        assert_eq!(image.get_pixel(5, 10).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(35, 10).0, [0, 0, 255, 255]);
+
+        let mut limits = Limits::default();
+
+        limits.max_image_width = Some(30);
+
+        assert!(rasterize_svg(data, Some(40), None, &limits).is_err());
+    }
+}
@@ -0,0 +1,71 @@
+use std::fmt::Write;
+
+use tinted_builder::Base16Scheme;
+
+use crate::{
+    adjust::scheme_slots,
+    export::{single_line, slot_hex},
+    Error,
+};
+
+/// Names for the slots whose role is the same in every scheme, after the
+/// base16 styling guidelines
+const ALIASES: [(&str, &str); 12] = [
+    ("background", "base00"),
+    ("surface", "base01"),
+    ("selection", "base02"),
+    ("comment", "base03"),
+    ("foreground", "base05"),
+    ("red", "base08"),
+    ("orange", "base09"),
+    ("yellow", "base0A"),
+    ("green", "base0B"),
+    ("cyan", "base0C"),
+    ("blue", "base0D"),
+    ("purple", "base0E"),
+];
+
+/// Render a Tailwind CSS config that adds every slot of the scheme, e.g.
+/// `bg-base00`, and the slots' roles, e.g. `text-foreground`, to the
+/// theme's colors
+///
+/// # Arguments
+/// * `scheme` - A reference to the generated Base16Scheme
+pub fn tailwind_config(scheme: &Base16Scheme) -> Result<String, Error> {
+    let mut config = format!(
+        "// {} by {}\n/** @type {{import('tailwindcss').Config}} */\nmodule.exports = {{\n  theme: {{\n    extend: {{\n      colors: {{\n",
+        single_line(&scheme.name),
+        single_line(&scheme.author)
+    );
+
+    for slot in scheme_slots(&scheme.system) {
+        let _ = writeln!(config, "        {}: '{}',", slot, slot_hex(scheme, &slot)?);
+    }
+
+    for (alias, slot) in ALIASES {
+        let _ = writeln!(config, "        {}: '{}',", alias, slot_hex(scheme, slot)?);
+    }
+
+    config.push_str("      },\n    },\n  },\n};\n");
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::test_scheme;
+    use tinted_builder::SchemeSystem;
+
+    #[test]
+    fn test_tailwind_config() {
+        let config = tailwind_config(&test_scheme(SchemeSystem::Base16)).unwrap();
+
+        assert!(config.starts_with("// Test \"Scheme\" by Author\n"));
+        assert!(config.contains("      colors: {\n        base00: '#000000',\n"));
+        assert!(config.contains("        base0F: '#804000',\n"));
+        assert!(!config.contains("base10"));
+        assert!(config.contains("        foreground: '#b6b6b6',\n"));
+        assert!(config.ends_with("        purple: '#8000ff',\n      },\n    },\n  },\n};\n"));
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use tinted_builder::{Base16Scheme, Scheme, SchemeSystem, SchemeVariant, Template};
+
+use crate::{adjust::scheme_slots, Error};
+
+/// Get the variables tinted-builder templates are rendered with, following
+/// the builder specification, e.g. `scheme-name`, `base00-hex` and
+/// `base00-rgb-r`
+///
+/// `scheme-is-light-variant` and `scheme-is-dark-variant` are only set when
+/// true, so they can be used as mustache sections. `scheme-description` is
+/// left out when the scheme has no description.
+///
+/// # Arguments
+/// * `scheme` - A reference to the generated Base16Scheme
+pub fn template_context(scheme: &Base16Scheme) -> BTreeMap<String, String> {
+    let mut context = BTreeMap::from([
+        ("scheme-name".to_string(), scheme.name.clone()),
+        ("scheme-author".to_string(), scheme.author.clone()),
+        ("scheme-slug".to_string(), scheme.slug.clone()),
+        (
+            "scheme-slug-underscored".to_string(),
+            scheme.slug.replace('-', "_"),
+        ),
+        ("scheme-system".to_string(), scheme.system.to_string()),
+        ("scheme-variant".to_string(), scheme.variant.to_string()),
+    ]);
+
+    if let Some(description) = &scheme.description {
+        context.insert("scheme-description".to_string(), description.clone());
+    }
+
+    match scheme.variant {
+        SchemeVariant::Light => {
+            context.insert("scheme-is-light-variant".to_string(), "true".to_string())
+        }
+        _ => context.insert("scheme-is-dark-variant".to_string(), "true".to_string()),
+    };
+
+    for slot in scheme_slots(&scheme.system) {
+        let Some(color) = scheme.palette.get(&slot) else {
+            continue;
+        };
+        let (red, green, blue) = color.rgb;
+        let (hex_red, hex_green, hex_blue) = &color.hex;
+
+        context.extend([
+            (
+                format!("{slot}-hex"),
+                format!("{hex_red}{hex_green}{hex_blue}"),
+            ),
+            (
+                format!("{slot}-hex-bgr"),
+                format!("{hex_blue}{hex_green}{hex_red}"),
+            ),
+            (format!("{slot}-hex-r"), hex_red.clone()),
+            (format!("{slot}-hex-g"), hex_green.clone()),
+            (format!("{slot}-hex-b"), hex_blue.clone()),
+            (format!("{slot}-rgb-r"), red.to_string()),
+            (format!("{slot}-rgb-g"), green.to_string()),
+            (format!("{slot}-rgb-b"), blue.to_string()),
+            (format!("{slot}-rgb16-r"), (red as u16 * 257).to_string()),
+            (format!("{slot}-rgb16-g"), (green as u16 * 257).to_string()),
+            (format!("{slot}-rgb16-b"), (blue as u16 * 257).to_string()),
+            (
+                format!("{slot}-dec-r"),
+                format!("{:.8}", red as f32 / 255.0),
+            ),
+            (
+                format!("{slot}-dec-g"),
+                format!("{:.8}", green as f32 / 255.0),
+            ),
+            (
+                format!("{slot}-dec-b"),
+                format!("{:.8}", blue as f32 / 255.0),
+            ),
+        ]);
+    }
+
+    context
+}
+
+/// Render a tinted-builder mustache template with the scheme, without
+/// writing the scheme to a file first
+///
+/// # Arguments
+/// * `template` - The template content, e.g. of a `templates/*.mustache` file
+/// * `scheme` - A reference to the generated Base16Scheme
+pub fn render_template(template: &str, scheme: &Base16Scheme) -> Result<String, Error> {
+    let scheme = match scheme.system {
+        SchemeSystem::Base24 => Scheme::Base24(scheme.clone()),
+        _ => Scheme::Base16(scheme.clone()),
+    };
+
+    Template::new(template.to_string(), scheme)
+        .render()
+        .map_err(|source| Error::Template { source })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tinted_builder::Color as SchemeColor;
+
+    #[test]
+    fn test_template_context() {
+        let scheme = Base16Scheme {
+            author: "Author".to_string(),
+            description: None,
+            name: "Name".to_string(),
+            slug: "my-name".to_string(),
+            system: SchemeSystem::Base16,
+            variant: SchemeVariant::Light,
+            palette: HashMap::from([(
+                "base08".to_string(),
+                SchemeColor::new("FF8000".to_string()).unwrap(),
+            )]),
+        };
+        let context = template_context(&scheme);
+
+        assert_eq!(context["scheme-slug-underscored"], "my_name");
+        assert_eq!(context["scheme-is-light-variant"], "true");
+        assert!(!context.contains_key("scheme-is-dark-variant"));
+        assert!(!context.contains_key("scheme-description"));
+        assert_eq!(context["base08-hex"], "ff8000");
+        assert_eq!(context["base08-hex-bgr"], "0080ff");
+        assert_eq!(context["base08-rgb-g"], "128");
+        assert_eq!(context["base08-rgb16-r"], "65535");
+        assert_eq!(context["base08-dec-b"], "0.00000000");
+        assert!(!context.contains_key("base00-hex"));
+    }
+}
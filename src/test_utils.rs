@@ -0,0 +1,214 @@
+use std::{env, fs, path::Path};
+
+use image::{DynamicImage, Rgb, RgbImage};
+use palette::{Lch, Srgb};
+use tinted_builder::Base16Scheme;
+
+use crate::gamut::to_srgb_within_gamut;
+
+/// Set to rewrite golden files with the schemes they're compared to
+/// instead of failing
+pub const UPDATE_GOLDEN_VAR: &str = "TINTED_SCHEME_EXTRACTOR_UPDATE_GOLDEN";
+
+/// How many of the pixels of `noise_image` have the dominant hue
+const DOMINANT_FRACTION: f32 = 0.75;
+/// How far from the dominant hue those pixels may be, in degrees
+const HUE_JITTER: f32 = 10.0;
+
+/// Create an image of a single color
+///
+/// # Arguments
+/// * `width` - The width of the image
+/// * `height` - The height of the image
+/// * `color` - The color of every pixel
+pub fn flat_image(width: u32, height: u32, color: Srgb<u8>) -> DynamicImage {
+    DynamicImage::ImageRgb8(RgbImage::from_pixel(
+        width,
+        height,
+        Rgb([color.red, color.green, color.blue]),
+    ))
+}
+
+/// Create an image blending from one color on the left to another on the
+/// right
+///
+/// # Arguments
+/// * `width` - The width of the image
+/// * `height` - The height of the image
+/// * `from` - The color of the leftmost column
+/// * `to` - The color of the rightmost column
+pub fn gradient_image(width: u32, height: u32, from: Srgb<u8>, to: Srgb<u8>) -> DynamicImage {
+    let from: Srgb<f32> = from.into_format();
+    let to: Srgb<f32> = to.into_format();
+
+    DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, _| {
+        let t = x as f32 / width.saturating_sub(1).max(1) as f32;
+        let color: Srgb<u8> = Srgb::new(
+            from.red + (to.red - from.red) * t,
+            from.green + (to.green - from.green) * t,
+            from.blue + (to.blue - from.blue) * t,
+        )
+        .into_format();
+
+        Rgb([color.red, color.green, color.blue])
+    }))
+}
+
+/// Create an image of random colors, three quarters of them within 10
+/// degrees of an LCh hue, so it's the image's dominant hue, as reported in
+/// `Diagnostics::dominant_hue`. The same seed always creates the same image
+///
+/// # Arguments
+/// * `width` - The width of the image
+/// * `height` - The height of the image
+/// * `hue` - The dominant LCh hue, in degrees
+/// * `seed` - The seed of the random colors
+pub fn noise_image(width: u32, height: u32, hue: f32, seed: u64) -> DynamicImage {
+    let mut rng = fastrand::Rng::with_seed(seed);
+
+    DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |_, _| {
+        let pixel_hue = if rng.f32() < DOMINANT_FRACTION {
+            hue + (rng.f32() * 2.0 - 1.0) * HUE_JITTER
+        } else {
+            rng.f32() * 360.0
+        };
+        let color = to_srgb_within_gamut(Lch::new(
+            40.0 + rng.f32() * 30.0,
+            30.0 + rng.f32() * 30.0,
+            pixel_hue,
+        ));
+
+        Rgb([color.red, color.green, color.blue])
+    }))
+}
+
+/// Assert that a scheme's YAML matches a golden file, e.g.
+/// `tests/golden/sunset.yaml`, panicking with the lines that differ. With
+/// `TINTED_SCHEME_EXTRACTOR_UPDATE_GOLDEN` set, the golden file is written
+/// instead, creating its directory
+///
+/// # Arguments
+/// * `scheme` - A reference to the generated Base16Scheme
+/// * `path` - The path of the golden YAML file
+pub fn assert_golden_scheme(scheme: &Base16Scheme, path: impl AsRef<Path>) {
+    let path = path.as_ref();
+    let actual = scheme.to_string();
+
+    if env::var_os(UPDATE_GOLDEN_VAR).is_some() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("the golden file's directory can be created");
+        }
+
+        fs::write(path, actual).expect("the golden file can be written");
+
+        return;
+    }
+
+    let expected = fs::read_to_string(path).unwrap_or_else(|err| {
+        panic!(
+            "Unable to read golden file {}: {}. Set {} to create it",
+            path.display(),
+            err,
+            UPDATE_GOLDEN_VAR
+        )
+    });
+
+    if expected == actual {
+        return;
+    }
+
+    let expected_lines: Vec<&str> = expected.lines().collect();
+    let actual_lines: Vec<&str> = actual.lines().collect();
+    let mut diff = String::new();
+
+    for index in 0..expected_lines.len().max(actual_lines.len()) {
+        let (expected_line, actual_line) = (expected_lines.get(index), actual_lines.get(index));
+
+        if expected_line != actual_line {
+            if let Some(line) = expected_line {
+                diff.push_str(&format!("- {}\n", line));
+            }
+            if let Some(line) = actual_line {
+                diff.push_str(&format!("+ {}\n", line));
+            }
+        }
+    }
+
+    panic!(
+        "Scheme doesn't match golden file {}. Set {} to update it\n{}",
+        path.display(),
+        UPDATE_GOLDEN_VAR,
+        diff
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{extract_scheme_from_dynamic_image, SchemeParams};
+    use std::path::PathBuf;
+    use tinted_builder::{SchemeSystem, SchemeVariant};
+
+    #[test]
+    fn test_synthetic_images_and_golden_schemes() {
+        let flat = flat_image(4, 2, Srgb::new(10, 20, 30)).to_rgb8();
+
+        assert!(flat.pixels().all(|pixel| pixel.0 == [10, 20, 30]));
+
+        let gradient = gradient_image(5, 1, Srgb::new(0, 0, 0), Srgb::new(200, 100, 0)).to_rgb8();
+
+        assert_eq!(gradient.get_pixel(0, 0).0, [0, 0, 0]);
+        assert_eq!(gradient.get_pixel(2, 0).0, [100, 50, 0]);
+        assert_eq!(gradient.get_pixel(4, 0).0, [200, 100, 0]);
+
+        let noise = noise_image(64, 64, 210.0, 7);
+
+        assert_eq!(noise, noise_image(64, 64, 210.0, 7));
+
+        let params = SchemeParams {
+            image_path: PathBuf::new(),
+            author: "Author".to_string(),
+            description: None,
+            name: "Noise".to_string(),
+            slug: "noise".to_string(),
+            system: SchemeSystem::Base16,
+            variant: SchemeVariant::Dark,
+            verbose: false,
+            options: Default::default(),
+        };
+        let extraction = extract_scheme_from_dynamic_image(&noise, params).unwrap();
+        let dominant_hue = extraction.diagnostics.dominant_hue.unwrap();
+
+        assert!((dominant_hue - 210.0).abs() < 30.0, "{}", dominant_hue);
+
+        let path = env::temp_dir().join(format!(
+            "tinted-scheme-extractor-golden-{}.yaml",
+            std::process::id()
+        ));
+
+        fs::write(&path, extraction.scheme.to_string()).unwrap();
+        assert_golden_scheme(&extraction.scheme, &path);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "Scheme doesn't match golden file")]
+    fn test_golden_scheme_mismatch() {
+        let path = env::temp_dir().join(format!(
+            "tinted-scheme-extractor-mismatch-{}.yaml",
+            std::process::id()
+        ));
+        let mut scheme = crate::export::tests::test_scheme(SchemeSystem::Base16);
+
+        fs::write(&path, scheme.to_string()).unwrap();
+        scheme.palette.insert(
+            "base00".to_string(),
+            tinted_builder::Color::new("ffffff".to_string()).unwrap(),
+        );
+
+        let result = std::panic::catch_unwind(|| assert_golden_scheme(&scheme, &path));
+
+        fs::remove_file(&path).unwrap();
+        std::panic::resume_unwind(result.unwrap_err());
+    }
+}
@@ -0,0 +1,151 @@
+use std::{env, fs, path::PathBuf, process::Command};
+
+use tinted_builder::Base16Scheme;
+
+use crate::Error;
+
+/// Get tinty's data directory
+/// This is `$XDG_DATA_HOME/tinted-theming/tinty`, falling back to
+/// `~/.local/share` when `XDG_DATA_HOME` isn't set. It can be changed with
+/// tinty's `--data-dir` flag, see [`TintyScheme::write`]
+pub fn tinty_data_dir() -> Result<PathBuf, Error> {
+    let data_dir = match env::var_os("XDG_DATA_HOME").filter(|value| !value.is_empty()) {
+        Some(data_dir) => PathBuf::from(data_dir),
+        None => env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(".local").join("share"))
+            .ok_or_else(|| Error::Tinty {
+                message: "Unable to determine the home directory".to_string(),
+                source: None,
+            })?,
+    };
+
+    Ok(data_dir.join("tinted-theming").join("tinty"))
+}
+
+/// Get the directory tinty reads custom schemes from
+/// This is `custom-schemes` in [`tinty_data_dir`]
+pub fn tinty_custom_schemes_dir() -> Result<PathBuf, Error> {
+    Ok(tinty_data_dir()?.join("custom-schemes"))
+}
+
+/// A scheme written into tinty's custom schemes directory, ready to be
+/// applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TintyScheme {
+    /// The path of the written scheme file
+    pub scheme_path: PathBuf,
+    /// The name tinty knows the scheme by, `<system>-<slug>`
+    pub scheme_name: String,
+    /// The tinty data directory, or `None` for tinty's default
+    pub data_dir: Option<PathBuf>,
+}
+
+impl TintyScheme {
+    /// Write the scheme into the `custom-schemes/<system>/<slug>.yaml` file
+    /// tinty reads it from
+    ///
+    /// # Arguments
+    /// * `scheme` - A reference to the generated Base16Scheme
+    /// * `data_dir` - The tinty data directory, as passed to tinty's
+    ///   `--data-dir` flag, or `None` for [`tinty_data_dir`]
+    pub fn write(scheme: &Base16Scheme, data_dir: Option<PathBuf>) -> Result<Self, Error> {
+        let custom_schemes_dir = match &data_dir {
+            Some(data_dir) => data_dir.join("custom-schemes"),
+            None => tinty_custom_schemes_dir()?,
+        };
+        let system_dir = custom_schemes_dir.join(scheme.system.to_string());
+        let scheme_path = system_dir.join(format!("{}.yaml", scheme.slug));
+
+        fs::create_dir_all(&system_dir).map_err(|err| Error::Tinty {
+            message: format!("Unable to create {}", system_dir.display()),
+            source: Some(err),
+        })?;
+        fs::write(&scheme_path, scheme.to_string()).map_err(|err| Error::Tinty {
+            message: format!("Unable to write {}", scheme_path.display()),
+            source: Some(err),
+        })?;
+
+        Ok(TintyScheme {
+            scheme_path,
+            scheme_name: format!("{}-{}", scheme.system, scheme.slug),
+            data_dir,
+        })
+    }
+
+    /// Get the `tinty apply` command that applies the scheme, e.g. to run it
+    /// with extra environment variables or to hand it to another process.
+    /// Applying runs the hooks configured in tinty's `config.toml`
+    pub fn apply_command(&self) -> Command {
+        let mut command = Command::new("tinty");
+
+        if let Some(data_dir) = &self.data_dir {
+            command.arg("--data-dir").arg(data_dir);
+        }
+
+        command.arg("apply").arg(&self.scheme_name);
+        command
+    }
+
+    /// Run [`TintyScheme::apply_command`], waiting for tinty and its hooks
+    /// to finish
+    ///
+    /// `tinty` needs to be available in `PATH`.
+    pub fn apply(&self) -> Result<(), Error> {
+        let status = self.apply_command().status().map_err(|err| Error::Tinty {
+            message: "Unable to run tinty".to_string(),
+            source: Some(err),
+        })?;
+
+        if !status.success() {
+            return Err(Error::Tinty {
+                message: format!("`tinty apply {}` exited with {}", self.scheme_name, status),
+                source: None,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Write the scheme into tinty's custom schemes directory and return the
+/// path of the written file
+///
+/// # Arguments
+/// * `scheme` - A reference to the generated Base16Scheme
+pub fn write_tinty_custom_scheme(scheme: &Base16Scheme) -> Result<PathBuf, Error> {
+    TintyScheme::write(scheme, None).map(|tinty_scheme| tinty_scheme.scheme_path)
+}
+
+/// Write the scheme where tinty expects it and run `tinty apply` with it
+///
+/// `tinty` needs to be available in `PATH`. The path of the written scheme
+/// file is returned.
+///
+/// # Arguments
+/// * `scheme` - A reference to the generated Base16Scheme
+pub fn apply_with_tinty(scheme: &Base16Scheme) -> Result<PathBuf, Error> {
+    let tinty_scheme = TintyScheme::write(scheme, None)?;
+
+    tinty_scheme.apply()?;
+
+    Ok(tinty_scheme.scheme_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_command_passes_data_dir() {
+        let tinty_scheme = TintyScheme {
+            scheme_path: PathBuf::from("/data/custom-schemes/base16/wallpaper.yaml"),
+            scheme_name: "base16-wallpaper".to_string(),
+            data_dir: Some(PathBuf::from("/data")),
+        };
+        let command = tinty_scheme.apply_command();
+        let args: Vec<_> = command.get_args().collect();
+
+        assert_eq!(command.get_program(), "tinty");
+        assert_eq!(args, ["--data-dir", "/data", "apply", "base16-wallpaper"]);
+    }
+}
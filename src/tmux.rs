@@ -0,0 +1,86 @@
+use std::fmt::Write;
+
+use tinted_builder::Base16Scheme;
+
+use crate::{
+    export::{single_line, slot_hex},
+    Error,
+};
+
+/// The style options with their foreground, background and attributes
+const STYLES: [(&str, &str, &str, &str); 12] = [
+    ("status-style", "base04", "base01", ""),
+    ("window-status-style", "base04", "base01", ""),
+    ("window-status-current-style", "base00", "base0D", "bold"),
+    ("window-status-activity-style", "base0A", "base01", ""),
+    ("window-status-bell-style", "base08", "base01", ""),
+    ("window-style", "base04", "base00", ""),
+    ("window-active-style", "base05", "base00", ""),
+    ("pane-border-style", "base02", "base00", ""),
+    ("pane-active-border-style", "base0D", "base00", ""),
+    ("message-style", "base05", "base02", ""),
+    ("message-command-style", "base05", "base02", ""),
+    ("mode-style", "base05", "base02", ""),
+];
+/// The color options and their slots
+const COLOURS: [(&str, &str); 3] = [
+    ("display-panes-colour", "base03"),
+    ("display-panes-active-colour", "base0D"),
+    ("clock-mode-colour", "base0D"),
+];
+
+/// Render tmux options styling the status line, the windows, the pane
+/// borders and the messages from the grays, with base0D marking what's
+/// active, to source from `tmux.conf`
+///
+/// # Arguments
+/// * `scheme` - A reference to the generated Base16Scheme
+pub fn tmux_theme(scheme: &Base16Scheme) -> Result<String, Error> {
+    let mut theme = format!(
+        "# {} by {}\n",
+        single_line(&scheme.name),
+        single_line(&scheme.author)
+    );
+
+    for (option, foreground, background, attributes) in STYLES {
+        let mut style = format!(
+            "fg={},bg={}",
+            slot_hex(scheme, foreground)?,
+            slot_hex(scheme, background)?
+        );
+
+        if !attributes.is_empty() {
+            style.push(',');
+            style.push_str(attributes);
+        }
+
+        let _ = writeln!(theme, "set -g {} \"{}\"", option, style);
+    }
+
+    for (option, slot) in COLOURS {
+        let _ = writeln!(theme, "set -g {} \"{}\"", option, slot_hex(scheme, slot)?);
+    }
+
+    Ok(theme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::test_scheme;
+    use tinted_builder::SchemeSystem;
+
+    #[test]
+    fn test_tmux_theme() {
+        let theme = tmux_theme(&test_scheme(SchemeSystem::Base16)).unwrap();
+
+        assert!(theme.starts_with(
+            "# Test \"Scheme\" by Author\nset -g status-style \"fg=#929292,bg=#242424\"\n"
+        ));
+        assert!(
+            theme.contains("set -g window-status-current-style \"fg=#000000,bg=#0000ff,bold\"\n")
+        );
+        assert!(theme.contains("set -g pane-active-border-style \"fg=#0000ff,bg=#000000\"\n"));
+        assert!(theme.ends_with("set -g clock-mode-colour \"#0000ff\"\n"));
+    }
+}
@@ -0,0 +1,310 @@
+use palette::{FromColor, Hsl, Srgb};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color as TuiColor, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, ListState, Paragraph},
+    DefaultTerminal, Frame,
+};
+use tinted_builder::Base16Scheme;
+
+use crate::{
+    adjust::{adjust_scheme_color, scheme_slots, HslAdjustment},
+    utils::{accent_hues, error_chain, fill_missing_accents},
+    Error, PureColor,
+};
+
+const HUE_STEP: f32 = 5.0;
+const SATURATION_STEP: f32 = 0.02;
+const LIGHTNESS_STEP: f32 = 0.02;
+const HELP: &str = "j/k select  h/H hue  s/S saturation  l/L lightness  r reset  \
+                    a regenerate accent  enter save  q quit";
+
+/// What a key press asks of the editor
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Continue,
+    Save,
+    Quit,
+}
+
+/// The scheme being edited, along with the selection and the message shown
+/// in place of the help line
+struct Editor<'a> {
+    original: &'a Base16Scheme,
+    scheme: Base16Scheme,
+    slots: Vec<String>,
+    state: ListState,
+    message: Option<String>,
+}
+
+impl<'a> Editor<'a> {
+    fn new(original: &'a Base16Scheme) -> Self {
+        Editor {
+            original,
+            scheme: original.clone(),
+            slots: scheme_slots(&original.system),
+            state: ListState::default().with_selected(Some(0)),
+            message: None,
+        }
+    }
+
+    fn handle_key(&mut self, code: KeyCode) -> Outcome {
+        let selected = self.state.selected().unwrap_or(0);
+        let slot = self.slots[selected].clone();
+
+        self.message = None;
+
+        let adjustment = match code {
+            KeyCode::Char('q') | KeyCode::Esc => return Outcome::Quit,
+            KeyCode::Enter | KeyCode::Char('w') => return Outcome::Save,
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.state.select(Some((selected + 1) % self.slots.len()));
+                None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.state
+                    .select(Some((selected + self.slots.len() - 1) % self.slots.len()));
+                None
+            }
+            KeyCode::Char('r') => {
+                if let Some(color) = self.original.palette.get(&slot) {
+                    self.scheme.palette.insert(slot.clone(), color.clone());
+                }
+                None
+            }
+            KeyCode::Char('a') => {
+                if let Err(err) = self.regenerate_accent(&slot) {
+                    self.message = Some(error_chain(&err));
+                }
+                None
+            }
+            KeyCode::Char('h') => Some(HslAdjustment {
+                hue: -HUE_STEP,
+                ..Default::default()
+            }),
+            KeyCode::Char('H') => Some(HslAdjustment {
+                hue: HUE_STEP,
+                ..Default::default()
+            }),
+            KeyCode::Char('s') => Some(HslAdjustment {
+                saturation: -SATURATION_STEP,
+                ..Default::default()
+            }),
+            KeyCode::Char('S') => Some(HslAdjustment {
+                saturation: SATURATION_STEP,
+                ..Default::default()
+            }),
+            KeyCode::Char('l') => Some(HslAdjustment {
+                lightness: -LIGHTNESS_STEP,
+                ..Default::default()
+            }),
+            KeyCode::Char('L') => Some(HslAdjustment {
+                lightness: LIGHTNESS_STEP,
+                ..Default::default()
+            }),
+            _ => None,
+        };
+
+        if let Some(adjustment) = adjustment {
+            // Keep the edits made so far when a slot can't be adjusted
+            if let Err(err) = adjust_scheme_color(&mut self.scheme, &slot, adjustment) {
+                self.message = Some(error_chain(&err));
+            }
+        }
+
+        Outcome::Continue
+    }
+
+    /// Synthesize an accent again from the accents closest in hue, the same
+    /// as accents the image had no color for. A base accent takes its
+    /// Base24 bright accent along with it
+    fn regenerate_accent(&mut self, slot: &str) -> Result<(), Error> {
+        let index = u8::from_str_radix(slot.trim_start_matches("base"), 16)
+            .ok()
+            .filter(|index| (0x08..=0x17).contains(index))
+            .ok_or_else(|| Error::NotAnAccent {
+                slot: slot.to_string(),
+            })?;
+
+        self.scheme.palette.remove(slot);
+
+        if index < 0x10 {
+            self.scheme
+                .palette
+                .remove(&format!("base{:02X}", index + 0x08));
+        }
+
+        fill_missing_accents(
+            &mut self.scheme.palette,
+            &self.scheme.system,
+            base0f_pure_color(self.original),
+        )
+    }
+}
+
+/// The pure color base0F of a scheme represents, brown unless its hue is
+/// closer to magenta
+fn base0f_pure_color(scheme: &Base16Scheme) -> PureColor {
+    let Some(color) = scheme.palette.get("base0F") else {
+        return PureColor::Brown;
+    };
+    let (red, green, blue) = color.rgb;
+    let hue = Hsl::from_color(Srgb::new(red, green, blue).into_format::<f32>())
+        .hue
+        .into_positive_degrees();
+    let hue_distance = |pure_color: PureColor| {
+        let distance = (accent_hues(pure_color)[7] - hue).rem_euclid(360.0);
+
+        distance.min(360.0 - distance)
+    };
+
+    if hue_distance(PureColor::Magenta) < hue_distance(PureColor::Brown) {
+        PureColor::Magenta
+    } else {
+        PureColor::Brown
+    }
+}
+
+/// Open a terminal UI to fine-tune the colors of a generated scheme
+///
+/// Returns `Some` with the adjusted scheme when the user saves and `None`
+/// when they quit without saving.
+///
+/// # Arguments
+/// * `scheme` - A reference to the Base16Scheme to start from
+pub fn edit_scheme(scheme: &Base16Scheme) -> Result<Option<Base16Scheme>, Error> {
+    let mut terminal = ratatui::try_init().map_err(|source| Error::Tui { source })?;
+    let result = run(&mut terminal, scheme);
+
+    ratatui::try_restore().map_err(|source| Error::Tui { source })?;
+
+    result
+}
+
+fn run(
+    terminal: &mut DefaultTerminal,
+    original: &Base16Scheme,
+) -> Result<Option<Base16Scheme>, Error> {
+    let mut editor = Editor::new(original);
+
+    loop {
+        terminal
+            .draw(|frame| draw(frame, &mut editor))
+            .map_err(|source| Error::Tui { source })?;
+
+        let Event::Key(key) = event::read().map_err(|source| Error::Tui { source })? else {
+            continue;
+        };
+
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match editor.handle_key(key.code) {
+            Outcome::Continue => {}
+            Outcome::Save => return Ok(Some(editor.scheme)),
+            Outcome::Quit => return Ok(None),
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, editor: &mut Editor) {
+    let [list_area, help_area] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+    let items: Vec<ListItem> = editor
+        .slots
+        .iter()
+        .map(|slot| {
+            let line = match editor.scheme.palette.get(slot) {
+                Some(color) => {
+                    let (red, green, blue) = color.rgb;
+
+                    Line::from(vec![
+                        Span::styled(
+                            "      ",
+                            Style::default().bg(TuiColor::Rgb(red, green, blue)),
+                        ),
+                        Span::raw(format!(" {} #{:02x}{:02x}{:02x}", slot, red, green, blue)),
+                    ])
+                }
+                None => Line::from(format!("        {} (missing)", slot)),
+            };
+
+            ListItem::new(line)
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::bordered().title(format!(" {} ", editor.scheme.name)))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+
+    frame.render_stateful_widget(list, list_area, &mut editor.state);
+
+    let help = match &editor.message {
+        Some(message) => Paragraph::new(message.as_str()).style(Style::default().fg(TuiColor::Red)),
+        None => Paragraph::new(HELP),
+    };
+
+    frame.render_widget(help, help_area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::test_scheme;
+    use tinted_builder::SchemeSystem;
+
+    fn hue(scheme: &Base16Scheme, slot: &str) -> f32 {
+        let (red, green, blue) = scheme.palette[slot].rgb;
+
+        Hsl::from_color(Srgb::new(red, green, blue).into_format::<f32>())
+            .hue
+            .into_positive_degrees()
+    }
+
+    #[test]
+    fn test_handle_key() {
+        let mut original = test_scheme(SchemeSystem::Base24);
+
+        original.palette.remove("base03");
+
+        let mut editor = Editor::new(&original);
+
+        for _ in 0..11 {
+            assert_eq!(editor.handle_key(KeyCode::Char('j')), Outcome::Continue);
+        }
+
+        assert_eq!(editor.slots[editor.state.selected().unwrap()], "base0B");
+
+        editor.handle_key(KeyCode::Char('H'));
+        assert!((hue(&editor.scheme, "base0B") - 125.0).abs() < 1.0);
+
+        editor.handle_key(KeyCode::Char('r'));
+        assert_eq!(editor.scheme.palette["base0B"], original.palette["base0B"]);
+
+        // A bad accent is synthesized again from its neighbours, along with
+        // its bright accent
+        editor
+            .scheme
+            .palette
+            .insert("base0B".to_string(), original.palette["base08"].clone());
+        editor.handle_key(KeyCode::Char('a'));
+        assert!((hue(&editor.scheme, "base0B") - 120.0).abs() < 1.0);
+        assert!((hue(&editor.scheme, "base13") - 120.0).abs() < 1.0);
+        assert_ne!(editor.scheme.palette["base13"], original.palette["base13"]);
+        assert_eq!(editor.message, None);
+
+        // Errors are shown instead of ending the editor and losing the edits
+        editor.state.select(Some(3));
+        assert_eq!(editor.handle_key(KeyCode::Char('l')), Outcome::Continue);
+        assert!(editor.message.is_some());
+        assert_eq!(editor.handle_key(KeyCode::Char('a')), Outcome::Continue);
+        assert_eq!(editor.message.as_deref(), Some("base03 isn't an accent"));
+        assert!((hue(&editor.scheme, "base0B") - 120.0).abs() < 1.0);
+
+        assert_eq!(editor.handle_key(KeyCode::Enter), Outcome::Save);
+        assert_eq!(editor.handle_key(KeyCode::Char('q')), Outcome::Quit);
+    }
+}
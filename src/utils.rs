@@ -1,82 +1,1707 @@
-use std::{collections::HashMap, path::Path};
+#[cfg(any(feature = "heif", feature = "jxl", feature = "pdf", feature = "svg"))]
+use std::io::Read;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::{self, BufRead, BufReader, Seek},
+    ops::{Range, RangeInclusive},
+    path::Path,
+};
+
+#[cfg(feature = "zune-jpeg")]
+use crate::decoder::decode_jpeg;
+#[cfg(feature = "heif")]
+use crate::heif::{decode_heif, is_heif};
+#[cfg(feature = "jpeg")]
+use crate::jpeg::decode_scaled_jpeg;
+#[cfg(feature = "jxl")]
+use crate::jxl::{decode_jxl, is_jxl};
+#[cfg(feature = "pdf")]
+use crate::pdf::{is_pdf, rasterize_pdf_page};
+#[cfg(feature = "raw")]
+use crate::raw::{decode_raw, is_raw};
+#[cfg(feature = "svg")]
+use crate::svg::{is_svg, rasterize_svg};
+#[cfg(feature = "video")]
+use crate::video::{decode_video_frame, is_video};
+#[cfg(any(feature = "gif", feature = "png", feature = "webp"))]
+use crate::FrameSelection;
+use crate::{
+    color::{Color, PureColor},
+    color_lut,
+    gamut::to_srgb_within_gamut,
+    superpixel::superpixel_image,
+    ColorPass, CropRegion, Denoise, Error, ExtractionOptions, HueRange, SubjectBias,
+    VariantProfile,
+};
+#[cfg(feature = "gif")]
+use image::codecs::gif::GifDecoder;
+#[cfg(feature = "png")]
+use image::codecs::png::PngDecoder;
+#[cfg(feature = "webp")]
+use image::codecs::webp::WebPDecoder;
+#[cfg(not(feature = "avif-native"))]
+use image::error::{UnsupportedError, UnsupportedErrorKind};
+use image::{
+    imageops, DynamicImage, GenericImageView, ImageError, ImageFormat, ImageReader, Limits,
+    RgbaImage,
+};
+#[cfg(any(feature = "gif", feature = "png", feature = "webp"))]
+use image::{AnimationDecoder, Frame, Frames, ImageDecoder, ImageResult};
+use palette::{
+    color_difference::Ciede2000, rgb::Rgb, FromColor, Hsl, IntoColor, Lab, Lch, Srgb, Yxy,
+};
+use tinted_builder::{Color as SchemeColor, SchemeSystem, SchemeVariant};
+
+/// How many bytes at the start of a file tell apart the formats the image
+/// crate can't guess
+#[cfg(any(feature = "heif", feature = "jxl", feature = "pdf", feature = "svg"))]
+pub(crate) const SNIFF_LENGTH: usize = 1024;
+/// The fewest colors color_thief has to find for its palette to be used
+#[cfg(feature = "color-thief")]
+const MIN_COLOR_THIEF_COLORS: usize = 2;
+/// The chroma a dominant color needs for its hue to tint the grays
+const MIN_DOMINANT_CHROMA: f32 = 10.0;
+/// The smallest contrast ratio between a locked background and base07
+const LOCKED_MIN_CONTRAST: f32 = 7.0;
+/// Hues of base08 to base0F, and of base10 to base17 for Base24
+pub(crate) const ACCENT_HUES: [f32; 8] = [0.0, 30.0, 60.0, 120.0, 180.0, 220.0, 280.0, 20.0];
+/// The hue of base0F when it represents magenta
+const MAGENTA_HUE: f32 = 300.0;
+/// How far the second dominant hue has to be from the dominant hue
+const MIN_SECOND_HUE_DISTANCE: f32 = 45.0;
+/// The LCh chroma below which a color's hue is too unstable to keep
+const MIN_HUE_CHROMA: f32 = 2.0;
+/// How light base00 has to be, as relative luminance, for yellow accents
+/// to be darkened
+const LIGHT_BACKGROUND_LUMA: f32 = 0.5;
+/// The HSL hues of yellow accents, which are hard to read on light
+/// backgrounds
+const YELLOW_HUES: RangeInclusive<f32> = 40.0..=75.0;
+/// The contrast ratio against a light base00 yellow accents are darkened
+/// to, WCAG's minimum for large text
+const YELLOW_MIN_CONTRAST: f32 = 3.0;
+/// The luma of skin tones, leaving out near-black shadows
+const SKIN_TONE_LUMA: RangeInclusive<f32> = 40.0..=240.0;
+/// The blue-difference chrominance of skin tones
+const SKIN_TONE_CB: RangeInclusive<f32> = 77.0..=127.0;
+/// The red-difference chrominance of skin tones
+const SKIN_TONE_CR: RangeInclusive<f32> = 133.0..=173.0;
+
+/// Get how far a pixel is from the center of the image, between 0.0 at the
+/// center and 1.0 at the corners
+fn center_distance(x: u32, y: u32, width: u32, height: u32) -> f32 {
+    let dx = (x as f32 + 0.5) / width as f32 - 0.5;
+    let dy = (y as f32 + 0.5) / height as f32 - 0.5;
+
+    (dx * dx + dy * dy).sqrt() / std::f32::consts::FRAC_1_SQRT_2
+}
+
+fn crop_image(image: &DynamicImage, region: CropRegion) -> Result<DynamicImage, Error> {
+    let (width, height) = image.dimensions();
+
+    if region.width == 0
+        || region.height == 0
+        || region.x.saturating_add(region.width) > width
+        || region.y.saturating_add(region.height) > height
+    {
+        return Err(Error::InvalidCropRegion {
+            region,
+            width,
+            height,
+        });
+    }
+
+    Ok(image.crop_imm(region.x, region.y, region.width, region.height))
+}
+
+/// Number of image rows scanned together, which is the unit of work that is
+/// spread over threads with the `rayon` feature
+const ROWS_PER_CHUNK: u64 = 64;
+
+const TARGET_COLORS: [PureColor; 12] = PureColor::ALL;
+
+/// The result of a single traversal over the pixels of the source images
+pub(crate) struct PixelScan<'a> {
+    /// The pixel closest to each pure color
+    pub(crate) closest_palette: Vec<Color>,
+    /// The RGBA pixels to hand to color_thief. This borrows the image buffer
+    /// when every pixel of a single image is used
+    pub(crate) color_thief_pixels: Cow<'a, [u8]>,
+}
+
+/// The scan result for a range of pixels
+struct PartialScan {
+    closest_distances: [f64; 12],
+    closest_palette: Vec<Color>,
+    /// The index of the largest closest distance, that distance and the
+    /// second largest one
+    farthest: (usize, f64, f64),
+    color_thief_pixels: Vec<u8>,
+}
+
+impl PartialScan {
+    fn new() -> Self {
+        PartialScan {
+            closest_distances: [f64::MAX; 12],
+            closest_palette: TARGET_COLORS.iter().map(|c| Color::from(*c)).collect(),
+            farthest: (0, f64::MAX, f64::MAX),
+            color_thief_pixels: Vec::new(),
+        }
+    }
+
+    /// The largest closest distance of all pure colors but the one at
+    /// `index`
+    fn farthest_except(&self, index: usize) -> f64 {
+        let (farthest_index, farthest, second) = self.farthest;
+
+        if index == farthest_index {
+            second
+        } else {
+            farthest
+        }
+    }
+
+    /// Keep `color` as the closest color to the pure color at `index` if
+    /// its weighted distance is smaller than the closest one so far
+    fn compare(&mut self, index: usize, color: Srgb<u8>, weight: f64) {
+        let pure_color = TARGET_COLORS[index];
+        let distance = Color::get_distance(&color, &pure_color.get_rgb());
+
+        if distance * weight >= self.closest_distances[index] {
+            return;
+        }
+
+        self.closest_distances[index] = distance * weight;
+        self.closest_palette[index] = Color {
+            associated_pure_color: pure_color,
+            value: color,
+            distance,
+        };
+        self.farthest = (0, f64::MIN, f64::MIN);
+
+        for (i, distance) in self.closest_distances.iter().enumerate() {
+            if *distance > self.farthest.1 {
+                self.farthest = (i, *distance, self.farthest.1);
+            } else if *distance > self.farthest.2 {
+                self.farthest.2 = *distance;
+            }
+        }
+    }
+
+    /// Merge a scan of later pixels into this one
+    /// Ties are kept by the earlier pixel, the same as a sequential scan.
+    #[cfg(feature = "rayon")]
+    fn merge(mut self, other: PartialScan) -> Self {
+        for (i, distance) in other.closest_distances.iter().enumerate() {
+            if *distance < self.closest_distances[i] {
+                self.closest_distances[i] = *distance;
+                self.closest_palette[i] = other.closest_palette[i];
+            }
+        }
+
+        self.color_thief_pixels
+            .extend_from_slice(&other.color_thief_pixels);
+
+        self
+    }
+}
+
+/// Scan the pixels of an image with a row-order index in `range`
+fn scan_range(
+    mut scan: PartialScan,
+    image: &RgbaImage,
+    range: Range<u64>,
+    options: &ExtractionOptions,
+    collect_pixels: bool,
+) -> PartialScan {
+    let (width, height) = image.dimensions();
+    let center_weight = options.center_weight.clamp(0.0, 1.0);
+    let sample_every = options.sample_every.max(1) as u64;
+    // Only every `sample_every`th pixel of the whole image is looked at, so
+    // start at the first one in this range
+    let start = range.start.div_ceil(sample_every) * sample_every;
+
+    if start >= range.end {
+        return scan;
+    }
+
+    let pixels = image.as_raw()[start as usize * 4..range.end as usize * 4]
+        .chunks_exact(4)
+        .step_by(sample_every as usize);
+
+    for (offset, pixel) in pixels.enumerate() {
+        let color = Srgb::new(pixel[0], pixel[1], pixel[2]);
+        // Finding a pixel's position takes a division, so it's only done
+        // when the center weight needs it
+        let (weight, keep) = if center_weight > 0.0 {
+            let index = start + offset as u64 * sample_every;
+            let x = (index % width as u64) as u32;
+            let y = (index / width as u64) as u32;
+            let drop_chance = center_weight * center_distance(x, y, width, height);
+
+            (1.0 + drop_chance as f64, dither_keep(x, y, drop_chance))
+        } else {
+            (1.0, true)
+        };
+        let entry = color_lut::lookup(color);
+
+        // No color of the pixel's cell is closer than `bucket` to any pure
+        // color, and weights only scale distances up, so once every other
+        // pure color has a pixel that close only the nearest one can change
+        if entry.bucket as f64 >= scan.farthest_except(entry.nearest) {
+            scan.compare(entry.nearest, color, weight);
+        } else {
+            for i in 0..TARGET_COLORS.len() {
+                scan.compare(i, color, weight);
+            }
+        }
+
+        if collect_pixels && keep {
+            scan.color_thief_pixels.extend_from_slice(pixel);
+        }
+    }
+
+    scan
+}
+
+/// Crop, downscale, denoise and equalize the image as configured and get
+/// its RGBA pixels, replaced by their superpixel's mean color when
+/// `superpixels` is set.
+/// The image is only copied when it needs to be changed or isn't already
+/// stored as 8-bit RGBA.
+pub(crate) fn prepare_image<'a>(
+    image: &'a DynamicImage,
+    options: &ExtractionOptions,
+) -> Result<Cow<'a, RgbaImage>, Error> {
+    if image.width() == 0 || image.height() == 0 {
+        return Err(Error::EmptyImage);
+    }
+
+    let image = match options.crop {
+        Some(region) => Cow::Owned(crop_image(image, region)?),
+        None => Cow::Borrowed(image),
+    };
+    let image = match options.max_dimension {
+        Some(max_dimension)
+            if max_dimension > 0
+                && (image.width() > max_dimension || image.height() > max_dimension) =>
+        {
+            Cow::Owned(image.thumbnail(max_dimension, max_dimension))
+        }
+        _ => image,
+    };
+
+    let image = match image {
+        Cow::Borrowed(image) => match image.as_rgba8() {
+            Some(rgba) => Cow::Borrowed(rgba),
+            None => Cow::Owned(image.to_rgba8()),
+        },
+        Cow::Owned(image) => Cow::Owned(image.into_rgba8()),
+    };
+    let image = match options.denoise {
+        Some(denoise) => Cow::Owned(denoise_image(&image, denoise)),
+        None => image,
+    };
+    let image = if options.equalize {
+        Cow::Owned(equalize_image(&image))
+    } else {
+        image
+    };
+
+    Ok(match options.superpixels {
+        Some(count) => Cow::Owned(superpixel_image(&image, count)),
+        None => image,
+    })
+}
+
+/// Smooth an image with a Gaussian blur or a median filter
+fn denoise_image(image: &RgbaImage, denoise: Denoise) -> RgbaImage {
+    match denoise {
+        Denoise::Gaussian(sigma) if sigma > 0.0 => imageops::blur(image, sigma),
+        Denoise::Median(radius) if radius > 0 => median_filter(image, radius),
+        _ => image.clone(),
+    }
+}
+
+/// Replace the color channels of every pixel by their medians within
+/// `radius` pixels, shrinking the window at the edges. Alpha is kept
+fn median_filter(image: &RgbaImage, radius: u32) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let mut window = [Vec::new(), Vec::new(), Vec::new()];
+
+    RgbaImage::from_fn(width, height, |x, y| {
+        for channel in &mut window {
+            channel.clear();
+        }
+
+        for window_y in y.saturating_sub(radius)..=(y + radius).min(height - 1) {
+            for window_x in x.saturating_sub(radius)..=(x + radius).min(width - 1) {
+                let pixel = image.get_pixel(window_x, window_y);
+
+                for (channel, value) in window.iter_mut().zip(pixel.0) {
+                    channel.push(value);
+                }
+            }
+        }
+
+        let median = |channel: &mut Vec<u8>| {
+            let middle = channel.len() / 2;
+
+            *channel.select_nth_unstable(middle).1
+        };
+        let [red, green, blue] = &mut window;
+
+        image::Rgba([
+            median(red),
+            median(green),
+            median(blue),
+            image.get_pixel(x, y)[3],
+        ])
+    })
+}
+
+/// Spread the luma of an image's opaque pixels over the whole range with
+/// histogram equalization, shifting every channel of a pixel by as much as
+/// its luma moved so its hue is kept. Alpha is kept
+fn equalize_image(image: &RgbaImage) -> RgbaImage {
+    let luma = |pixel: &image::Rgba<u8>| {
+        let [red, green, blue, _] = pixel.0;
+
+        ((2126 * red as u32 + 7152 * green as u32 + 722 * blue as u32 + 5000) / 10000) as usize
+    };
+    let mut histogram = [0u64; 256];
+
+    for pixel in image.pixels().filter(|pixel| pixel[3] > 0) {
+        histogram[luma(pixel)] += 1;
+    }
+
+    let mut cumulative = [0u64; 256];
+    let mut total = 0;
+
+    for (sum, count) in cumulative.iter_mut().zip(histogram) {
+        total += count;
+        *sum = total;
+    }
+
+    let darkest = cumulative.iter().copied().find(|sum| *sum > 0).unwrap_or(0);
+
+    // A single luma has nothing to spread
+    if total == darkest {
+        return image.clone();
+    }
+
+    let mut equalized = image.clone();
+
+    for pixel in equalized.pixels_mut().filter(|pixel| pixel[3] > 0) {
+        let old = luma(pixel);
+        let new = ((cumulative[old] - darkest) * 255 + (total - darkest) / 2) / (total - darkest);
+        let shift = new as i32 - old as i32;
+
+        for channel in &mut pixel.0[..3] {
+            *channel = (*channel as i32 + shift).clamp(0, 255) as u8;
+        }
+    }
+
+    equalized
+}
+
+/// Get how likely every pixel of an image is part of the photo's subject,
+/// between 0.0 and 1.0: how close it is to the center, or how far its
+/// color is from the image's average color in Lab, relative to the pixel
+/// that is furthest from it
+fn subject_scores(image: &RgbaImage, bias: SubjectBias) -> Vec<f32> {
+    let (width, height) = image.dimensions();
+
+    match bias {
+        SubjectBias::Center(_) => (0..height)
+            .flat_map(|y| (0..width).map(move |x| 1.0 - center_distance(x, y, width, height)))
+            .collect(),
+        SubjectBias::Contrast(_) => {
+            let lab: Vec<Lab> = image
+                .pixels()
+                .map(|pixel| {
+                    let [red, green, blue, _] = pixel.0;
+
+                    Lab::from_color(Srgb::new(red, green, blue).into_format::<f32>())
+                })
+                .collect();
+            let count = lab.len().max(1) as f32;
+            let (l, a, b) = lab.iter().fold((0.0, 0.0, 0.0), |(l, a, b), color| {
+                (
+                    l + color.l / count,
+                    a + color.a / count,
+                    b + color.b / count,
+                )
+            });
+            let distances: Vec<f32> = lab
+                .iter()
+                .map(|color| {
+                    ((color.l - l).powi(2) + (color.a - a).powi(2) + (color.b - b).powi(2)).sqrt()
+                })
+                .collect();
+            let furthest = distances.iter().copied().fold(0.0, f32::max);
+
+            if furthest <= f32::EPSILON {
+                return vec![1.0; distances.len()];
+            }
+
+            distances
+                .into_iter()
+                .map(|distance| distance / furthest)
+                .collect()
+        }
+    }
+}
+
+/// Whether to keep a pixel that is left out with a likelihood of
+/// `drop_chance`, from 0.0 to 1.0
+///
+/// This is a cheap deterministic dither on the pixel's position rather
+/// than random sampling, so the same image always keeps the same pixels and
+/// results are reproducible.
+pub(crate) fn dither_keep(x: u32, y: u32, drop_chance: f32) -> bool {
+    let threshold = (x.wrapping_mul(73_856_093) ^ y.wrapping_mul(19_349_663)) % 1000;
+
+    threshold as f32 / 1000.0 >= drop_chance
+}
+
+/// Collect the RGBA pixels of the images for color_thief, leaving pixels
+/// out with a likelihood that grows the less likely they are part of the
+/// subject, the same way `center_weight` leaves out pixels near the edges
+pub(crate) fn subject_pixels(images: &[Cow<RgbaImage>], bias: SubjectBias) -> Vec<u8> {
+    let strength = match bias {
+        SubjectBias::Center(strength) | SubjectBias::Contrast(strength) => strength.clamp(0.0, 1.0),
+    };
+    let mut pixels = Vec::new();
+
+    for image in images {
+        let width = image.width();
+        let scores = subject_scores(image, bias);
+
+        for (index, (pixel, score)) in image.as_raw().chunks_exact(4).zip(scores).enumerate() {
+            let x = index as u32 % width;
+            let y = index as u32 / width;
+
+            if dither_keep(x, y, strength * (1.0 - score)) {
+                pixels.extend_from_slice(pixel);
+            }
+        }
+    }
+
+    pixels
+}
+
+/// Whether a color looks like human skin, using the chrominance bounds of
+/// Chai and Ngan in YCbCr, which hold across skin colors since they mostly
+/// differ in luma
+pub(crate) fn is_skin_tone(color: Srgb<u8>) -> bool {
+    let (red, green, blue) = (color.red as f32, color.green as f32, color.blue as f32);
+    let luma = 0.299 * red + 0.587 * green + 0.114 * blue;
+    let cb = 128.0 + (blue - luma) * 0.564;
+    let cr = 128.0 + (red - luma) * 0.713;
+
+    SKIN_TONE_LUMA.contains(&luma) && SKIN_TONE_CB.contains(&cb) && SKIN_TONE_CR.contains(&cr)
+}
+
+/// Leave skin-tone pixels out of RGBA pixels, keeping about `weight` of
+/// them, from 0.0 to 1.0
+pub(crate) fn without_skin_tones(pixels: &[u8], weight: f32) -> Vec<u8> {
+    let weight = weight.clamp(0.0, 1.0);
+
+    pixels
+        .chunks_exact(4)
+        .enumerate()
+        .filter(|(index, pixel)| {
+            !is_skin_tone(Srgb::new(pixel[0], pixel[1], pixel[2]))
+                || dither_keep(*index as u32, 0, 1.0 - weight)
+        })
+        .flat_map(|(_, pixel)| pixel.iter().copied())
+        .collect()
+}
+
+/// Traverse the pixels of all images once, finding the pixel closest to each
+/// pure color and collecting the pixels color_thief should look at
+///
+/// Each pixel's 8×8×8 cell is looked up in a table of the pure color nearest
+/// to the cell and how close any of its colors can get to a pure color. Once
+/// every other pure color has a pixel closer than that, only the nearest one
+/// is compared, so most pixels cost a single comparison. The result is the
+/// same as comparing every pixel against every pure color, with ties kept by
+/// the earlier pixel.
+///
+/// With a center weight, distances of pixels towards the edges of the image
+/// are scaled up by up to `1 + center_weight` when comparing. Pixels are
+/// also left out of the color_thief pixels with a likelihood that grows
+/// towards the edges, so the center makes up more of the sampled colors.
+///
+/// With the `rayon` feature, chunks of rows are scanned in parallel and the
+/// partial results are merged in order, so the result is the same as a
+/// sequential scan.
+pub(crate) fn scan_pixels<'a>(
+    images: &'a [Cow<RgbaImage>],
+    options: &ExtractionOptions,
+) -> PixelScan<'a> {
+    // Copying pixels is only needed when color_thief shouldn't see all
+    // pixels of a single image as they are
+    let borrowed_pixels = match images {
+        [image] if options.center_weight <= 0.0 && options.sample_every <= 1 => {
+            Some(image.as_raw().as_slice())
+        }
+        _ => None,
+    };
+    let collect_pixels = cfg!(feature = "color-thief") && borrowed_pixels.is_none();
+    let chunks: Vec<(&RgbaImage, Range<u64>)> = images
+        .iter()
+        .flat_map(|image| {
+            let width = image.width() as u64;
+            let height = image.height() as u64;
+
+            (0..height)
+                .step_by(ROWS_PER_CHUNK as usize)
+                .map(move |row| {
+                    let end_row = (row + ROWS_PER_CHUNK).min(height);
+
+                    (image.as_ref(), row * width..end_row * width)
+                })
+        })
+        .collect();
+
+    #[cfg(feature = "rayon")]
+    let scan = {
+        use rayon::prelude::*;
+
+        chunks
+            .into_par_iter()
+            .fold(PartialScan::new, |scan, (image, range)| {
+                scan_range(scan, image, range, options, collect_pixels)
+            })
+            .reduce(PartialScan::new, PartialScan::merge)
+    };
+    #[cfg(not(feature = "rayon"))]
+    let scan = chunks
+        .into_iter()
+        .fold(PartialScan::new(), |scan, (image, range)| {
+            scan_range(scan, image, range, options, collect_pixels)
+        });
+
+    PixelScan {
+        closest_palette: scan.closest_palette,
+        color_thief_pixels: match borrowed_pixels {
+            Some(pixels) => Cow::Borrowed(pixels),
+            None => Cow::Owned(scan.color_thief_pixels),
+        },
+    }
+}
+
+/// Map a decoding error of the image at `path` to the matching Error
+fn decode_error(path: &Path, source: ImageError) -> Error {
+    match source {
+        ImageError::Limits(_) => Error::LimitsExceeded {
+            path: path.to_path_buf(),
+            source,
+        },
+        ImageError::Decoding(_) => Error::CorruptImage {
+            path: path.to_path_buf(),
+            source,
+        },
+        ImageError::Unsupported(_) => Error::UnsupportedFormat {
+            path: path.to_path_buf(),
+            source,
+        },
+        ImageError::IoError(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+            Error::CorruptImage {
+                path: path.to_path_buf(),
+                source,
+            }
+        }
+        source => Error::ImageDecode {
+            path: path.to_path_buf(),
+            source,
+        },
+    }
+}
+
+/// The image crate's limits for the configured decoding limits. Limits
+/// that aren't set keep the image crate's defaults, e.g. its 512MiB
+/// allocation cap
+fn image_limits(options: &ExtractionOptions) -> Limits {
+    let mut image_limits = Limits::default();
+
+    if let Some(limits) = &options.limits {
+        if limits.max_width.is_some() {
+            image_limits.max_image_width = limits.max_width;
+        }
+        if limits.max_height.is_some() {
+            image_limits.max_image_height = limits.max_height;
+        }
+        if limits.max_alloc.is_some() {
+            image_limits.max_alloc = limits.max_alloc;
+        }
+    }
+
+    image_limits
+}
+
+/// The longest side formats that can decode or render at a reduced size
+/// are made at, which is `max_dimension` unless a crop needs the full-size
+/// pixels. `prepare_image` still thumbnails whatever is larger
+#[cfg(any(feature = "jpeg", feature = "pdf", feature = "svg", feature = "video"))]
+fn decode_size(options: &ExtractionOptions) -> Option<u32> {
+    options
+        .max_dimension
+        .filter(|size| *size > 0 && options.crop.is_none())
+}
+
+/// Decode an image file into the images colors are extracted from,
+/// enforcing the configured decoding limits
+///
+/// For animated GIF, APNG and WebP files, `options.frames` decides which
+/// frames are used. Other files are decoded as a single image. With the
+/// `zune-jpeg` feature, JPEGs are decoded by zune-jpeg, with the `jxl`
+/// feature JPEG XL files by jxl-oxide, and with the `heif` feature HEIC
+/// files by libheif. With the `svg` feature, SVG files are rasterized at
+/// `options.svg_size`, with the `pdf` feature, `options.pdf_page` of PDF
+/// files is rendered, with the `raw` feature, camera RAW files are
+/// developed, and with the `video` feature, `options.video_frame` of video
+/// files is decoded.
+pub(crate) fn load_image_frames(
+    path: &Path,
+    options: &ExtractionOptions,
+) -> Result<Vec<DynamicImage>, Error> {
+    #[cfg(feature = "video")]
+    if is_video(path) {
+        return decode_video_frame(
+            path,
+            options.video_frame,
+            decode_size(options),
+            &image_limits(options),
+        )
+        .map(|frame| vec![DynamicImage::ImageRgba8(frame)])
+        .map_err(|source| decode_error(path, source));
+    }
+
+    #[cfg(feature = "raw")]
+    if is_raw(path) {
+        return decode_raw(path, &image_limits(options))
+            .map(|image| vec![DynamicImage::ImageRgba8(image)])
+            .map_err(|source| decode_error(path, source));
+    }
+
+    let file = File::open(path).map_err(|err| Error::ImageDecode {
+        path: path.to_path_buf(),
+        source: ImageError::IoError(err),
+    })?;
+
+    decode_image_frames(
+        BufReader::new(file),
+        path,
+        ImageFormat::from_path(path).ok(),
+        options,
+    )
+}
+
+/// Decode an image from a reader, like `load_image_frames`
+///
+/// # Arguments
+/// * `reader` - The encoded image
+/// * `path` - Where the image came from, used in errors
+/// * `format_hint` - The format to use when it can't be guessed from the
+///   contents, e.g. from the file extension
+/// * `options` - The ExtractionOptions with the limits and frame selection
+pub(crate) fn decode_image_frames<R: BufRead + Seek>(
+    mut reader: R,
+    path: &Path,
+    format_hint: Option<ImageFormat>,
+    options: &ExtractionOptions,
+) -> Result<Vec<DynamicImage>, Error> {
+    let io_error = |err| Error::ImageDecode {
+        path: path.to_path_buf(),
+        source: ImageError::IoError(err),
+    };
+    let mut guess_reader = ImageReader::new(&mut reader);
+
+    if let Some(format) = format_hint {
+        guess_reader.set_format(format);
+    }
+
+    let format = guess_reader
+        .with_guessed_format()
+        .map_err(io_error)?
+        .format();
+    let image_limits = image_limits(options);
+
+    // The image crate doesn't know HEIF, JPEG XL, SVG or PDF, so look for
+    // them in what it can't guess. Their decoders need the whole file, but
+    // its start tells them apart, so other files aren't read into memory
+    #[cfg(any(feature = "heif", feature = "jxl", feature = "pdf", feature = "svg"))]
+    if format.is_none() {
+        let mut data = Vec::new();
+
+        reader
+            .by_ref()
+            .take(SNIFF_LENGTH as u64)
+            .read_to_end(&mut data)
+            .map_err(io_error)?;
+
+        let is_known = [
+            #[cfg(feature = "heif")]
+            is_heif(&data),
+            #[cfg(feature = "jxl")]
+            is_jxl(&data),
+            #[cfg(feature = "pdf")]
+            is_pdf(&data),
+            #[cfg(feature = "svg")]
+            is_svg(&data),
+        ]
+        .contains(&true);
+
+        if is_known {
+            reader.read_to_end(&mut data).map_err(io_error)?;
+        }
+
+        #[cfg(feature = "heif")]
+        if is_heif(&data) {
+            return decode_heif(&data, &image_limits)
+                .map(|image| vec![DynamicImage::ImageRgba8(image)])
+                .map_err(|source| decode_error(path, source));
+        }
+
+        #[cfg(feature = "jxl")]
+        if is_jxl(&data) {
+            return decode_jxl(&data, &image_limits)
+                .map(|image| vec![DynamicImage::ImageRgba8(image)])
+                .map_err(|source| decode_error(path, source));
+        }
+
+        #[cfg(feature = "pdf")]
+        if is_pdf(&data) {
+            return rasterize_pdf_page(
+                &data,
+                options.pdf_page,
+                decode_size(options),
+                &image_limits,
+            )
+            .map(|image| vec![DynamicImage::ImageRgba8(image)])
+            .map_err(|source| decode_error(path, source));
+        }
+
+        #[cfg(feature = "svg")]
+        if is_svg(&data) {
+            return rasterize_svg(&data, options.svg_size, decode_size(options), &image_limits)
+                .map(|image| vec![DynamicImage::ImageRgba8(image)])
+                .map_err(|source| decode_error(path, source));
+        }
+
+        reader.rewind().map_err(io_error)?;
+    }
+
+    #[cfg(any(feature = "gif", feature = "png", feature = "webp"))]
+    if options.frames != FrameSelection::First {
+        if let Some(format) = format {
+            let frames = decode_frames(&mut reader, format, image_limits.clone(), options.frames)
+                .map_err(|source| decode_error(path, source))?;
+
+            if let Some(frames) = frames {
+                if frames.is_empty() {
+                    return Err(Error::EmptyImage);
+                }
+
+                return Ok(frames.into_iter().map(DynamicImage::ImageRgba8).collect());
+            }
+
+            reader.rewind().map_err(io_error)?;
+        }
+    }
+
+    // The image crate's avif feature only encodes, so say what's missing
+    // rather than that AVIF isn't supported at all
+    #[cfg(not(feature = "avif-native"))]
+    if format == Some(ImageFormat::Avif) {
+        return Err(decode_error(
+            path,
+            ImageError::Unsupported(UnsupportedError::from_format_and_kind(
+                ImageFormat::Avif.into(),
+                UnsupportedErrorKind::GenericFeature(
+                    "decoding AVIF files needs the avif-native feature".to_string(),
+                ),
+            )),
+        ));
+    }
+
+    // JPEGs that are downscaled anyway are decoded at a reduced DCT scale,
+    // falling back to a full decode for those jpeg-decoder can't scale
+    #[cfg(feature = "jpeg")]
+    if let (Some(ImageFormat::Jpeg), Some(size)) = (format, decode_size(options)) {
+        if let Ok(image) = decode_scaled_jpeg(&mut reader, size, &image_limits) {
+            return Ok(vec![DynamicImage::ImageRgba8(image)]);
+        }
+
+        reader.rewind().map_err(io_error)?;
+    }
+
+    #[cfg(feature = "zune-jpeg")]
+    if format == Some(ImageFormat::Jpeg) {
+        return decode_jpeg(reader, &image_limits)
+            .map(|image| vec![DynamicImage::ImageRgba8(image)])
+            .map_err(|source| decode_error(path, source));
+    }
+
+    let mut image_reader = ImageReader::new(reader);
+
+    if let Some(format) = format {
+        image_reader.set_format(format);
+    }
+
+    image_reader.limits(image_limits);
+    image_reader
+        .decode()
+        .map(|image| vec![image])
+        .map_err(|source| decode_error(path, source))
+}
+
+/// Decode the frames of an animated image that `selection` needs, or `None`
+/// if the image isn't animated
+///
+/// Selections spread over the animation decode it twice, first to count
+/// its frames and then to keep the ones they need, so only those frames
+/// are held in memory rather than the whole animation.
+#[cfg(any(feature = "gif", feature = "png", feature = "webp"))]
+fn decode_frames<R: BufRead + Seek>(
+    mut file: R,
+    format: ImageFormat,
+    limits: Limits,
+    selection: FrameSelection,
+) -> ImageResult<Option<Vec<RgbaImage>>> {
+    // Only decode as far as needed when the frame count doesn't matter
+    let count = match selection {
+        FrameSelection::First | FrameSelection::Index(_) => 0,
+        _ => {
+            let Some(frames) = animation_frames(&mut file, format, limits.clone())? else {
+                return Ok(None);
+            };
+            let mut count = 0;
+
+            for frame in frames {
+                frame?;
+                count += 1;
+            }
+
+            file.rewind()?;
+            count
+        }
+    };
+    let Some(frames) = animation_frames(&mut file, format, limits)? else {
+        return Ok(None);
+    };
+
+    select_frames(
+        frames.map(|frame| frame.map(Frame::into_buffer)),
+        count,
+        selection,
+    )
+    .map(Some)
+}
+
+/// The frames of an animated image, or `None` if the image isn't animated
+#[cfg(any(feature = "gif", feature = "png", feature = "webp"))]
+fn animation_frames<'a, R: BufRead + Seek + 'a>(
+    file: R,
+    format: ImageFormat,
+    limits: Limits,
+) -> ImageResult<Option<Frames<'a>>> {
+    let frames = match format {
+        #[cfg(feature = "gif")]
+        ImageFormat::Gif => {
+            let mut decoder = GifDecoder::new(file)?;
+
+            decoder.set_limits(limits)?;
+            decoder.into_frames()
+        }
+        #[cfg(feature = "png")]
+        ImageFormat::Png => {
+            let mut decoder = PngDecoder::new(file)?;
+
+            if !decoder.is_apng()? {
+                return Ok(None);
+            }
+
+            decoder.set_limits(limits)?;
+            decoder.apng()?.into_frames()
+        }
+        #[cfg(feature = "webp")]
+        ImageFormat::WebP => {
+            let mut decoder = WebPDecoder::new(file)?;
+
+            if !decoder.has_animation() {
+                return Ok(None);
+            }
+
+            decoder.set_limits(limits)?;
+            decoder.into_frames()
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(frames))
+}
+
+/// The indices of up to `samples` of `count` frames, spread evenly over
+/// them starting at the first
+#[cfg(any(feature = "gif", feature = "png", feature = "webp"))]
+fn sample_frames(count: usize, samples: usize) -> impl Iterator<Item = usize> {
+    let samples = samples.clamp(1, count.max(1));
+
+    (0..samples).map(move |sample| sample * count / samples)
+}
+
+/// Pick, sample or average the frames according to the selection, keeping
+/// only the frames it needs, and for averages only their running sums
+///
+/// # Arguments
+/// * `frames` - The frames of the animation, decoded as they're needed
+/// * `count` - How many frames the animation has, for the selections that
+///   spread over it
+/// * `selection` - Which frames to use
+#[cfg(any(feature = "gif", feature = "png", feature = "webp"))]
+fn select_frames(
+    frames: impl Iterator<Item = ImageResult<RgbaImage>>,
+    count: usize,
+    selection: FrameSelection,
+) -> ImageResult<Vec<RgbaImage>> {
+    let indices: Vec<usize> = match selection {
+        FrameSelection::First => vec![0],
+        FrameSelection::Index(index) => {
+            let mut selected = Vec::new();
+
+            for frame in frames.take(index.saturating_add(1)) {
+                selected = vec![frame?];
+            }
+
+            return Ok(selected);
+        }
+        FrameSelection::Middle => vec![count / 2],
+        FrameSelection::Sample(samples) | FrameSelection::Average(samples) => {
+            sample_frames(count, samples).collect()
+        }
+    };
+    let needed = indices.last().map_or(0, |last| last + 1);
+    let mut selected = Vec::new();
+    let mut sums: Vec<u32> = Vec::new();
+    let mut dimensions = None;
+    let mut averaged = 0;
+
+    for (index, frame) in frames.enumerate().take(needed) {
+        let frame = frame?;
+
+        if !indices.contains(&index) {
+            continue;
+        }
+
+        if let FrameSelection::Average(_) = selection {
+            let (width, height) = *dimensions.get_or_insert(frame.dimensions());
+
+            if frame.dimensions() != (width, height) {
+                continue;
+            }
+
+            if sums.is_empty() {
+                sums = vec![0; frame.as_raw().len()];
+            }
+
+            for (sum, channel) in sums.iter_mut().zip(frame.as_raw()) {
+                *sum += *channel as u32;
+            }
+
+            averaged += 1;
+        } else {
+            selected.push(frame);
+        }
+    }
+
+    if let Some((width, height)) = dimensions {
+        let pixels = sums
+            .into_iter()
+            .map(|sum| ((sum + averaged / 2) / averaged) as u8)
+            .collect();
+
+        selected.extend(RgbaImage::from_raw(width, height, pixels));
+    }
+
+    Ok(selected)
+}
+
+/// Format an error followed by each of its sources, e.g. for reporting it
+/// outside of Rust
+#[cfg(any(
+    all(feature = "daemon", unix),
+    feature = "http",
+    feature = "pyo3",
+    feature = "tui"
+))]
+pub(crate) fn error_chain(err: &Error) -> String {
+    let mut message = err.to_string();
+    let mut source = std::error::Error::source(err);
+
+    while let Some(err) = source {
+        message.push_str(&format!(": {}", err));
+        source = err.source();
+    }
+
+    message
+}
+
+/// Create a scheme color from a hex string
+pub(crate) fn scheme_color(hex: String) -> Result<SchemeColor, Error> {
+    SchemeColor::new(hex.clone()).map_err(|_| Error::InvalidHex { value: hex })
+}
+
+/// Create a scheme color from an sRGB color
+pub(crate) fn scheme_color_from_srgb(rgb: Srgb<u8>) -> SchemeColor {
+    let hex = format!("{:02X}{:02X}{:02X}", rgb.red, rgb.green, rgb.blue);
+
+    SchemeColor::new(hex).expect("six hex digits are always a valid color")
+}
+
+pub(crate) fn interpolate_color(start: Srgb<u8>, end: Srgb<u8>, t: f32) -> Srgb<u8> {
+    Srgb::new(
+        (start.red as f32 + t * (end.red as f32 - start.red as f32)).round() as u8,
+        (start.green as f32 + t * (end.green as f32 - start.green as f32)).round() as u8,
+        (start.blue as f32 + t * (end.blue as f32 - start.blue as f32)).round() as u8,
+    )
+}
+
+pub(crate) fn generate_gradient(
+    darkest: Srgb<u8>,
+    lightest: Srgb<u8>,
+    steps: usize,
+) -> Vec<Srgb<u8>> {
+    (0..steps)
+        .map(|i| {
+            let t = if steps > 1 {
+                i as f32 / (steps - 1) as f32
+            } else {
+                0.0
+            };
+
+            interpolate_color(darkest, lightest, t)
+        })
+        .collect()
+}
+
+/// Get the WCAG contrast ratio between two colors, from 1.0 to 21.0
+pub(crate) fn contrast_ratio(a: Rgb, b: Rgb) -> f32 {
+    let (_, a_luma) = get_sat_luma(a);
+    let (_, b_luma) = get_sat_luma(b);
+
+    (a_luma.max(b_luma) + 0.05) / (a_luma.min(b_luma) + 0.05)
+}
+
+/// Build the gray ramp from the background towards the foreground, placing
+/// each following gray at the first point reaching its target contrast
+/// ratio against the background. Targets beyond the foreground's contrast
+/// stop at the foreground
+pub(crate) fn generate_contrast_ramp(
+    background: Srgb<u8>,
+    foreground: Srgb<u8>,
+    targets: &[f32],
+) -> Vec<Srgb<u8>> {
+    let background: Rgb = background.into_format();
+    let foreground: Rgb = foreground.into_format();
+    let at = |t: f32| {
+        Rgb::new(
+            background.red + t * (foreground.red - background.red),
+            background.green + t * (foreground.green - background.green),
+            background.blue + t * (foreground.blue - background.blue),
+        )
+    };
+    let mut ramp = vec![background.into_format()];
+    let mut start = 0.0;
+
+    for target in targets {
+        let mut end = 1.0;
+
+        if contrast_ratio(at(end), background) > *target {
+            // Earlier targets were reached at `start`, so the first point
+            // reaching this one lies after it
+            let mut low = start;
+
+            for _ in 0..24 {
+                let middle = (low + end) / 2.0;
+
+                if contrast_ratio(at(middle), background) >= *target {
+                    end = middle;
+                } else {
+                    low = middle;
+                }
+            }
+        }
+
+        start = end;
+        ramp.push(at(end).into_format());
+    }
+
+    ramp
+}
+
+/// Rebuild base00 to base07 from a background the user picked, towards
+/// the foreground moved to at least a 7:1 contrast ratio against it
+///
+/// # Arguments
+/// * `palette` - A mutable reference to the scheme palette
+/// * `background` - The color base00 is locked to
+/// * `foreground` - The palette's base07 before the background is locked
+/// * `contrast_ramp` - The contrast ratios for base01 to base07, or `None`
+///   to space the grays evenly
+pub(crate) fn lock_background(
+    palette: &mut HashMap<String, SchemeColor>,
+    background: Srgb<u8>,
+    foreground: Srgb<u8>,
+    contrast_ramp: Option<&[f32; 7]>,
+) {
+    let foreground = with_min_contrast(foreground, background, LOCKED_MIN_CONTRAST);
+    let ramp = match contrast_ramp {
+        Some(targets) => generate_contrast_ramp(background, foreground, targets),
+        None => generate_gradient(background, foreground, 8),
+    };
+
+    for (index, rgb) in ramp.iter().enumerate() {
+        palette.insert(format!("base0{}", index), scheme_color_from_srgb(*rgb));
+    }
+}
+
+/// Move a color's lightness away from the background's until it reaches
+/// the contrast ratio, or as close as black or white get
+pub(crate) fn with_min_contrast(
+    color: Srgb<u8>,
+    background: Srgb<u8>,
+    min_contrast: f32,
+) -> Srgb<u8> {
+    let background_rgb: Rgb = background.into_format();
+    let mut lch = Lch::from_color(color.into_format::<f32>());
+    let direction = if lch.l >= Lch::from_color(background_rgb).l {
+        1.0
+    } else {
+        -1.0
+    };
+    let mut rgb = color;
+
+    while contrast_ratio(rgb.into_format(), background_rgb) < min_contrast
+        && (0.0..=100.0).contains(&lch.l)
+    {
+        lch.l += direction;
+
+        rgb = to_srgb_within_gamut(lch);
+    }
+
+    rgb
+}
+
+/// Get the dominant colors of the RGBA pixels with color_thief, or `None`
+/// when it fails or finds too few colors
+#[cfg(feature = "color-thief")]
+pub(crate) fn get_color_thief_palette(pixels: &[u8]) -> Option<Vec<Srgb<u8>>> {
+    color_thief::get_palette(pixels, color_thief::ColorFormat::Rgba, 1, 15)
+        .ok()
+        .filter(|palette| palette.len() >= MIN_COLOR_THIEF_COLORS)
+        .map(|palette| palette.iter().map(|c| Srgb::new(c.r, c.g, c.b)).collect())
+}
+
+/// Without the `color-thief` feature there are no dominant colors, so the
+/// fallback palette is always used
+#[cfg(not(feature = "color-thief"))]
+pub(crate) fn get_color_thief_palette(_pixels: &[u8]) -> Option<Vec<Srgb<u8>>> {
+    None
+}
+
+/// Build a palette for when color_thief can't, e.g. for tiny or uniform
+/// images, from the pixels closest to each pure color
+///
+/// A dark and a light anchor with the average hue of those pixels are put
+/// first, so the light and dark color passes always have something to find.
+pub(crate) fn fallback_palette(closest_palette: &[Color]) -> Vec<Srgb<u8>> {
+    let count = closest_palette.len().max(1) as f32;
+    let (red, green, blue) =
+        closest_palette
+            .iter()
+            .fold((0.0, 0.0, 0.0), |(red, green, blue), color| {
+                let value = color.value.into_format::<f32>();
+
+                (red + value.red, green + value.green, blue + value.blue)
+            });
+    let average: Hsl = Srgb::new(red / count, green / count, blue / count).into_color();
+    let saturation = average.saturation.min(0.2);
+    let anchor = |lightness: f32| -> Srgb<u8> {
+        let rgb: Srgb = Hsl::new(average.hue, saturation, lightness).into_color();
+
+        rgb.into_format()
+    };
+    let mut palette = vec![anchor(0.1), anchor(0.9)];
+
+    for color in closest_palette {
+        if !palette.contains(&color.value) {
+            palette.push(color.value);
+        }
+    }
+
+    palette
+}
+
+/// Synthesize accent slots that no color in the image mapped to, so the
+/// scheme always has every slot of its system
+///
+/// A missing accent takes the saturation and lightness of the present
+/// accent closest in hue, rotated to the slot's hue, with brown darkened to
+/// 70% of that lightness. A missing base0F is synthesized at `base0f`, the
+/// pure color of the `Base0FPolicy`: magenta for `Magenta`, and brown for
+/// `Brown` and for `SecondDominantHue`, which already set base0F unless the
+/// image has no second hue. Without any accents, the foreground tone base05
+/// is used with at least 0.5 saturation. Missing Base24 bright accents are
+/// desaturated from their base accent, the same as `Color::to_saturated(0.7)`.
+///
+/// # Arguments
+/// * `palette` - A mutable reference to the scheme palette to fill
+/// * `system` - A reference to the SchemeSystem deciding the slots
+/// * `base0f` - The pure color base0F represents
+pub(crate) fn fill_missing_accents(
+    palette: &mut HashMap<String, SchemeColor>,
+    system: &SchemeSystem,
+    base0f: PureColor,
+) -> Result<(), Error> {
+    let hues = accent_hues(base0f);
+    let to_hsl = |color: &SchemeColor| -> Hsl {
+        let (red, green, blue) = color.rgb;
+
+        Hsl::from_color(Srgb::new(red, green, blue).into_format::<f32>())
+    };
+    let to_scheme_color = |hsl: Hsl| {
+        let rgb: Srgb = hsl.into_color();
+        let rgb: Srgb<u8> = rgb.into_format();
+
+        scheme_color_from_srgb(rgb)
+    };
+    let hue_distance = |a: f32, b: f32| {
+        let distance = (a - b).rem_euclid(360.0);
+
+        distance.min(360.0 - distance)
+    };
+    let present: Vec<(f32, Hsl)> = hues
+        .iter()
+        .enumerate()
+        .filter_map(|(index, hue)| {
+            palette
+                .get(&format!("base{:02X}", 0x08 + index))
+                .map(|color| (*hue, to_hsl(color)))
+        })
+        .collect();
+
+    for (index, hue) in hues.iter().enumerate() {
+        let slot = format!("base{:02X}", 0x08 + index);
+
+        if palette.contains_key(&slot) {
+            continue;
+        }
+
+        let neighbour = present.iter().min_by(|(a, _), (b, _)| {
+            hue_distance(*a, *hue)
+                .partial_cmp(&hue_distance(*b, *hue))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let (saturation, lightness) = match neighbour {
+            Some((_, hsl)) => (hsl.saturation, hsl.lightness),
+            None => {
+                let foreground = palette
+                    .get("base05")
+                    .map(to_hsl)
+                    .unwrap_or_else(|| Hsl::new(0.0, 0.0, 0.5));
+
+                (foreground.saturation.max(0.5), foreground.lightness)
+            }
+        };
+        let lightness = if index == 7 && base0f == PureColor::Brown {
+            lightness * 0.7
+        } else {
+            lightness
+        };
+
+        palette.insert(slot, to_scheme_color(Hsl::new(*hue, saturation, lightness)));
+    }
+
+    if let SchemeSystem::Base24 = system {
+        for index in 0..hues.len() {
+            let slot = format!("base{:02X}", 0x10 + index);
+
+            if palette.contains_key(&slot) {
+                continue;
+            }
+
+            if let Some(accent) = palette.get(&format!("base{:02X}", 0x08 + index)) {
+                let hsl = to_hsl(accent);
+                let color =
+                    to_scheme_color(Hsl::new(hsl.hue, hsl.saturation * 0.7 * 0.7, hsl.lightness));
+
+                palette.insert(slot, color);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Rotate the accents whose hue is outside their slot's range to the
+/// closest edge of the range, keeping their saturation and lightness
+///
+/// # Arguments
+/// * `palette` - A mutable reference to the scheme palette to adjust
+/// * `system` - A reference to the SchemeSystem deciding the slots, with
+///   Base24's bright accents using the range of their base accent
+/// * `ranges` - The hue range of each of base08 to base0F, if any
+pub(crate) fn constrain_accent_hues(
+    palette: &mut HashMap<String, SchemeColor>,
+    system: &SchemeSystem,
+    ranges: &[Option<HueRange>; 8],
+) -> Result<(), Error> {
+    let offsets: &[usize] = match system {
+        SchemeSystem::Base24 => &[0x08, 0x10],
+        _ => &[0x08],
+    };
+
+    for (index, range) in ranges.iter().enumerate() {
+        let Some(range) = range else {
+            continue;
+        };
+
+        for offset in offsets {
+            let slot = format!("base{:02X}", offset + index);
+            let Some(accent) = palette.get(&slot) else {
+                continue;
+            };
+            let (red, green, blue) = accent.rgb;
+            let hsl = Hsl::from_color(Srgb::new(red, green, blue).into_format::<f32>());
+            let hue = hsl.hue.into_positive_degrees();
+
+            if range.contains(hue) {
+                continue;
+            }
+
+            let rgb: Srgb = Hsl::new(range.clamp(hue), hsl.saturation, hsl.lightness).into_color();
+            let rgb: Srgb<u8> = rgb.into_format();
+
+            palette.insert(slot, scheme_color_from_srgb(rgb));
+        }
+    }
+
+    Ok(())
+}
+
+/// Raise the LCh chroma of the accents below `min_chroma` to it, keeping
+/// their lightness and hue, so accents from muted images read as colors
+/// rather than tinted grays. Accents too gray to have a hue get the hue of
+/// their slot's pure color. Colors outside sRGB keep as much chroma as fits
+///
+/// # Arguments
+/// * `palette` - A mutable reference to the scheme palette to adjust
+/// * `system` - A reference to the SchemeSystem deciding the accent slots
+/// * `min_chroma` - The lowest LCh chroma of an accent, e.g. `30.0`
+/// * `base0f` - The pure color base0F represents
+pub(crate) fn saturate_accents(
+    palette: &mut HashMap<String, SchemeColor>,
+    system: &SchemeSystem,
+    min_chroma: f32,
+    base0f: PureColor,
+) -> Result<(), Error> {
+    let offsets: &[usize] = match system {
+        SchemeSystem::Base24 => &[0x08, 0x10],
+        _ => &[0x08],
+    };
+
+    for (index, slot_hue) in accent_hues(base0f).iter().enumerate() {
+        for offset in offsets {
+            let slot = format!("base{:02X}", offset + index);
+            let Some(accent) = palette.get(&slot) else {
+                continue;
+            };
+            let (red, green, blue) = accent.rgb;
+            let lch = Lch::from_color(Srgb::new(red, green, blue).into_format::<f32>());
+
+            if lch.chroma >= min_chroma {
+                continue;
+            }
+
+            let hue = if lch.chroma < MIN_HUE_CHROMA {
+                let pure: Hsl = Hsl::new(*slot_hue, 1.0, 0.5);
+
+                Lch::from_color(pure).hue
+            } else {
+                lch.hue
+            };
+            let rgb = to_srgb_within_gamut(Lch::new(lch.l, min_chroma, hue));
+
+            palette.insert(slot, scheme_color_from_srgb(rgb));
+        }
+    }
+
+    Ok(())
+}
+
+/// Darken the yellow accents of a palette with a light base00 until they
+/// reach a 3:1 contrast ratio against it, keeping their LCh chroma so they
+/// get more saturated rather than turning olive. Palettes with a dark
+/// base00 are left alone
+///
+/// # Arguments
+/// * `palette` - A mutable reference to the scheme palette to adjust
+/// * `system` - A reference to the SchemeSystem deciding the accent slots
+pub(crate) fn legible_yellows(
+    palette: &mut HashMap<String, SchemeColor>,
+    system: &SchemeSystem,
+) -> Result<(), Error> {
+    let to_rgb = |color: &SchemeColor| -> Rgb {
+        let (red, green, blue) = color.rgb;
+
+        Srgb::new(red, green, blue).into_format()
+    };
+    let Some(background) = palette.get("base00").map(to_rgb) else {
+        return Ok(());
+    };
+
+    if get_sat_luma(background).1 < LIGHT_BACKGROUND_LUMA {
+        return Ok(());
+    }
+
+    let slots = match system {
+        SchemeSystem::Base24 => 0x08..0x18,
+        _ => 0x08..0x10,
+    };
+
+    for index in slots {
+        let slot = format!("base{:02X}", index);
+        let Some(accent) = palette.get(&slot).map(to_rgb) else {
+            continue;
+        };
+        let hsl = Hsl::from_color(accent);
+
+        if !YELLOW_HUES.contains(&hsl.hue.into_positive_degrees())
+            || contrast_ratio(accent, background) >= YELLOW_MIN_CONTRAST
+        {
+            continue;
+        }
+
+        let mut lch = Lch::from_color(accent);
+        let mut rgb = accent;
+
+        while contrast_ratio(rgb, background) < YELLOW_MIN_CONTRAST && lch.l > 0.0 {
+            lch.l -= 1.0;
+            rgb = to_srgb_within_gamut(lch).into_format();
+        }
+
+        let rgb: Srgb<u8> = rgb.into_format();
+
+        palette.insert(slot, scheme_color_from_srgb(rgb));
+    }
+
+    Ok(())
+}
+
+/// Get the hues of base08 to base0F, with base0F representing a pure color
+pub(crate) fn accent_hues(base0f: PureColor) -> [f32; 8] {
+    let mut hues = ACCENT_HUES;
+
+    if base0f == PureColor::Magenta {
+        hues[7] = MAGENTA_HUE;
+    }
+
+    hues
+}
+
+/// Get the most dominant noticeably colored color whose hue is far from
+/// the dominant hue, for `Base0FPolicy::SecondDominantHue`
+pub(crate) fn second_dominant_color(colors: &[Srgb<u8>]) -> Option<Srgb<u8>> {
+    let dominant_hue = dominant_hue(colors)?;
+
+    colors.iter().copied().find(|color| {
+        let lch = Lch::from_color(color.into_format::<f32>());
+        let distance = (lch.hue.into_positive_degrees() - dominant_hue).rem_euclid(360.0);
+
+        lch.chroma >= MIN_DOMINANT_CHROMA
+            && distance.min(360.0 - distance) >= MIN_SECOND_HUE_DISTANCE
+    })
+}
+
+/// Get the hue of the most dominant color that is noticeably colored,
+/// falling back to the most dominant color's hue
+pub(crate) fn dominant_hue(colors: &[Srgb<u8>]) -> Option<f32> {
+    let lch = |color: &Srgb<u8>| Lch::from_color(color.into_format::<f32>());
+
+    colors
+        .iter()
+        .map(lch)
+        .find(|color| color.chroma >= MIN_DOMINANT_CHROMA)
+        .or_else(|| colors.first().map(lch))
+        .map(|color| color.hue.into_positive_degrees())
+}
+
+/// Tint base00 to base07 towards a hue, keeping each gray's lightness and
+/// giving it the same chroma, like the colored grays of Nord or Dracula
+///
+/// # Arguments
+/// * `palette` - A mutable reference to the scheme palette to adjust
+/// * `hue` - The hue to tint towards, in degrees
+/// * `chroma` - The chroma of every gray
+pub(crate) fn tint_grays(
+    palette: &mut HashMap<String, SchemeColor>,
+    hue: f32,
+    chroma: f32,
+) -> Result<(), Error> {
+    for index in 0..8 {
+        let slot = format!("base{:02X}", index);
+        let Some(gray) = palette.get(&slot) else {
+            continue;
+        };
+        let (red, green, blue) = gray.rgb;
+        let lightness = Lch::from_color(Srgb::new(red, green, blue).into_format::<f32>()).l;
+        let rgb = to_srgb_within_gamut(Lch::new(lightness, chroma, hue));
+
+        palette.insert(slot, scheme_color_from_srgb(rgb));
+    }
+
+    Ok(())
+}
+
+/// Move accents that are too close to the background away from it, until
+/// they differ from base00 by at least `min_delta_e` (CIEDE2000). The
+/// lightness is moved away from base00's first, and the hue is only
+/// rotated when no lightness is far enough. An accent that can't get far
+/// enough gets the farthest color tried
+///
+/// # Arguments
+/// * `palette` - A mutable reference to the scheme palette to adjust
+/// * `system` - A reference to the SchemeSystem deciding the accent slots
+/// * `min_delta_e` - The smallest difference allowed from base00
+pub(crate) fn separate_accents(
+    palette: &mut HashMap<String, SchemeColor>,
+    system: &SchemeSystem,
+    min_delta_e: f32,
+) -> Result<(), Error> {
+    let to_lab = |color: &SchemeColor| -> Lab {
+        let (red, green, blue) = color.rgb;
+
+        Lab::from_color(Srgb::new(red, green, blue).into_format::<f32>())
+    };
+    let Some(background) = palette.get("base00").map(to_lab) else {
+        return Ok(());
+    };
+    let slots = match system {
+        SchemeSystem::Base24 => 0x08..0x18,
+        _ => 0x08..0x10,
+    };
+
+    for index in slots {
+        let slot = format!("base{:02X}", index);
+        let Some(accent) = palette.get(&slot).map(to_lab) else {
+            continue;
+        };
+
+        if background.difference(accent) >= min_delta_e {
+            continue;
+        }
+
+        let rgb = separate_color(Lch::from_color(accent), background, min_delta_e);
+
+        palette.insert(slot, scheme_color_from_srgb(rgb));
+    }
+
+    Ok(())
+}
+
+fn separate_color(accent: Lch, background: Lab, min_delta_e: f32) -> Srgb<u8> {
+    // Colors outside of sRGB are clamped, so the difference is measured on
+    // the color that is actually used
+    let candidate = |lightness: f32, hue: f32| -> (Srgb<u8>, f32) {
+        let rgb = to_srgb_within_gamut(Lch::new(lightness, accent.chroma, hue));
+
+        (
+            rgb,
+            background.difference(Lab::from_color(rgb.into_format::<f32>())),
+        )
+    };
+    let direction = if accent.l > background.l || (accent.l == background.l && background.l < 50.0)
+    {
+        1.0
+    } else {
+        -1.0
+    };
+    let hue = accent.hue.into_positive_degrees();
+    let mut best = (accent.l, candidate(accent.l, hue));
+    let mut lightness = accent.l;
 
-use crate::{
-    color::{Color, PureColor},
-    Error,
-};
-use image::{DynamicImage, GenericImageView};
-use palette::{rgb::Rgb, Hsl, IntoColor, Srgb, Yxy};
-use tinted_builder::SchemeVariant;
-
-const MAX_COLOR_DISTANCE: f64 = 100.0;
-
-pub(crate) fn find_closest_palette(image: &DynamicImage) -> Vec<Color> {
-    let target_colors: Vec<Color> = vec![
-        Color::from(PureColor::Red),
-        Color::from(PureColor::Yellow),
-        Color::from(PureColor::Orange),
-        Color::from(PureColor::Green),
-        Color::from(PureColor::Cyan),
-        Color::from(PureColor::Blue),
-        Color::from(PureColor::Purple),
-        Color::from(PureColor::Brown),
-        Color::from(PureColor::Magenta),
-        Color::from(PureColor::Azure),
-        Color::from(PureColor::SpringGreen),
-        Color::from(PureColor::LightCyan),
-    ];
-
-    let mut closest_colors_with_distance = target_colors.clone();
-    let mut closest_distances = [f64::MAX; 13];
-
-    for (_, _, pixel) in image.pixels() {
-        let color = Srgb::new(pixel[0], pixel[1], pixel[2]);
+    while (0.0..=100.0).contains(&lightness) {
+        let (rgb, delta_e) = candidate(lightness, hue);
 
-        for (i, &target_color) in target_colors.iter().enumerate() {
-            let distance = Color::get_distance(&color, &target_color.value);
-            if distance < closest_distances[i] {
-                closest_distances[i] = distance;
-                closest_colors_with_distance[i] = Color {
-                    associated_pure_color: target_color.associated_pure_color,
-                    value: color,
-                    distance,
-                };
-            }
+        if delta_e >= min_delta_e {
+            return rgb;
+        }
+
+        if delta_e > best.1 .1 {
+            best = (lightness, (rgb, delta_e));
         }
+
+        lightness += direction;
     }
 
-    closest_colors_with_distance.to_vec()
-}
+    let (lightness, _) = best;
 
-pub(crate) fn load_image(path: &Path) -> DynamicImage {
-    image::open(path).expect("Unable to load image")
-}
+    for step in 1..=36 {
+        for sign in [1.0, -1.0] {
+            let (rgb, delta_e) = candidate(lightness, hue + sign * step as f32 * 5.0);
 
-pub(crate) fn interpolate_color(start: Srgb<u8>, end: Srgb<u8>, t: f32) -> Srgb<u8> {
-    Srgb::new(
-        (start.red as f32 + t * (end.red as f32 - start.red as f32)) as u8,
-        (start.green as f32 + t * (end.green as f32 - start.green as f32)) as u8,
-        (start.blue as f32 + t * (end.blue as f32 - start.blue as f32)) as u8,
-    )
+            if delta_e >= min_delta_e {
+                return rgb;
+            }
+
+            if delta_e > best.1 .1 {
+                best = (lightness, (rgb, delta_e));
+            }
+        }
+    }
+
+    best.1 .0
 }
 
-pub(crate) fn generate_gradient(
-    darkest: Srgb<u8>,
-    lightest: Srgb<u8>,
-    steps: usize,
-) -> Vec<Srgb<u8>> {
-    (0..steps)
-        .map(|i| {
-            let t = i as f32 / (steps - 1) as f32;
+/// Keep the colors that are within `max_distance` of a pixel of the images,
+/// looking at every `sample_every`th pixel
+pub(crate) fn colors_in_images(
+    images: &[Cow<RgbaImage>],
+    colors: impl IntoIterator<Item = Color>,
+    max_distance: f64,
+    sample_every: usize,
+) -> Vec<Color> {
+    colors
+        .into_iter()
+        .filter(|color| {
+            images.iter().any(|image| {
+                image
+                    .as_raw()
+                    .chunks_exact(4)
+                    .step_by(sample_every.max(1))
+                    .any(|pixel| {
+                        let pixel = Srgb::new(pixel[0], pixel[1], pixel[2]);
 
-            interpolate_color(darkest, lightest, t)
+                        Color::get_distance(&pixel, &color.value) <= max_distance
+                    })
+            })
         })
         .collect()
 }
 
+/// Pick the inverse's match for each pure color, keeping the match only when
+/// it's farther than `max_color_distance` from the pure color and still
+/// closer to it than the inverse's match
 pub(crate) fn create_palette_with_inverse_colors(
     palette: &[Color],
     inverse_palette: &[Color],
+    max_color_distance: f64,
 ) -> Vec<Color> {
     let mut curated_palette: Vec<Color> = Vec::new();
 
@@ -86,7 +1711,7 @@ pub(crate) fn create_palette_with_inverse_colors(
             .find(|c| c.associated_pure_color.as_str() == color.associated_pure_color.as_str());
 
         if let Some(color_inverse) = color_inverse_opt {
-            if color.distance > MAX_COLOR_DISTANCE && color.distance < color_inverse.distance {
+            if color.distance > max_color_distance && color.distance < color_inverse.distance {
                 curated_palette.push(*color);
             } else {
                 curated_palette.push(*color_inverse);
@@ -99,67 +1724,69 @@ pub(crate) fn create_palette_with_inverse_colors(
     curated_palette
 }
 
+/// Replace the matches with the dominant colors within `max_delta_e` of
+/// their pure color
 pub(crate) fn create_palette_with_color_thief_colors(
     palette: &[Color],
     color_thief_palette: &[Srgb<u8>],
+    max_delta_e: f32,
 ) -> Result<Vec<Color>, Error> {
-    let color_thief_palette: Vec<Option<Color>> = color_thief_palette
+    // Each dominant color with the difference to its closest pure color
+    let color_thief_palette: Vec<Option<(Color, f32)>> = color_thief_palette
         .iter()
         .map(|c| {
-            let mut matching_colors: Vec<Color> = Vec::new();
             let rgb = Srgb::new(c.red, c.green, c.blue);
 
-            for color in palette {
-                let attempted_color = Color::new(color.associated_pure_color, rgb);
-
-                if attempted_color.distance < MAX_COLOR_DISTANCE {
-                    matching_colors.push(attempted_color);
-                }
-            }
-
-            matching_colors.sort_by(|a, b| {
-                a.distance
-                    .partial_cmp(&b.distance)
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            });
+            palette
+                .iter()
+                .map(|color| {
+                    let pure_color = color.associated_pure_color;
 
-            if matching_colors.is_empty() {
-                None
-            } else {
-                matching_colors.first().copied()
-            }
+                    (
+                        Color::new(pure_color, rgb),
+                        Color::get_delta_e(&pure_color.get_rgb(), &rgb),
+                    )
+                })
+                .filter(|(_, delta_e)| *delta_e < max_delta_e)
+                .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
         })
         .collect();
-    let mut color_by_pure_color: HashMap<String, Color> = HashMap::new();
+    // Keyed by pure color name so iteration order doesn't depend on hashing.
+    // On equal differences the more dominant color_thief color, which comes
+    // first, is kept
+    let mut color_by_pure_color: BTreeMap<String, (Color, f32)> = BTreeMap::new();
 
-    for color in &color_thief_palette
-        .into_iter()
-        .flatten()
-        .collect::<Vec<Color>>()
-    {
+    for (color, delta_e) in color_thief_palette.into_iter().flatten() {
         color_by_pure_color
             .entry(color.associated_pure_color.as_str().to_string())
             .and_modify(|e| {
-                if color.distance < e.distance {
-                    *e = *color
+                if delta_e < e.1 {
+                    *e = (color, delta_e)
                 }
             })
-            .or_insert(*color);
+            .or_insert((color, delta_e));
     }
 
-    let mut palette_with_color_thief_colors: Vec<Color> =
-        color_by_pure_color.into_values().collect();
+    // One color per pure color, in the order the pure colors first appear in
+    // the palette
+    let mut palette_with_color_thief_colors: Vec<Color> = Vec::new();
 
     for color in palette {
+        let pure_color = color.associated_pure_color.as_str();
+
         if !palette_with_color_thief_colors
             .iter()
-            .any(|c| c.associated_pure_color.as_str() == color.associated_pure_color.as_str())
+            .any(|c| c.associated_pure_color.as_str() == pure_color)
         {
-            palette_with_color_thief_colors.push(*color);
+            palette_with_color_thief_colors.push(
+                color_by_pure_color
+                    .get(pure_color)
+                    .map_or(*color, |(color, _)| *color),
+            );
         }
     }
 
-    Ok(palette_with_color_thief_colors.clone())
+    Ok(palette_with_color_thief_colors)
 }
 
 fn get_sat_luma(color: Rgb) -> (f32, f32) {
@@ -170,77 +1797,41 @@ fn get_sat_luma(color: Rgb) -> (f32, f32) {
     (saturation, luma)
 }
 
-pub(crate) fn fix_colors(dark: Rgb, light: Rgb, mode: &SchemeVariant) -> (Rgb, Rgb) {
+/// Bring the dark and light colors within the profile's bounds, returning
+/// the background and foreground
+pub(crate) fn fix_colors(
+    dark: Rgb,
+    light: Rgb,
+    mode: &SchemeVariant,
+    profile: &VariantProfile,
+) -> (Rgb, Rgb) {
     match mode {
         SchemeVariant::Light => {
-            let mut fg = dark;
-            let mut bg = light;
-            // Foreground should be pretty dark and have:
-            // luma <= 0.015 && saturation <= 0.65
-            let (saturation, luma) = get_sat_luma(fg);
-            if luma > 0.015 {
-                let yxy: Yxy = fg.into_color();
-                let (x, y, _) = yxy.into_components();
-                let yxy: Yxy = Yxy::from_components((x, y, 0.015));
-                fg = yxy.into_color();
-            }
-            if saturation > 0.65 {
-                let hsl: Hsl = fg.into_color();
-                let (h, _, l) = hsl.into_components();
-                let hsl: Hsl = Hsl::from_components((h, 0.65, l));
-                fg = hsl.into_color();
-            }
+            let bg = clamp_color(
+                light,
+                profile.background_luma..=1.0,
+                profile.background_max_saturation,
+            );
+            let fg = clamp_color(
+                dark,
+                0.0..=profile.foreground_luma,
+                profile.foreground_max_saturation,
+            );
 
-            // Background should be light have:
-            // luma >= 0.7 && saturation <= 0.12
-            let (saturation, luma) = get_sat_luma(light);
-            if luma < 0.75 {
-                let yxy: Yxy = bg.into_color();
-                let (x, y, _) = yxy.into_components();
-                let yxy: Yxy = Yxy::from_components((x, y, 0.75));
-                bg = yxy.into_color();
-            }
-            if saturation > 0.12 {
-                let hsl: Hsl = bg.into_color();
-                let (h, _, l) = hsl.into_components();
-                let hsl: Hsl = Hsl::from_components((h, 0.15, l));
-                bg = hsl.into_color();
-            }
             (bg, fg)
         }
         SchemeVariant::Dark => {
-            let mut fg = light;
-            let mut bg = dark;
-            // Foreground should be light and have:
-            // luma >= 0.6 && saturation <= 0.15
-            let (saturation, luma) = get_sat_luma(light);
-            if luma < 0.6 {
-                let yxy: Yxy = fg.into_color();
-                let (x, y, _) = yxy.into_components();
-                let yxy: Yxy = Yxy::from_components((x, y, 0.6));
-                fg = yxy.into_color();
-            }
-            if saturation > 0.15 {
-                let hsl: Hsl = fg.into_color();
-                let (h, _, l) = hsl.into_components();
-                let hsl: Hsl = Hsl::from_components((h, 0.15, l));
-                fg = hsl.into_color();
-            }
-            // Background should be dark and have:
-            // luma <= 0.02 && saturation <= 0.6
-            let (saturation, luma) = get_sat_luma(dark);
-            if luma > 0.02 {
-                let yxy: Yxy = bg.into_color();
-                let (x, y, _) = yxy.into_components();
-                let yxy: Yxy = Yxy::from_components((x, y, 0.02));
-                bg = yxy.into_color();
-            }
-            if saturation > 0.6 {
-                let hsl: Hsl = bg.into_color();
-                let (h, _, l) = hsl.into_components();
-                let hsl: Hsl = Hsl::from_components((h, 0.6, l));
-                bg = hsl.into_color();
-            }
+            let bg = clamp_color(
+                dark,
+                0.0..=profile.background_luma,
+                profile.background_max_saturation,
+            );
+            let fg = clamp_color(
+                light,
+                profile.foreground_luma..=1.0,
+                profile.foreground_max_saturation,
+            );
+
             (bg, fg)
         }
         // This case shouldn't be reachable since a check against it is done earlier
@@ -248,116 +1839,894 @@ pub(crate) fn fix_colors(dark: Rgb, light: Rgb, mode: &SchemeVariant) -> (Rgb, R
     }
 }
 
-fn color_pass(
-    colors: &[Rgb],
-    min_luma: Option<f32>,
-    max_luma: Option<f32>,
-    min_saturation: Option<f32>,
-    max_saturation: Option<f32>,
-) -> Option<Rgb> {
+/// Move the color's luma into the range and cap its saturation
+fn clamp_color(color: Rgb, luma_range: RangeInclusive<f32>, max_saturation: f32) -> Rgb {
+    let (saturation, luma) = get_sat_luma(color);
+    let mut color = color;
+    let clamped_luma = luma.clamp(*luma_range.start(), *luma_range.end());
+
+    if clamped_luma != luma {
+        let yxy: Yxy = color.into_color();
+        let (x, y, _) = yxy.into_components();
+
+        color = Yxy::from_components((x, y, clamped_luma)).into_color();
+    }
+
+    if saturation > max_saturation {
+        let hsl: Hsl = color.into_color();
+        let (h, _, l) = hsl.into_components();
+
+        color = Hsl::from_components((h, max_saturation, l)).into_color();
+    }
+
+    color
+}
+
+fn color_pass(colors: &[Rgb], pass: &ColorPass) -> Option<Rgb> {
+    let within = |value: f32, min: Option<f32>, max: Option<f32>| {
+        min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max)
+    };
     let predicate = |rgb: &Rgb| {
         let (saturation, luma) = get_sat_luma(*rgb);
 
-        let luma_check = match (min_luma, max_luma) {
-            (Some(min), Some(max)) => luma >= min && luma <= max,
-            (Some(min), None) => luma >= min,
-            (None, Some(max)) => luma <= max,
-            (None, None) => true,
+        within(luma, pass.min_luma, pass.max_luma)
+            && within(saturation, pass.min_saturation, pass.max_saturation)
+    };
+
+    colors.iter().copied().find(predicate)
+}
+
+/// Find an anchor color with the first pass of the ladder that any color
+/// matches, falling back to the most dominant color. Also returns the
+/// number of passes it took and the pass that matched
+pub(crate) fn anchor_color(
+    colors: &[Srgb<f32>],
+    ladder: &[ColorPass],
+) -> Result<(Srgb<f32>, u32, Option<ColorPass>), Error> {
+    for (index, pass) in ladder.iter().enumerate() {
+        if let Some(color) = color_pass(colors, pass) {
+            return Ok((color, index as u32 + 1, Some(*pass)));
+        }
+    }
+
+    colors
+        .first()
+        .map(|color| (*color, ladder.len() as u32 + 1, None))
+        .ok_or(Error::NoColors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        DecodeLimits, DEFAULT_ACCENT_HUE_RANGES, DEFAULT_LIGHT_LADDER, DEFAULT_MAX_DELTA_E,
+    };
+
+    #[test]
+    fn test_interpolate_color_rounds() {
+        let color = interpolate_color(Srgb::new(0, 0, 0), Srgb::new(3, 255, 100), 0.5);
+
+        assert_eq!(color, Srgb::new(2, 128, 50));
+    }
+
+    #[test]
+    fn test_create_palette_with_color_thief_colors_order() {
+        let palette: Vec<Color> = TARGET_COLORS.iter().map(|c| Color::from(*c)).collect();
+        let color_thief_palette = [
+            Srgb::new(20, 20, 240),
+            Srgb::new(240, 20, 20),
+            Srgb::new(250, 10, 10),
+        ];
+        let combined = create_palette_with_color_thief_colors(
+            &palette,
+            &color_thief_palette,
+            DEFAULT_MAX_DELTA_E,
+        )
+        .unwrap();
+        let pure_colors: Vec<PureColor> =
+            combined.iter().map(|c| c.associated_pure_color).collect();
+
+        assert_eq!(pure_colors, TARGET_COLORS);
+        assert_eq!(combined[0].value, Srgb::new(250, 10, 10));
+        assert_eq!(combined[5].value, Srgb::new(20, 20, 240));
+    }
+
+    #[test]
+    fn test_create_palette_with_color_thief_colors_max_delta_e() {
+        let palette: Vec<Color> = TARGET_COLORS.iter().map(|c| Color::from(*c)).collect();
+        // A muted mauve, about 18 from pure magenta and further from the others
+        let color_thief_palette = [Srgb::new(180, 110, 160)];
+        let magenta = TARGET_COLORS
+            .iter()
+            .position(|c| *c == PureColor::Magenta)
+            .unwrap();
+
+        let combined =
+            create_palette_with_color_thief_colors(&palette, &color_thief_palette, 10.0).unwrap();
+
+        assert_eq!(combined[magenta].value, Srgb::new(255, 0, 255));
+
+        let combined = create_palette_with_color_thief_colors(
+            &palette,
+            &color_thief_palette,
+            DEFAULT_MAX_DELTA_E,
+        )
+        .unwrap();
+
+        assert_eq!(combined[magenta].value, Srgb::new(180, 110, 160));
+    }
+
+    #[test]
+    fn test_image_limits_keep_defaults() {
+        let options = ExtractionOptions {
+            limits: Some(DecodeLimits {
+                max_width: Some(100),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let limits = image_limits(&options);
+
+        assert_eq!(limits.max_image_width, Some(100));
+        assert_eq!(limits.max_image_height, None);
+        assert_eq!(limits.max_alloc, Limits::default().max_alloc);
+        assert!(limits.max_alloc.is_some());
+    }
+
+    #[test]
+    fn test_create_palette_with_inverse_colors() {
+        let red = Color::new(PureColor::Red, Srgb::new(230, 20, 20));
+        let far_red = Color::new(PureColor::Red, Srgb::new(120, 90, 90));
+        let inverse = Color::new(PureColor::Red, Srgb::new(200, 40, 40));
+
+        let far_inverse = Color::new(PureColor::Red, Srgb::new(90, 120, 120));
+
+        // Matches within max_color_distance use the inverse's match
+        let curated = create_palette_with_inverse_colors(&[red], &[inverse], 100.0);
+
+        assert_eq!(curated[0].value, inverse.value);
+
+        // Far matches are kept when they're closer than the inverse's match
+        let curated = create_palette_with_inverse_colors(&[far_red], &[far_inverse], 100.0);
+
+        assert_eq!(curated[0].value, far_red.value);
+
+        let curated = create_palette_with_inverse_colors(&[far_red], &[inverse], 100.0);
+
+        assert_eq!(curated[0].value, inverse.value);
+    }
+
+    #[test]
+    fn test_fix_colors_uses_profile() {
+        let dark: Rgb = Srgb::new(100u8, 100, 120).into_format();
+        let light: Rgb = Srgb::new(230u8, 230, 230).into_format();
+        let profile = VariantProfile {
+            background_luma: 0.05,
+            ..VariantProfile::dark()
         };
 
-        let saturation_check = match (min_saturation, max_saturation) {
-            (Some(min), Some(max)) => saturation >= min && saturation <= max,
-            (Some(min), None) => saturation >= min,
-            (None, Some(max)) => saturation <= max,
-            (None, None) => true,
+        let (background, _) =
+            fix_colors(dark, light, &SchemeVariant::Dark, &VariantProfile::dark());
+        let (soft_background, foreground) = fix_colors(dark, light, &SchemeVariant::Dark, &profile);
+
+        assert!((get_sat_luma(background).1 - 0.02).abs() < 0.001);
+        assert!((get_sat_luma(soft_background).1 - 0.05).abs() < 0.001);
+        assert_eq!(foreground, light);
+    }
+
+    #[test]
+    fn test_anchor_color_ladder() {
+        let colors: Vec<Srgb<f32>> = [Srgb::new(250u8, 20, 20), Srgb::new(240, 240, 240)]
+            .iter()
+            .map(|color| color.into_format())
+            .collect();
+        let saturated = ColorPass {
+            min_saturation: Some(0.9),
+            ..Default::default()
         };
 
-        luma_check && saturation_check
-    };
+        let (color, passes, pass) = anchor_color(&colors, &DEFAULT_LIGHT_LADDER).unwrap();
 
-    colors.iter().copied().find(predicate)
-}
+        assert_eq!(color, colors[1]);
+        assert_eq!((passes, pass), (1, Some(DEFAULT_LIGHT_LADDER[0])));
+
+        let (color, passes, pass) = anchor_color(&colors, &[saturated]).unwrap();
+
+        assert_eq!(color, colors[0]);
+        assert_eq!((passes, pass), (1, Some(saturated)));
+
+        let (color, passes, pass) = anchor_color(&colors[1..], &[saturated]).unwrap();
+
+        assert_eq!(color, colors[1]);
+        assert_eq!((passes, pass), (2, None));
+    }
+
+    #[test]
+    fn test_fill_missing_accents() {
+        let mut palette: HashMap<String, SchemeColor> =
+            generate_gradient(Srgb::new(16, 16, 16), Srgb::new(240, 240, 240), 8)
+                .iter()
+                .enumerate()
+                .map(|(index, rgb)| (format!("base0{}", index), scheme_color_from_srgb(*rgb)))
+                .collect();
+
+        palette.insert(
+            "base08".to_string(),
+            SchemeColor::new("CC3333".to_string()).unwrap(),
+        );
+        fill_missing_accents(&mut palette, &SchemeSystem::Base24, PureColor::Brown).unwrap();
+
+        assert_eq!(palette.len(), 24);
+        assert_eq!(palette["base08"].rgb, (204, 51, 51));
+        // Rotated from base08 to green, keeping saturation and lightness
+        assert_eq!(palette["base0B"].rgb, (51, 204, 51));
+    }
+
+    #[test]
+    fn test_constrain_accent_hues() {
+        let mut palette: HashMap<String, SchemeColor> = [
+            ("base08", "CC7A33"),
+            ("base0B", "33CC33"),
+            ("base0F", "CC7A33"),
+            ("base10", "CC33CC"),
+        ]
+        .iter()
+        .map(|(slot, hex)| (slot.to_string(), scheme_color(hex.to_string()).unwrap()))
+        .collect();
+
+        constrain_accent_hues(
+            &mut palette,
+            &SchemeSystem::Base24,
+            &DEFAULT_ACCENT_HUE_RANGES,
+        )
+        .unwrap();
+
+        let hue = |slot: &str| {
+            let (red, green, blue) = palette[slot].rgb;
+
+            Hsl::from_color(Srgb::new(red, green, blue).into_format::<f32>())
+                .hue
+                .into_positive_degrees()
+        };
+
+        // The orange-brown red is rotated to the top of the red range
+        assert!((hue("base08") - 15.0).abs() < 1.0);
+        assert_eq!(palette["base0B"].rgb, (51, 204, 51));
+        // base0F has no range
+        assert_eq!(palette["base0F"].rgb, (204, 122, 51));
+        // The magenta bright red is rotated to the bottom of the red range
+        assert!((hue("base10") - 340.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_saturate_accents() {
+        let mut palette: HashMap<String, SchemeColor> = [
+            ("base08", "8A7C7A"),
+            ("base0B", "808080"),
+            ("base0D", "3366CC"),
+            ("base15", "7A7C8A"),
+        ]
+        .iter()
+        .map(|(slot, hex)| (slot.to_string(), scheme_color(hex.to_string()).unwrap()))
+        .collect();
+        let lch = |palette: &HashMap<String, SchemeColor>, slot: &str| {
+            let (red, green, blue) = palette[slot].rgb;
+
+            Lch::from_color(Srgb::new(red, green, blue).into_format::<f32>())
+        };
+        let muted_red = lch(&palette, "base08");
 
-pub(crate) fn light_color(colors: &[Srgb<f32>], verbose: bool) -> Result<Srgb<f32>, Error> {
-    let mut passes = 1;
-    // Try to find a nice light color with low saturation
-    let mut light = color_pass(colors, Some(0.6), None, None, Some(0.4));
+        saturate_accents(&mut palette, &SchemeSystem::Base24, 30.0, PureColor::Brown).unwrap();
 
-    // Try again, but now we will accept saturated colors, as long as they're very bright
-    if light.is_none() {
-        passes += 1;
-        light = color_pass(colors, Some(0.7), None, None, Some(0.85));
+        let red = lch(&palette, "base08");
+
+        assert!(red.chroma > 29.0);
+        assert!((red.l - muted_red.l).abs() < 1.0);
+        assert!(
+            (red.hue.into_positive_degrees() - muted_red.hue.into_positive_degrees()).abs() < 3.0
+        );
+        // The gray gets the hue of green
+        let green = lch(&palette, "base0B");
+
+        assert!(green.chroma > 29.0);
+        assert!((green.hue.into_positive_degrees() - 136.0).abs() < 5.0);
+        assert_eq!(palette["base0D"].rgb, (51, 102, 204));
+        assert!(lch(&palette, "base15").chroma > 29.0);
+    }
+
+    #[test]
+    fn test_scan_pixels_matches_brute_force() {
+        // Pseudo-random colors over several row chunks, with two colors at
+        // the same distance from red where the later one is the smaller value
+        let image = RgbaImage::from_fn(150, 140, |x, y| match (x, y) {
+            (3, 1) => image::Rgba([255, 4, 3, 255]),
+            (7, 90) => image::Rgba([255, 3, 4, 255]),
+            _ => {
+                let seed = (x * 7919 + y * 104_729).wrapping_mul(2_654_435_761);
+
+                image::Rgba([
+                    (seed >> 8) as u8,
+                    (seed >> 16) as u8,
+                    (seed >> 24) as u8,
+                    255,
+                ])
+            }
+        });
+        let images: [Cow<RgbaImage>; 1] = [Cow::Owned(image)];
+
+        // The same comparisons as the original find_closest_palette, with
+        // the center weights applied
+        let find_closest_palette = |center_weight: f32| {
+            let mut closest_distances = [f64::MAX; 12];
+            let mut closest_palette: Vec<Color> =
+                TARGET_COLORS.iter().map(|c| Color::from(*c)).collect();
+
+            for (x, y, pixel) in images[0].enumerate_pixels() {
+                let color = Srgb::new(pixel[0], pixel[1], pixel[2]);
+                let weight = 1.0 + (center_weight * center_distance(x, y, 150, 140)) as f64;
+
+                for (i, pure_color) in TARGET_COLORS.iter().enumerate() {
+                    let distance = Color::get_distance(&color, &pure_color.get_rgb());
+
+                    if distance * weight < closest_distances[i] {
+                        closest_distances[i] = distance * weight;
+                        closest_palette[i] = Color::new(*pure_color, color);
+                    }
+                }
+            }
+
+            closest_palette
+        };
+
+        for center_weight in [0.0, 0.7] {
+            let options = ExtractionOptions {
+                center_weight,
+                ..Default::default()
+            };
+
+            assert_eq!(
+                scan_pixels(&images, &options).closest_palette,
+                find_closest_palette(center_weight)
+            );
+        }
+
+        assert_eq!(
+            scan_pixels(&images, &ExtractionOptions::default()).closest_palette[0].value,
+            Srgb::new(255, 4, 3)
+        );
     }
 
-    // Try again, same as first, but a little more permissive
-    if light.is_none() {
-        passes += 1;
-        light = color_pass(colors, Some(0.5), None, None, Some(0.5));
+    #[test]
+    fn test_subject_pixels() {
+        // A small red subject in the middle of a large gray background
+        let image = RgbaImage::from_fn(40, 40, |x, y| {
+            if (15..25).contains(&x) && (15..25).contains(&y) {
+                image::Rgba([220, 30, 30, 255])
+            } else {
+                image::Rgba([120, 120, 120, 255])
+            }
+        });
+        let images = [Cow::Owned(image)];
+        let red_share = |pixels: &[u8]| {
+            let red = pixels
+                .chunks_exact(4)
+                .filter(|pixel| pixel[0] == 220)
+                .count();
+
+            red as f32 / (pixels.len() / 4) as f32
+        };
+
+        assert_eq!(
+            red_share(&subject_pixels(&images, SubjectBias::Contrast(0.0))),
+            0.0625
+        );
+        assert!(red_share(&subject_pixels(&images, SubjectBias::Contrast(1.0))) > 0.5);
+        assert!(red_share(&subject_pixels(&images, SubjectBias::Center(1.0))) > 0.1);
     }
 
-    // Try again, but accept more saturated colors
-    if light.is_none() {
-        passes += 1;
-        light = color_pass(colors, Some(0.6), None, None, Some(0.85));
+    #[test]
+    fn test_without_skin_tones() {
+        let skin = [224, 172, 140, 255];
+        let blue = [40, 40, 200, 255];
+        let pixels: Vec<u8> = [skin, blue].repeat(50).concat();
+
+        assert!(is_skin_tone(Srgb::new(224, 172, 140)));
+        assert!(is_skin_tone(Srgb::new(141, 85, 36)));
+        assert!(!is_skin_tone(Srgb::new(40, 40, 200)));
+        assert!(!is_skin_tone(Srgb::new(128, 128, 128)));
+        assert_eq!(without_skin_tones(&pixels, 0.0), blue.repeat(50));
+        assert_eq!(without_skin_tones(&pixels, 1.0), pixels);
     }
 
-    // Try again, but now we will accept darker colors, as long as they're not saturated
-    if light.is_none() {
-        passes += 1;
-        light = color_pass(colors, Some(0.32), None, None, Some(0.4));
+    #[test]
+    fn test_denoise_image() {
+        // A gray image with a single bright outlier
+        let mut image = RgbaImage::from_pixel(9, 9, image::Rgba([100, 100, 100, 255]));
+
+        image.put_pixel(4, 4, image::Rgba([255, 0, 255, 255]));
+
+        let median = denoise_image(&image, Denoise::Median(1));
+        let blurred = denoise_image(&image, Denoise::Gaussian(1.0));
+
+        assert_eq!(median.get_pixel(4, 4).0, [100, 100, 100, 255]);
+        assert!(blurred.get_pixel(4, 4)[0] < 200);
+        assert_eq!(denoise_image(&image, Denoise::Median(0)), image);
     }
 
-    // Try again, but now we will accept even more saturated colors
-    if light.is_none() {
-        passes += 1;
-        light = color_pass(colors, Some(0.4), None, None, None);
+    #[test]
+    fn test_equalize_image() {
+        // A hazy image whose grays only span 100 to 130
+        let image = RgbaImage::from_fn(31, 1, |x, _| {
+            image::Rgba([100 + x as u8, 100 + x as u8, 110 + x as u8, 255])
+        });
+        let equalized = equalize_image(&image);
+        let first = equalized.get_pixel(0, 0).0;
+        let last = equalized.get_pixel(30, 0).0;
+
+        let middle = equalized.get_pixel(15, 0).0;
+
+        assert!(first[0] < 10 && last[0] > 240);
+        // The blue tint is kept where it isn't clipped
+        assert_eq!(middle[2] as i32 - middle[0] as i32, 10);
+        assert_eq!(
+            equalize_image(&RgbaImage::from_pixel(4, 4, image::Rgba([9, 8, 7, 255]))),
+            RgbaImage::from_pixel(4, 4, image::Rgba([9, 8, 7, 255]))
+        );
     }
 
-    // Try again, with darker colors
-    if light.is_none() {
-        passes += 1;
-        light = color_pass(colors, Some(0.3), None, None, None);
+    #[cfg(any(feature = "gif", feature = "png", feature = "webp"))]
+    #[test]
+    fn test_select_frames() {
+        let frames: Vec<RgbaImage> = (0..6u8)
+            .map(|index| RgbaImage::from_pixel(2, 2, image::Rgba([index * 40, 0, 0, 255])))
+            .collect();
+        let red = |frames: &[RgbaImage]| -> Vec<u8> {
+            frames
+                .iter()
+                .map(|frame| frame.get_pixel(0, 0)[0])
+                .collect()
+        };
+
+        let select = |frames: &[RgbaImage], selection| {
+            select_frames(frames.iter().cloned().map(Ok), frames.len(), selection).unwrap()
+        };
+
+        assert_eq!(
+            red(&select(&frames, FrameSelection::Sample(3))),
+            [0, 80, 160]
+        );
+        assert_eq!(
+            red(&select(&frames, FrameSelection::Sample(10))),
+            [0, 40, 80, 120, 160, 200]
+        );
+        assert_eq!(red(&select(&frames, FrameSelection::Average(3))), [80]);
+        assert_eq!(red(&select(&frames, FrameSelection::Middle)), [120]);
+        assert_eq!(red(&select(&frames, FrameSelection::Index(4))), [160]);
+        assert_eq!(red(&select(&frames, FrameSelection::Index(40))), [200]);
+        assert!(select(&[], FrameSelection::Sample(3)).is_empty());
+        assert!(select(&[], FrameSelection::Average(3)).is_empty());
     }
 
-    // Ok, we didn't find anything usable. So let's just grab the most dominant color (we'll lighten it later)
-    if light.is_none() {
-        passes += 1;
-        light = colors.first().copied();
+    #[cfg(feature = "gif")]
+    #[test]
+    fn test_decode_gif_frames() {
+        let mut gif = io::Cursor::new(Vec::new());
+        let frames = (0..5u8).map(|index| {
+            Frame::new(RgbaImage::from_pixel(
+                2,
+                2,
+                image::Rgba([index * 50, 0, 0, 255]),
+            ))
+        });
+
+        image::codecs::gif::GifEncoder::new(&mut gif)
+            .encode_frames(frames)
+            .unwrap();
+
+        let decode = |selection| {
+            decode_frames(
+                io::Cursor::new(gif.get_ref()),
+                ImageFormat::Gif,
+                Limits::default(),
+                selection,
+            )
+            .unwrap()
+            .unwrap()
+            .iter()
+            .map(|frame| frame.get_pixel(0, 0)[0])
+            .collect::<Vec<u8>>()
+        };
+
+        assert_eq!(decode(FrameSelection::Middle), [100]);
+        assert_eq!(decode(FrameSelection::Sample(2)), [0, 100]);
+        assert_eq!(decode(FrameSelection::Average(5)), [100]);
+        assert_eq!(decode(FrameSelection::Index(3)), [150]);
     }
 
-    if verbose {
-        println!("Passes: {}", passes);
+    #[cfg(any(feature = "gif", feature = "png", feature = "webp"))]
+    #[test]
+    fn test_select_frames_memory() {
+        const FRAMES: usize = 200;
+        const FRAME_BYTES: isize = 256 * 256 * 4;
+
+        let peak_while = |selection| {
+            let frames = (0..FRAMES).map(|_| Ok(RgbaImage::new(256, 256)));
+            let start = counting_allocator::reset_peak();
+            let selected = select_frames(frames, FRAMES, selection).unwrap();
+
+            (counting_allocator::peak() - start, selected.len())
+        };
+
+        // Buffering the animation would take 200 frames
+        let (peak, selected) = peak_while(FrameSelection::Sample(4));
+
+        assert_eq!(selected, 4);
+        assert!(peak < 6 * FRAME_BYTES, "{}", peak);
+
+        let (peak, selected) = peak_while(FrameSelection::Average(50));
+
+        assert_eq!(selected, 1);
+        assert!(peak < 7 * FRAME_BYTES, "{}", peak);
+
+        let (peak, selected) = peak_while(FrameSelection::Middle);
+
+        assert_eq!(selected, 1);
+        assert!(peak < 3 * FRAME_BYTES, "{}", peak);
     }
 
-    light.ok_or_else(|| Error::NoColors("Failed to find colors on image".to_string()))
-}
+    /// Counts the bytes allocated by each thread, so tests running in
+    /// parallel can check their own peak memory use
+    #[cfg(any(feature = "gif", feature = "png", feature = "webp"))]
+    mod counting_allocator {
+        use std::{
+            alloc::{GlobalAlloc, Layout, System},
+            cell::Cell,
+        };
+
+        struct CountingAllocator;
+
+        #[global_allocator]
+        static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+        thread_local! {
+            static ALLOCATED: Cell<isize> = const { Cell::new(0) };
+            static PEAK: Cell<isize> = const { Cell::new(0) };
+        }
+
+        fn track(bytes: isize) {
+            let _ = ALLOCATED.try_with(|allocated| {
+                allocated.set(allocated.get() + bytes);
+
+                let _ = PEAK.try_with(|peak| peak.set(peak.get().max(allocated.get())));
+            });
+        }
+
+        /// Start measuring the peak from the bytes allocated now, which
+        /// are returned
+        pub(super) fn reset_peak() -> isize {
+            let allocated = ALLOCATED.with(Cell::get);
+
+            PEAK.with(|peak| peak.set(allocated));
+            allocated
+        }
+
+        pub(super) fn peak() -> isize {
+            PEAK.with(Cell::get)
+        }
+
+        unsafe impl GlobalAlloc for CountingAllocator {
+            unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+                track(layout.size() as isize);
+                System.alloc(layout)
+            }
 
-pub(crate) fn dark_color(colors: &[Srgb<f32>], verbose: bool) -> Result<Srgb<f32>, Error> {
-    let mut passes = 1;
-    // Try to find a nice darkish color with at least a bit of color
-    let mut dark = color_pass(colors, Some(0.012), Some(0.1), Some(0.18), Some(0.9));
+            unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+                track(layout.size() as isize);
+                System.alloc_zeroed(layout)
+            }
+
+            unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+                track(-(layout.size() as isize));
+                System.dealloc(ptr, layout)
+            }
+
+            unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+                track(new_size as isize - layout.size() as isize);
+                System.realloc(ptr, layout, new_size)
+            }
+        }
+    }
+
+    #[cfg(all(feature = "ff", feature = "qoi"))]
+    #[test]
+    fn test_decode_qoi_and_farbfeld() {
+        let image = RgbaImage::from_fn(3, 2, |x, y| {
+            image::Rgba([x as u8 * 80, y as u8 * 200, 30, 255])
+        });
+        let mut qoi = io::Cursor::new(Vec::new());
+
+        image.write_to(&mut qoi, ImageFormat::Qoi).unwrap();
+
+        // farbfeld stores 16-bit big-endian channels after its header
+        let mut farbfeld = b"farbfeld".to_vec();
+
+        farbfeld.extend(3u32.to_be_bytes());
+        farbfeld.extend(2u32.to_be_bytes());
+        farbfeld.extend(
+            image
+                .as_raw()
+                .iter()
+                .flat_map(|channel| (*channel as u16 * 257).to_be_bytes()),
+        );
+
+        for encoded in [qoi.into_inner(), farbfeld] {
+            // Recognised by their contents, e.g. screenshots piped in
+            let frames = decode_image_frames(
+                io::Cursor::new(encoded),
+                Path::new("screenshot"),
+                None,
+                &ExtractionOptions::default(),
+            )
+            .unwrap();
+
+            assert_eq!(frames.len(), 1);
+            assert_eq!(frames[0].to_rgba8(), image);
+        }
+    }
+
+    #[cfg(feature = "jpeg")]
+    #[test]
+    fn test_decode_reduced_jpeg() {
+        let image = image::RgbImage::from_pixel(400, 200, image::Rgb([200, 40, 40]));
+        let mut jpeg = io::Cursor::new(Vec::new());
+
+        image.write_to(&mut jpeg, ImageFormat::Jpeg).unwrap();
+
+        let decode = |options: ExtractionOptions| {
+            decode_image_frames(
+                io::Cursor::new(jpeg.get_ref()),
+                Path::new("wallpaper.jpg"),
+                None,
+                &options,
+            )
+            .unwrap()[0]
+                .dimensions()
+        };
+
+        assert_eq!(
+            decode(ExtractionOptions {
+                max_dimension: Some(100),
+                ..Default::default()
+            }),
+            (100, 50)
+        );
+        // Crop regions are in the full-size image's pixels
+        assert_eq!(
+            decode(ExtractionOptions {
+                max_dimension: Some(100),
+                crop: Some(CropRegion {
+                    x: 0,
+                    y: 0,
+                    width: 40,
+                    height: 40,
+                }),
+                ..Default::default()
+            }),
+            (400, 200)
+        );
+    }
+
+    #[cfg(not(feature = "avif-native"))]
+    #[test]
+    fn test_decode_avif_without_decoder() {
+        let avif = b"\0\0\0\x1cftypavif\0\0\0\0avifmif1miaf";
+        let result = decode_image_frames(
+            io::Cursor::new(avif),
+            Path::new("wallpaper.avif"),
+            None,
+            &ExtractionOptions::default(),
+        );
+
+        assert!(matches!(result, Err(Error::UnsupportedFormat { .. })));
+    }
+
+    #[test]
+    fn test_colors_in_images() {
+        let image = RgbaImage::from_pixel(4, 4, image::Rgba([200, 40, 40, 255]));
+        let images = [Cow::Owned(image)];
+        let red = Color::new(PureColor::Red, Srgb::new(210, 30, 30));
+        let cyan = red.get_inverse();
+
+        assert_eq!(colors_in_images(&images, [red, cyan], 30.0, 1), vec![red]);
+        assert!(colors_in_images(&images, [red], 10.0, 1).is_empty());
+    }
+
+    #[test]
+    fn test_legible_yellows() {
+        let mut palette: HashMap<String, SchemeColor> = [
+            ("base00", "F5F5F0"),
+            ("base08", "E0E060"),
+            ("base0A", "F0D040"),
+            ("base0D", "8080F0"),
+        ]
+        .iter()
+        .map(|(slot, hex)| (slot.to_string(), scheme_color(hex.to_string()).unwrap()))
+        .collect();
+        let rgb = |color: &SchemeColor| {
+            let (red, green, blue) = color.rgb;
+
+            Srgb::new(red, green, blue).into_format()
+        };
 
-    // Try again, but now we will accept colors with any saturations, as long long as they're dark but not very dark
-    if dark.is_none() {
-        passes += 1;
-        dark = color_pass(colors, Some(0.012), Some(0.1), None, None);
+        legible_yellows(&mut palette, &SchemeSystem::Base16).unwrap();
+
+        let background = rgb(&palette["base00"]);
+
+        assert!(contrast_ratio(rgb(&palette["base0A"]), background) >= 3.0);
+        assert!(contrast_ratio(rgb(&palette["base08"]), background) >= 3.0);
+        // Not yellow
+        assert_eq!(
+            palette["base0D"],
+            scheme_color("8080F0".to_string()).unwrap()
+        );
+
+        let mut dark = palette.clone();
+
+        dark.insert(
+            "base00".to_string(),
+            scheme_color("101010".to_string()).unwrap(),
+        );
+        dark.insert(
+            "base0A".to_string(),
+            scheme_color("F0D040".to_string()).unwrap(),
+        );
+        legible_yellows(&mut dark, &SchemeSystem::Base16).unwrap();
+
+        assert_eq!(dark["base0A"], scheme_color("F0D040".to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_second_dominant_color() {
+        let colors = [
+            Srgb::new(20, 140, 140),
+            Srgb::new(30, 160, 150),
+            Srgb::new(128, 128, 128),
+            Srgb::new(220, 80, 160),
+        ];
+
+        assert_eq!(second_dominant_color(&colors), Some(colors[3]));
+        assert_eq!(second_dominant_color(&colors[..3]), None);
+        assert_eq!(accent_hues(PureColor::Magenta)[7], MAGENTA_HUE);
+        assert_eq!(accent_hues(PureColor::Brown), ACCENT_HUES);
     }
 
-    // Try again, but now we will accept darker colors too
-    if dark.is_none() {
-        passes += 1;
-        dark = color_pass(colors, None, Some(0.1), None, None);
+    #[test]
+    fn test_generate_contrast_ramp() {
+        let black = Srgb::new(0, 0, 0);
+        let ramp = generate_contrast_ramp(black, Srgb::new(200, 200, 200), &[1.5, 3.0, 4.5, 21.0]);
+
+        assert_eq!(ramp.len(), 5);
+        assert_eq!(ramp[0], black);
+
+        for (color, target) in ramp[1..4].iter().zip([1.5, 3.0, 4.5]) {
+            let ratio = contrast_ratio(color.into_format(), black.into_format());
+
+            assert!((ratio - target).abs() < 0.1, "{} for {}", ratio, target);
+        }
+
+        assert_eq!(ramp[4], Srgb::new(200, 200, 200));
+    }
+
+    #[test]
+    fn test_lock_background() {
+        let mut palette = HashMap::from([
+            (
+                "base00".to_string(),
+                scheme_color("1A1A2E".to_string()).unwrap(),
+            ),
+            (
+                "base07".to_string(),
+                scheme_color("707080".to_string()).unwrap(),
+            ),
+        ]);
+        let black = Srgb::new(0, 0, 0);
+
+        lock_background(&mut palette, black, Srgb::new(112, 112, 128), None);
+
+        let base07 = palette["base07"].rgb;
+
+        assert_eq!(palette["base00"].rgb, (0, 0, 0));
+        assert!(
+            contrast_ratio(
+                Srgb::new(base07.0, base07.1, base07.2).into_format(),
+                black.into_format()
+            ) >= LOCKED_MIN_CONTRAST
+        );
+        assert!(palette.contains_key("base04"));
     }
 
-    // Ok, we didn't find anything usable. So let's just grab the most dominant color (we'll darken it later)
-    if dark.is_none() {
-        passes += 1;
-        dark = colors.first().copied()
+    #[test]
+    fn test_tint_grays() {
+        let mut palette = HashMap::from([
+            (
+                "base00".to_string(),
+                scheme_color("202020".to_string()).unwrap(),
+            ),
+            (
+                "base07".to_string(),
+                scheme_color("E0E0E0".to_string()).unwrap(),
+            ),
+            (
+                "base08".to_string(),
+                scheme_color("E06C75".to_string()).unwrap(),
+            ),
+        ]);
+        let to_lch = |color: &SchemeColor| -> Lch {
+            let (red, green, blue) = color.rgb;
+
+            Lch::from_color(Srgb::new(red, green, blue).into_format::<f32>())
+        };
+
+        tint_grays(&mut palette, 220.0, 8.0).unwrap();
+
+        for slot in ["base00", "base07"] {
+            let lch = to_lch(&palette[slot]);
+
+            assert!((lch.chroma - 8.0).abs() < 1.5, "{}: {:?}", slot, lch);
+            assert!((lch.hue.into_positive_degrees() - 220.0).abs() < 10.0);
+        }
+
+        assert!((to_lch(&palette["base00"]).l - 12.6).abs() < 1.0);
+        assert_eq!(palette["base08"].rgb, (0xE0, 0x6C, 0x75));
+        assert_eq!(
+            dominant_hue(&[Srgb::new(128, 128, 128), Srgb::new(0, 0, 255)]),
+            dominant_hue(&[Srgb::new(0, 0, 255)])
+        );
     }
 
-    if verbose {
-        println!("Passes: {}", passes);
+    #[test]
+    fn test_separate_accents() {
+        let mut palette = HashMap::from([
+            (
+                "base00".to_string(),
+                scheme_color("0A1440".to_string()).unwrap(),
+            ),
+            (
+                "base08".to_string(),
+                scheme_color("E06C75".to_string()).unwrap(),
+            ),
+            (
+                "base0D".to_string(),
+                scheme_color("1E2A5C".to_string()).unwrap(),
+            ),
+        ]);
+        let to_lab = |color: &SchemeColor| -> Lab {
+            let (red, green, blue) = color.rgb;
+
+            Lab::from_color(Srgb::new(red, green, blue).into_format::<f32>())
+        };
+
+        separate_accents(&mut palette, &SchemeSystem::Base16, 20.0).unwrap();
+
+        let background = to_lab(&palette["base00"]);
+        let blue = to_lab(&palette["base0D"]);
+
+        assert_eq!(palette["base08"].rgb, (0xE0, 0x6C, 0x75));
+        assert!(background.difference(blue) >= 20.0);
+        assert!(blue.l > background.l);
     }
 
-    dark.ok_or_else(|| Error::NoColors("Failed to find colors on image".to_string()))
+    #[test]
+    fn test_generate_gradient() {
+        let gradient = generate_gradient(Srgb::new(0, 0, 0), Srgb::new(255, 255, 255), 8);
+
+        assert_eq!(gradient.first(), Some(&Srgb::new(0, 0, 0)));
+        assert_eq!(gradient[1], Srgb::new(36, 36, 36));
+        assert_eq!(gradient.last(), Some(&Srgb::new(255, 255, 255)));
+        assert_eq!(
+            generate_gradient(Srgb::new(0, 0, 0), Srgb::new(255, 255, 255), 1),
+            vec![Srgb::new(0, 0, 0)]
+        );
+    }
 }
@@ -0,0 +1,178 @@
+use std::path::Path;
+
+use ffmpeg_next::{
+    codec::{context::Context as CodecContext, decoder},
+    format::{input, Pixel},
+    media::Type,
+    software::scaling::{context::Context as Scaler, flag::Flags},
+    util::frame::video::Video,
+};
+use image::{
+    error::{DecodingError, ImageFormatHint},
+    ImageError, ImageResult, Limits, RgbaImage,
+};
+
+use crate::VideoFrame;
+
+/// The file extensions of the video files decoded with ffmpeg
+const VIDEO_EXTENSIONS: [&str; 8] = ["avi", "m4v", "mkv", "mov", "mp4", "mpeg", "mpg", "webm"];
+/// ffmpeg's `AV_TIME_BASE`, the units per second of container timestamps
+const TIME_BASE: f64 = 1_000_000.0;
+
+/// Whether a path has the extension of a video file
+pub(crate) fn is_video(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| VIDEO_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+/// Decode one frame of a video file with ffmpeg, or the last frame when the
+/// video ends before it
+///
+/// # Arguments
+/// * `path` - The path of the video file
+/// * `frame` - Which frame to decode
+/// * `max_size` - The longest the longer side can be, scaled down to while
+///   converting the frame to RGBA
+/// * `limits` - The decoding limits, enforced before any frame is decoded
+pub(crate) fn decode_video_frame(
+    path: &Path,
+    frame: VideoFrame,
+    max_size: Option<u32>,
+    limits: &Limits,
+) -> ImageResult<RgbaImage> {
+    ffmpeg_next::init().map_err(video_error)?;
+
+    let mut context = input(&path).map_err(video_error)?;
+    let (stream_index, time_base, mut decoder) = {
+        let stream = context
+            .streams()
+            .best(Type::Video)
+            .ok_or_else(|| video_error(ffmpeg_next::Error::StreamNotFound))?;
+        let decoder = CodecContext::from_parameters(stream.parameters())
+            .and_then(|context| context.decoder().video())
+            .map_err(video_error)?;
+
+        (stream.index(), f64::from(stream.time_base()), decoder)
+    };
+
+    limits.check_dimensions(decoder.width(), decoder.height())?;
+
+    // The timestamp of the wanted frame in the stream's time base, after
+    // seeking to the keyframe before it
+    let target = match frame {
+        VideoFrame::Timestamp(seconds) => {
+            let seconds = seconds.max(0.0);
+            let timestamp = (seconds * TIME_BASE) as i64;
+
+            context.seek(timestamp, ..timestamp).map_err(video_error)?;
+
+            Some((seconds / time_base) as i64)
+        }
+        VideoFrame::Index(_) => None,
+    };
+    let mut decoded = Video::empty();
+    let mut last = None;
+    let mut index = 0;
+    let mut packets = context
+        .packets()
+        .filter(|(stream, _)| stream.index() == stream_index)
+        .map(|(_, packet)| packet);
+    let mut ended = false;
+
+    loop {
+        while decoder.receive_frame(&mut decoded).is_ok() {
+            let wanted = match (frame, target) {
+                (VideoFrame::Index(wanted), _) => index == wanted,
+                (_, Some(target)) => decoded.timestamp().is_none_or(|pts| pts >= target),
+                _ => true,
+            };
+
+            if wanted {
+                return to_rgba_image(&mut decoder, &decoded, max_size);
+            }
+
+            index += 1;
+            last = Some(decoded.clone());
+        }
+
+        match packets.next() {
+            Some(packet) => decoder.send_packet(&packet).map_err(video_error)?,
+            None if !ended => {
+                decoder.send_eof().map_err(video_error)?;
+                ended = true;
+            }
+            None => break,
+        }
+    }
+
+    match last {
+        Some(last) => to_rgba_image(&mut decoder, &last, max_size),
+        None => Err(video_error("The video has no frames")),
+    }
+}
+
+/// Convert a decoded frame to RGBA pixels, scaled down to fit in
+/// `max_size` when it's smaller
+fn to_rgba_image(
+    decoder: &mut decoder::Video,
+    frame: &Video,
+    max_size: Option<u32>,
+) -> ImageResult<RgbaImage> {
+    let longer_side = frame.width().max(frame.height());
+    let (width, height) = match max_size {
+        Some(max_size) if max_size < longer_side => {
+            let scale = |side: u32| {
+                ((u64::from(side) * u64::from(max_size)) / u64::from(longer_side)).max(1) as u32
+            };
+
+            (scale(frame.width()), scale(frame.height()))
+        }
+        _ => (frame.width(), frame.height()),
+    };
+    let mut scaler = Scaler::get(
+        decoder.format(),
+        frame.width(),
+        frame.height(),
+        Pixel::RGBA,
+        width,
+        height,
+        Flags::BILINEAR,
+    )
+    .map_err(video_error)?;
+    let mut rgba = Video::empty();
+
+    scaler.run(frame, &mut rgba).map_err(video_error)?;
+
+    // Rows can be padded beyond the image's width
+    let pixels = rgba
+        .data(0)
+        .chunks(rgba.stride(0))
+        .take(height as usize)
+        .flat_map(|row| &row[..width as usize * 4])
+        .copied()
+        .collect();
+
+    RgbaImage::from_raw(width, height, pixels)
+        .ok_or_else(|| video_error("Unexpected video frame size"))
+}
+
+fn video_error(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> ImageError {
+    ImageError::Decoding(DecodingError::new(
+        ImageFormatHint::Name("video".to_string()),
+        err,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_video() {
+        assert!(is_video(Path::new("wallpaper.mp4")));
+        assert!(is_video(Path::new("clip.WebM")));
+        assert!(!is_video(Path::new("wallpaper.png")));
+        assert!(!is_video(Path::new("mp4")));
+    }
+}
@@ -0,0 +1,180 @@
+use std::fmt::Write;
+
+use tinted_builder::{Base16Scheme, SchemeVariant};
+
+use crate::{
+    export::{json_string, slot_hex},
+    xterm::ansi_slots,
+    Error,
+};
+
+/// The workbench colors and the slots they use
+const COLORS: [(&str, &str); 24] = [
+    ("editor.background", "base00"),
+    ("editor.foreground", "base05"),
+    ("editor.lineHighlightBackground", "base01"),
+    ("editor.selectionBackground", "base02"),
+    ("editor.findMatchHighlightBackground", "base02"),
+    ("editorCursor.foreground", "base05"),
+    ("editorLineNumber.foreground", "base03"),
+    ("editorLineNumber.activeForeground", "base04"),
+    ("editorWhitespace.foreground", "base02"),
+    ("editorError.foreground", "base08"),
+    ("editorWarning.foreground", "base0A"),
+    ("focusBorder", "base0D"),
+    ("activityBar.background", "base01"),
+    ("activityBar.foreground", "base05"),
+    ("sideBar.background", "base01"),
+    ("sideBar.foreground", "base04"),
+    ("titleBar.activeBackground", "base01"),
+    ("titleBar.activeForeground", "base05"),
+    ("statusBar.background", "base01"),
+    ("statusBar.foreground", "base04"),
+    ("tab.activeBackground", "base00"),
+    ("tab.inactiveBackground", "base01"),
+    ("panel.background", "base00"),
+    ("terminal.foreground", "base05"),
+];
+/// The names of the terminal's ANSI colors, black to bright white
+const TERMINAL_COLORS: [&str; 16] = [
+    "Black",
+    "Red",
+    "Green",
+    "Yellow",
+    "Blue",
+    "Magenta",
+    "Cyan",
+    "White",
+    "BrightBlack",
+    "BrightRed",
+    "BrightGreen",
+    "BrightYellow",
+    "BrightBlue",
+    "BrightMagenta",
+    "BrightCyan",
+    "BrightWhite",
+];
+/// The TextMate scopes of every token color, the slot they use and their
+/// font style, after the base16 styling guidelines
+const TOKEN_COLORS: [(&str, &str, &str); 10] = [
+    ("comment, punctuation.definition.comment", "base03", "italic"),
+    (
+        "variable, entity.name.tag, markup.list, markup.deleted",
+        "base08",
+        "",
+    ),
+    (
+        "constant.numeric, constant.language, constant.character, entity.other.attribute-name, markup.underline.link",
+        "base09",
+        "",
+    ),
+    (
+        "entity.name.type, entity.name.class, support.class, markup.bold",
+        "base0A",
+        "",
+    ),
+    (
+        "string, entity.other.inherited-class, markup.inline.raw, markup.inserted",
+        "base0B",
+        "",
+    ),
+    (
+        "support, string.regexp, constant.character.escape, markup.quote",
+        "base0C",
+        "",
+    ),
+    (
+        "entity.name.function, support.function, meta.function-call, markup.heading",
+        "base0D",
+        "",
+    ),
+    (
+        "keyword, storage, entity.name.selector, markup.italic, markup.changed",
+        "base0E",
+        "",
+    ),
+    ("invalid.deprecated, punctuation.section.embedded", "base0F", ""),
+    ("punctuation, meta.brace", "base05", ""),
+];
+
+/// Render a minimal VS Code color theme: the editor and workbench colors
+/// from the grays, the terminal's ANSI colors and token colors mapped from
+/// base08 to base0F
+///
+/// # Arguments
+/// * `scheme` - A reference to the generated Base16Scheme
+pub fn vscode_theme(scheme: &Base16Scheme) -> Result<String, Error> {
+    let kind = match scheme.variant {
+        SchemeVariant::Light => "light",
+        _ => "dark",
+    };
+    let mut theme = format!(
+        "{{\n  \"name\": {},\n  \"type\": \"{}\",\n  \"colors\": {{\n",
+        json_string(&scheme.name),
+        kind
+    );
+    let terminal = TERMINAL_COLORS
+        .iter()
+        .zip(ansi_slots(&scheme.system))
+        .map(|(name, slot)| (format!("terminal.ansi{}", name), slot));
+    let colors: Vec<(String, &str)> = COLORS
+        .iter()
+        .map(|(key, slot)| (key.to_string(), *slot))
+        .chain(terminal)
+        .collect();
+
+    for (index, (key, slot)) in colors.iter().enumerate() {
+        let separator = if index + 1 < colors.len() { "," } else { "" };
+        let _ = writeln!(
+            theme,
+            "    \"{}\": \"{}\"{}",
+            key,
+            slot_hex(scheme, slot)?,
+            separator
+        );
+    }
+
+    theme.push_str("  },\n  \"tokenColors\": [\n");
+
+    for (index, (scope, slot, font_style)) in TOKEN_COLORS.iter().enumerate() {
+        let separator = if index + 1 < TOKEN_COLORS.len() {
+            ","
+        } else {
+            ""
+        };
+        let _ = writeln!(
+            theme,
+            "    {{ \"scope\": \"{}\", \"settings\": {{ \"foreground\": \"{}\", \"fontStyle\": \"{}\" }} }}{}",
+            scope,
+            slot_hex(scheme, slot)?,
+            font_style,
+            separator
+        );
+    }
+
+    theme.push_str("  ]\n}\n");
+
+    Ok(theme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::test_scheme;
+    use tinted_builder::SchemeSystem;
+
+    #[test]
+    fn test_vscode_theme() {
+        let theme = vscode_theme(&test_scheme(SchemeSystem::Base16)).unwrap();
+
+        assert!(theme.starts_with(
+            "{\n  \"name\": \"Test \\\"Scheme\\\"\",\n  \"type\": \"dark\",\n  \"colors\": {\n    \"editor.background\": \"#000000\",\n"
+        ));
+        assert!(theme.contains("    \"terminal.ansiRed\": \"#ff0000\",\n"));
+        assert!(theme.contains("    \"terminal.ansiBrightWhite\": \"#ffffff\"\n  },\n"));
+        assert!(theme.contains(
+            "    { \"scope\": \"comment, punctuation.definition.comment\", \"settings\": { \"foreground\": \"#6d6d6d\", \"fontStyle\": \"italic\" } },\n"
+        ));
+        assert!(theme.ends_with("\"fontStyle\": \"\" } }\n  ]\n}\n"));
+    }
+}
@@ -0,0 +1,465 @@
+use std::{env, fs, path::PathBuf, process::Command};
+
+use crate::{extract_scheme_from_image, Error, Extraction, SchemeParams};
+
+/// The `reg query` arguments reading the wallpaper's path
+#[cfg(not(windows))]
+const REGISTRY_QUERY: [&str; 4] = ["query", "HKCU\\Control Panel\\Desktop", "/v", "WallPaper"];
+/// Where Windows keeps a copy of the wallpaper, relative to `%APPDATA%`
+const TRANSCODED_WALLPAPER: &str = "Microsoft/Windows/Themes/TranscodedWallpaper";
+/// The output of swaybg's wallpapers when none is given
+const ALL_OUTPUTS: &str = "*";
+
+/// The wallpaper shown on an output of a Wayland compositor
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OutputWallpaper {
+    /// The name of the output, e.g. `DP-1`, or `*` for every output
+    pub output: String,
+    pub path: PathBuf,
+}
+
+/// The scheme extracted from the wallpaper of an output
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OutputExtraction {
+    pub wallpaper: OutputWallpaper,
+    pub extraction: Extraction,
+}
+
+/// Find the path of the current desktop wallpaper, so a scheme can be
+/// generated from it without looking it up by hand
+///
+/// On macOS, System Events is asked for the picture of the current desktop
+/// with `osascript`. On Windows, `SystemParametersInfoW` is asked for the
+/// desktop wallpaper, and from WSL the `WallPaper` value of the
+/// `HKCU\Control Panel\Desktop` registry key is read with `reg.exe`. Both
+/// fall back to the copy Windows keeps of slideshow and Spotlight
+/// wallpapers. On Hyprland and Sway, the wallpaper of the first output from
+/// `output_wallpapers` is used. Other platforms return `Error::Wallpaper`.
+pub fn current_wallpaper() -> Result<PathBuf, Error> {
+    if cfg!(target_os = "macos") {
+        return macos_wallpaper();
+    }
+
+    #[cfg(windows)]
+    return windows_wallpaper();
+
+    #[cfg(not(windows))]
+    return linux_wallpaper();
+}
+
+/// Find the wallpaper of a Wayland compositor, or of Windows from WSL
+#[cfg(not(windows))]
+fn linux_wallpaper() -> Result<PathBuf, Error> {
+    if is_hyprland() || is_sway() {
+        return Ok(output_wallpapers()?.swap_remove(0).path);
+    }
+
+    if env::var_os("WSL_DISTRO_NAME").is_some() {
+        return wsl_wallpaper();
+    }
+
+    Err(Error::Wallpaper {
+        message: "Wallpaper detection isn't supported on this platform".to_string(),
+        source: None,
+    })
+}
+
+/// Ask System Events for the picture of the desktop the frontmost window is
+/// on. Dynamic wallpapers return their `.heic` file
+fn macos_wallpaper() -> Result<PathBuf, Error> {
+    let output = command_output(Command::new("osascript").args([
+        "-e",
+        "tell application \"System Events\" to get picture of current desktop",
+    ]))?;
+
+    wallpaper_path(&output)
+}
+
+/// Find the wallpaper of every output of the Wayland compositor: on
+/// Hyprland, hyprpaper is asked with `hyprctl hyprpaper listactive`, and on
+/// Sway, the arguments of the running swaybg processes are read. Other
+/// compositors return `Error::Wallpaper`
+pub fn output_wallpapers() -> Result<Vec<OutputWallpaper>, Error> {
+    let wallpapers = if is_hyprland() {
+        let output = command_output(Command::new("hyprctl").args(["hyprpaper", "listactive"]))?;
+
+        hyprpaper_wallpapers(&output)
+    } else if is_sway() {
+        swaybg_wallpapers()?
+    } else {
+        return Err(Error::Wallpaper {
+            message: "Output wallpapers are only detected on Hyprland and Sway".to_string(),
+            source: None,
+        });
+    };
+
+    if wallpapers.is_empty() {
+        return Err(Error::Wallpaper {
+            message: "No wallpaper is set".to_string(),
+            source: None,
+        });
+    }
+
+    Ok(wallpapers)
+}
+
+/// Extract a scheme from the wallpaper of every output found by
+/// `output_wallpapers`, e.g. for per-monitor themes
+///
+/// # Arguments
+/// * `params` - The SchemeParams used for every scheme. `image_path` is
+///   replaced by each wallpaper, and the output's name is added to the
+///   name and the slug, e.g. `Wallpaper (DP-1)` and `wallpaper-dp-1`,
+///   unless the wallpaper is shown on every output
+pub fn extract_schemes_by_output(params: SchemeParams) -> Result<Vec<OutputExtraction>, Error> {
+    params.validate()?;
+
+    output_wallpapers()?
+        .into_iter()
+        .map(|wallpaper| {
+            let mut output_params = params.clone();
+
+            output_params.image_path = wallpaper.path.clone();
+
+            if wallpaper.output != ALL_OUTPUTS {
+                output_params.name = format!("{} ({})", params.name, wallpaper.output);
+                output_params.slug = format!("{}-{}", params.slug, output_slug(&wallpaper.output));
+            }
+
+            extract_scheme_from_image(output_params).map(|extraction| OutputExtraction {
+                wallpaper,
+                extraction,
+            })
+        })
+        .collect()
+}
+
+fn is_hyprland() -> bool {
+    env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some()
+}
+
+fn is_sway() -> bool {
+    env::var_os("SWAYSOCK").is_some()
+}
+
+/// Read the `<output> = <path>` lines of `hyprctl hyprpaper listactive`
+fn hyprpaper_wallpapers(output: &str) -> Vec<OutputWallpaper> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once(" = "))
+        .map(|(output, path)| OutputWallpaper {
+            output: output.trim().to_string(),
+            path: PathBuf::from(path.trim()),
+        })
+        .collect()
+}
+
+/// Read the wallpapers of the running swaybg processes from their command
+/// lines in `/proc`
+fn swaybg_wallpapers() -> Result<Vec<OutputWallpaper>, Error> {
+    let processes = fs::read_dir("/proc").map_err(|err| Error::Wallpaper {
+        message: "Unable to list processes".to_string(),
+        source: Some(err),
+    })?;
+
+    Ok(processes
+        .flatten()
+        .filter(|process| {
+            fs::read_to_string(process.path().join("comm"))
+                .is_ok_and(|comm| comm.trim() == "swaybg")
+        })
+        .filter_map(|process| fs::read(process.path().join("cmdline")).ok())
+        .flat_map(|cmdline| {
+            let args: Vec<String> = cmdline
+                .split(|byte| *byte == 0)
+                .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                .collect();
+
+            swaybg_args_wallpapers(&args)
+        })
+        .collect())
+}
+
+/// Pair every `--image` of swaybg's arguments with the `--output` before
+/// it, as swaybg does
+fn swaybg_args_wallpapers(args: &[String]) -> Vec<OutputWallpaper> {
+    let mut wallpapers = Vec::new();
+    let mut output = ALL_OUTPUTS.to_string();
+    let mut args = args.iter().skip(1);
+
+    while let Some(arg) = args.next() {
+        let (flag, value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut next_value = || value.clone().or_else(|| args.next().cloned());
+
+        match flag {
+            "-o" | "--output" => output = next_value().unwrap_or_default(),
+            "-i" | "--image" => {
+                if let Some(path) = next_value() {
+                    wallpapers.push(OutputWallpaper {
+                        output: output.clone(),
+                        path: PathBuf::from(path),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    wallpapers
+}
+
+/// Turn an output's name into a part of a slug, e.g. `DP-1` into `dp-1`
+fn output_slug(output: &str) -> String {
+    output
+        .to_ascii_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Ask Windows for the desktop wallpaper, or use its transcoded copy of
+/// it, which has no extension but is a JPEG
+#[cfg(windows)]
+fn windows_wallpaper() -> Result<PathBuf, Error> {
+    use std::{ffi::OsString, io, os::windows::ffi::OsStringExt};
+    use windows_sys::Win32::{
+        Foundation::MAX_PATH,
+        UI::WindowsAndMessaging::{SystemParametersInfoW, SPI_GETDESKWALLPAPER},
+    };
+
+    let mut buffer = [0u16; MAX_PATH as usize];
+    // SAFETY: SPI_GETDESKWALLPAPER writes a null-terminated path of at most
+    // as many UTF-16 units as the buffer holds
+    let found = unsafe {
+        SystemParametersInfoW(
+            SPI_GETDESKWALLPAPER,
+            buffer.len() as u32,
+            buffer.as_mut_ptr().cast(),
+            0,
+        )
+    };
+
+    if found == 0 {
+        return Err(Error::Wallpaper {
+            message: "Unable to get the wallpaper".to_string(),
+            source: Some(io::Error::last_os_error()),
+        });
+    }
+
+    let length = buffer
+        .iter()
+        .position(|unit| *unit == 0)
+        .unwrap_or(buffer.len());
+
+    if length > 0 {
+        return Ok(PathBuf::from(OsString::from_wide(&buffer[..length])));
+    }
+
+    let transcoded = env::var_os("APPDATA")
+        .map(|app_data| PathBuf::from(app_data).join(TRANSCODED_WALLPAPER))
+        .filter(|path| path.is_file());
+
+    transcoded.ok_or_else(|| Error::Wallpaper {
+        message: "No wallpaper is set".to_string(),
+        source: None,
+    })
+}
+
+/// Read the wallpaper from the Windows registry with `reg.exe` and convert
+/// its path to a Linux one with `wslpath`
+#[cfg(not(windows))]
+fn wsl_wallpaper() -> Result<PathBuf, Error> {
+    let output = command_output(Command::new("reg.exe").args(REGISTRY_QUERY))?;
+    let path = match registry_value(&output) {
+        Some(path) => path.to_string(),
+        None => {
+            let app_data = command_output(Command::new("cmd.exe").args(["/c", "echo %APPDATA%"]))?;
+
+            format!(
+                "{}\\{}",
+                app_data.trim(),
+                TRANSCODED_WALLPAPER.replace('/', "\\")
+            )
+        }
+    };
+    let output = command_output(Command::new("wslpath").arg("-u").arg(path))?;
+
+    wallpaper_path(&output)
+}
+
+/// Get the data of a `REG_SZ` value from the output of `reg query`, or
+/// `None` when it's empty
+#[cfg(not(windows))]
+fn registry_value(output: &str) -> Option<&str> {
+    output
+        .lines()
+        .find_map(|line| line.split_once("REG_SZ"))
+        .map(|(_, value)| value.trim())
+        .filter(|value| !value.is_empty())
+}
+
+/// Run a command and return its standard output
+fn command_output(command: &mut Command) -> Result<String, Error> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let output = command.output().map_err(|err| Error::Wallpaper {
+        message: format!("Unable to run {}", program),
+        source: Some(err),
+    })?;
+
+    if !output.status.success() {
+        return Err(Error::Wallpaper {
+            message: format!(
+                "{} exited with {}: {}",
+                program,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            source: None,
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Read a wallpaper path printed by a command, which may be quoted or a
+/// `file://` URI
+fn wallpaper_path(output: &str) -> Result<PathBuf, Error> {
+    let path = output.trim().trim_matches(|c| c == '\'' || c == '"');
+    let path = match path.strip_prefix("file://") {
+        Some(path) => percent_decode(path),
+        None => path.to_string(),
+    };
+
+    if path.is_empty() {
+        return Err(Error::Wallpaper {
+            message: "No wallpaper is set".to_string(),
+            source: None,
+        });
+    }
+
+    Ok(PathBuf::from(path))
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let byte = value
+            .get(index + 1..index + 3)
+            .filter(|_| bytes[index] == b'%')
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match byte {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wallpaper_path() {
+        assert_eq!(
+            wallpaper_path("/Users/me/Pictures/Sunset.jpg\n").unwrap(),
+            PathBuf::from("/Users/me/Pictures/Sunset.jpg")
+        );
+        assert_eq!(
+            wallpaper_path("'file:///home/me/My%20Pictures/%C3%A9t%C3%A9.png'\n").unwrap(),
+            PathBuf::from("/home/me/My Pictures/été.png")
+        );
+        assert_eq!(
+            wallpaper_path("file:///tmp/100%.png").unwrap(),
+            PathBuf::from("/tmp/100%.png")
+        );
+        assert!(matches!(wallpaper_path("\n"), Err(Error::Wallpaper { .. })));
+
+        let err = command_output(&mut Command::new("tinted-scheme-extractor-missing")).unwrap_err();
+
+        assert!(matches!(
+            err,
+            Error::Wallpaper {
+                source: Some(_),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_registry_value() {
+        let output = "\r\nHKEY_CURRENT_USER\\Control Panel\\Desktop\r\n    WallPaper    REG_SZ    C:\\Users\\me\\Pictures\\My wall.jpg\r\n\r\n";
+
+        assert_eq!(
+            registry_value(output),
+            Some("C:\\Users\\me\\Pictures\\My wall.jpg")
+        );
+        assert_eq!(registry_value("    WallPaper    REG_SZ    \r\n"), None);
+    }
+
+    #[test]
+    fn test_output_wallpapers() {
+        let wallpaper = |output: &str, path: &str| OutputWallpaper {
+            output: output.to_string(),
+            path: PathBuf::from(path),
+        };
+
+        assert_eq!(
+            hyprpaper_wallpapers("DP-1 = /home/me/left.png\nHDMI-A-1 = /home/me/right.jpg\n"),
+            vec![
+                wallpaper("DP-1", "/home/me/left.png"),
+                wallpaper("HDMI-A-1", "/home/me/right.jpg")
+            ]
+        );
+        assert!(hyprpaper_wallpapers("no wallpapers active\n").is_empty());
+
+        let args: Vec<String> = [
+            "swaybg",
+            "-o",
+            "*",
+            "-i",
+            "/wall.png",
+            "-m",
+            "fill",
+            "-o",
+            "eDP-1",
+            "--image=/laptop.png",
+            "--output",
+            "DP-2",
+            "-c",
+            "#000000",
+            "",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+
+        assert_eq!(
+            swaybg_args_wallpapers(&args),
+            vec![
+                wallpaper("*", "/wall.png"),
+                wallpaper("eDP-1", "/laptop.png")
+            ]
+        );
+        assert_eq!(output_slug("HDMI-A-1"), "hdmi-a-1");
+        assert_eq!(output_slug("Dell Inc. U2720Q"), "dell-inc-u2720q");
+    }
+}
@@ -0,0 +1,53 @@
+use std::{path::PathBuf, str::FromStr};
+
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    create_scheme_from_rgba, Error, ExtractionOptions, SchemeParams, SchemeSystem, SchemeVariant,
+};
+
+/// Create a scheme from the RGBA pixels of e.g. a canvas `ImageData`,
+/// returning the scheme as YAML
+///
+/// # Arguments
+/// * `width` - The width of the image in pixels
+/// * `height` - The height of the image in pixels
+/// * `pixels` - Row-major RGBA bytes, 4 per pixel
+/// * `name` - The scheme name
+/// * `slug` - The scheme slug
+/// * `author` - The scheme author
+/// * `system` - `base16` or `base24`
+/// * `variant` - `dark` or `light`
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen(js_name = createSchemeFromRgba)]
+pub fn create_scheme_from_rgba_js(
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    name: String,
+    slug: String,
+    author: String,
+    system: String,
+    variant: String,
+) -> Result<String, JsError> {
+    let params = SchemeParams {
+        image_path: PathBuf::new(),
+        author,
+        description: None,
+        name,
+        slug,
+        system: SchemeSystem::from_str(&system).map_err(|_| Error::UnsupportedSchemeSystem {
+            system: system.clone(),
+        })?,
+        variant: SchemeVariant::from_str(&variant).map_err(|_| {
+            Error::UnsupportedSchemeVariant {
+                variant: variant.clone(),
+            }
+        })?,
+        verbose: false,
+        options: ExtractionOptions::default(),
+    };
+    let scheme = create_scheme_from_rgba(width, height, pixels, params)?;
+
+    Ok(scheme.to_string())
+}
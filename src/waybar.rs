@@ -0,0 +1,69 @@
+use std::fmt::Write;
+
+use tinted_builder::Base16Scheme;
+
+use crate::{
+    adjust::scheme_slots,
+    export::{single_line, slot_hex},
+    Error,
+};
+
+/// The named colors derived from the slots, with GTK CSS expressions so
+/// they follow edits to the slots
+const DERIVED: [(&str, &str); 10] = [
+    ("background", "@base00"),
+    ("foreground", "@base05"),
+    ("border", "@base02"),
+    ("accent", "@base0D"),
+    ("hover", "mix(@base01, @base0D, 0.25)"),
+    ("success", "@base0B"),
+    ("warning", "@base0A"),
+    ("warning_bg", "mix(@base00, @base0A, 0.25)"),
+    ("critical", "@base08"),
+    ("critical_bg", "mix(@base00, @base08, 0.25)"),
+];
+
+/// Render a Waybar `colors.css` defining a color per slot and derived
+/// colors for hovered modules and warning and critical states, to
+/// `@import` from `style.css`
+///
+/// # Arguments
+/// * `scheme` - A reference to the generated Base16Scheme
+pub fn waybar_colors(scheme: &Base16Scheme) -> Result<String, Error> {
+    let mut css = format!(
+        "/* {} by {} */\n",
+        single_line(&scheme.name).replace("*/", "* /"),
+        single_line(&scheme.author).replace("*/", "* /")
+    );
+
+    for slot in scheme_slots(&scheme.system) {
+        let _ = writeln!(css, "@define-color {} {};", slot, slot_hex(scheme, &slot)?);
+    }
+
+    css.push('\n');
+
+    for (name, value) in DERIVED {
+        let _ = writeln!(css, "@define-color {} {};", name, value);
+    }
+
+    Ok(css)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::test_scheme;
+    use tinted_builder::SchemeSystem;
+
+    #[test]
+    fn test_waybar_colors() {
+        let css = waybar_colors(&test_scheme(SchemeSystem::Base16)).unwrap();
+
+        assert!(css.starts_with("/* Test \"Scheme\" by Author */\n@define-color base00 #000000;\n"));
+        assert!(
+            css.contains("@define-color base0F #804000;\n\n@define-color background @base00;\n")
+        );
+        assert!(css.contains("@define-color hover mix(@base01, @base0D, 0.25);\n"));
+        assert!(css.ends_with("@define-color critical_bg mix(@base00, @base08, 0.25);\n"));
+    }
+}
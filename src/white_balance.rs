@@ -0,0 +1,153 @@
+use std::borrow::Cow;
+
+use image::RgbaImage;
+use palette::{LinSrgb, Srgb};
+
+use crate::options::WhiteBalance;
+
+/// The share of the brightest values of each channel left out when looking
+/// for white, so a few clipped pixels don't decide it
+const WHITE_PATCH_PERCENTILE: f64 = 0.99;
+
+/// Estimate the color of the light in linear sRGB, from the opaque pixels
+/// of the images, or `None` when they have none or it's black
+///
+/// # Arguments
+/// * `images` - The prepared images
+/// * `method` - How the color of the light is estimated
+pub(crate) fn estimate_light(images: &[Cow<RgbaImage>], method: WhiteBalance) -> Option<LinSrgb> {
+    let to_linear = linear_table();
+    let mut histograms = [[0u64; 256]; 3];
+
+    for pixel in images
+        .iter()
+        .flat_map(|image| image.pixels())
+        .filter(|pixel| pixel[3] > 0)
+    {
+        for (histogram, value) in histograms.iter_mut().zip(pixel.0) {
+            histogram[value as usize] += 1;
+        }
+    }
+
+    let count: u64 = histograms[0].iter().sum();
+
+    if count == 0 {
+        return None;
+    }
+
+    let [red, green, blue] = histograms.map(|histogram| match method {
+        WhiteBalance::GrayWorld => {
+            histogram
+                .iter()
+                .zip(to_linear)
+                .map(|(count, value)| *count as f64 * value as f64)
+                .sum::<f64>() as f32
+                / count as f32
+        }
+        WhiteBalance::WhitePatch => {
+            let threshold = (count as f64 * WHITE_PATCH_PERCENTILE).ceil() as u64;
+            let mut sum = 0;
+            let value = histogram
+                .iter()
+                .position(|count| {
+                    sum += count;
+                    sum >= threshold
+                })
+                .unwrap_or(255);
+
+            to_linear[value]
+        }
+    });
+
+    (red.max(green).max(blue) > 0.0).then(|| LinSrgb::new(red, green, blue))
+}
+
+/// Scale the channels of every pixel in linear light so the light becomes
+/// gray, keeping its luminance. Alpha is kept
+///
+/// # Arguments
+/// * `image` - The image to correct
+/// * `light` - The color of the light, from `estimate_light`
+pub(crate) fn correct_white_balance(image: &RgbaImage, light: LinSrgb) -> RgbaImage {
+    let to_linear = linear_table();
+    let luminance = 0.2126 * light.red + 0.7152 * light.green + 0.0722 * light.blue;
+    // Channels the light has almost none of are not amplified without
+    // bounds
+    let gains = [light.red, light.green, light.blue]
+        .map(|channel| luminance / channel.max(luminance / 8.0).max(f32::EPSILON));
+    let mut corrected = image.clone();
+
+    for pixel in corrected.pixels_mut() {
+        let [red, green, blue] = [0, 1, 2]
+            .map(|channel| (to_linear[pixel[channel] as usize] * gains[channel]).clamp(0.0, 1.0));
+        let color: Srgb<u8> = Srgb::from_linear(LinSrgb::new(red, green, blue));
+
+        pixel.0[..3].copy_from_slice(&[color.red, color.green, color.blue]);
+    }
+
+    corrected
+}
+
+/// The color cast of a light, as the 8-bit sRGB color of the light with
+/// its brightest channel at 255, for `Diagnostics::color_cast`
+pub(crate) fn color_cast(light: LinSrgb) -> Srgb<u8> {
+    let brightest = light.red.max(light.green).max(light.blue).max(f32::EPSILON);
+
+    Srgb::from_linear(LinSrgb::new(
+        light.red / brightest,
+        light.green / brightest,
+        light.blue / brightest,
+    ))
+}
+
+/// The linear value of every 8-bit sRGB value
+fn linear_table() -> [f32; 256] {
+    std::array::from_fn(|value| {
+        Srgb::new(value as u8, 0, 0)
+            .into_format::<f32>()
+            .into_linear()
+            .red
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn test_correct_white_balance() {
+        // Gray and blue surfaces under a warm light
+        let image = RgbaImage::from_fn(10, 10, |x, _| {
+            if x < 5 {
+                Rgba([200, 160, 120, 255])
+            } else {
+                Rgba([60, 70, 110, 255])
+            }
+        });
+        let images = [Cow::Borrowed(&image)];
+        let light = estimate_light(&images, WhiteBalance::WhitePatch).unwrap();
+        let cast = color_cast(light);
+
+        assert_eq!(cast.red, 255);
+        assert!(cast.blue < cast.green && cast.green < cast.red);
+
+        let corrected = correct_white_balance(&image, light);
+        let [red, green, blue, alpha] = corrected.get_pixel(0, 0).0;
+
+        // The brightest surface becomes gray
+        assert!(red.abs_diff(green) <= 2 && green.abs_diff(blue) <= 2);
+        assert_eq!(alpha, 255);
+        assert!(corrected.get_pixel(9, 0)[2] > corrected.get_pixel(9, 0)[0]);
+
+        let gray = RgbaImage::from_pixel(4, 4, Rgba([120, 120, 120, 255]));
+        let light = estimate_light(&[Cow::Borrowed(&gray)], WhiteBalance::GrayWorld).unwrap();
+
+        assert_eq!(color_cast(light), Srgb::new(255, 255, 255));
+        assert_eq!(correct_white_balance(&gray, light), gray);
+        assert_eq!(
+            estimate_light(&[Cow::Owned(RgbaImage::new(2, 2))], WhiteBalance::GrayWorld),
+            None
+        );
+    }
+}
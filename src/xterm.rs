@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+
+use palette::{color_difference::Ciede2000, FromColor, Lab, Srgb};
+use tinted_builder::{Base16Scheme, SchemeSystem};
+
+use crate::Error;
+
+/// The channel values of the xterm 6x6x6 color cube
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// A scheme mapped onto the xterm 256-color palette, for terminals without
+/// truecolor support
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XtermPalette {
+    /// The nearest xterm color index of every slot, e.g. `base08`
+    pub slots: BTreeMap<String, u8>,
+    /// The xterm color index for each of the 16 ANSI colors, black to
+    /// bright white
+    pub ansi: [u8; 16],
+}
+
+/// Get the color of an xterm 256-color palette index, for the color cube
+/// (16 to 231) and the grayscale ramp (232 to 255). The first 16 colors are
+/// set by the terminal, so `None` is returned for them
+///
+/// # Arguments
+/// * `index` - The xterm color index
+pub fn xterm_color(index: u8) -> Option<Srgb<u8>> {
+    match index {
+        0..=15 => None,
+        16..=231 => {
+            let cube_index = (index - 16) as usize;
+
+            Some(Srgb::new(
+                CUBE_LEVELS[cube_index / 36],
+                CUBE_LEVELS[cube_index / 6 % 6],
+                CUBE_LEVELS[cube_index % 6],
+            ))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+
+            Some(Srgb::new(gray, gray, gray))
+        }
+    }
+}
+
+/// Get the xterm color index, from 16 to 255, that looks closest to a color
+///
+/// # Arguments
+/// * `color` - The color to find the nearest xterm color for
+pub fn nearest_xterm_index(color: Srgb<u8>) -> u8 {
+    let target: Lab = Lab::from_color(color.into_format::<f32>());
+
+    (16..=255)
+        .filter_map(|index| xterm_color(index).map(|xterm| (index, xterm)))
+        .map(|(index, xterm)| {
+            let lab: Lab = Lab::from_color(xterm.into_format::<f32>());
+
+            (index, target.difference(lab))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(index, _)| index)
+        .unwrap_or(16)
+}
+
+/// Get the slots the 16 ANSI colors use, black to bright white
+///
+/// # Arguments
+/// * `system` - The scheme system. Base24 schemes have their own bright
+///   colors, in base10 to base17 in the same order as base08 to base0F
+pub fn ansi_slots(system: &SchemeSystem) -> [&'static str; 16] {
+    match system {
+        SchemeSystem::Base24 => [
+            "base00", "base08", "base0B", "base0A", "base0D", "base0E", "base0C", "base05",
+            "base02", "base10", "base13", "base12", "base15", "base16", "base14", "base07",
+        ],
+        _ => [
+            "base00", "base08", "base0B", "base0A", "base0D", "base0E", "base0C", "base05",
+            "base03", "base08", "base0B", "base0A", "base0D", "base0E", "base0C", "base07",
+        ],
+    }
+}
+
+/// Map a scheme onto the xterm 256-color palette
+///
+/// # Arguments
+/// * `scheme` - A reference to the generated Base16Scheme
+pub fn xterm_palette(scheme: &Base16Scheme) -> Result<XtermPalette, Error> {
+    let slots: BTreeMap<String, u8> = scheme
+        .palette
+        .iter()
+        .map(|(slot, color)| {
+            let (red, green, blue) = color.rgb;
+
+            (
+                slot.clone(),
+                nearest_xterm_index(Srgb::new(red, green, blue)),
+            )
+        })
+        .collect();
+    let mut ansi = [0; 16];
+
+    for (index, slot) in ansi_slots(&scheme.system).iter().enumerate() {
+        ansi[index] = *slots.get(*slot).ok_or_else(|| Error::UnknownSlot {
+            slot: slot.to_string(),
+        })?;
+    }
+
+    Ok(XtermPalette { slots, ansi })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{extract_scheme_from_dynamic_image, SchemeParams};
+    use image::{DynamicImage, Rgb, RgbImage};
+    use palette::{Hsl, Hsv};
+    use std::path::PathBuf;
+    use tinted_builder::SchemeVariant;
+
+    #[test]
+    fn test_xterm_color() {
+        assert_eq!(xterm_color(15), None);
+        assert_eq!(xterm_color(16), Some(Srgb::new(0, 0, 0)));
+        assert_eq!(xterm_color(196), Some(Srgb::new(255, 0, 0)));
+        assert_eq!(xterm_color(231), Some(Srgb::new(255, 255, 255)));
+        assert_eq!(xterm_color(244), Some(Srgb::new(128, 128, 128)));
+    }
+
+    #[test]
+    fn test_ansi_slots_base24_extraction() {
+        let image = DynamicImage::ImageRgb8(RgbImage::from_fn(120, 40, |x, y| {
+            let hsv = Hsv::new(x as f32 * 3.0, 0.8, 0.4 + y as f32 / 80.0);
+            let color: Srgb<u8> = Srgb::from_color(hsv).into_format();
+
+            Rgb([color.red, color.green, color.blue])
+        }));
+        let params = SchemeParams {
+            image_path: PathBuf::new(),
+            author: "Author".to_string(),
+            description: None,
+            name: "Rainbow".to_string(),
+            slug: "rainbow".to_string(),
+            system: SchemeSystem::Base24,
+            variant: SchemeVariant::Dark,
+            verbose: false,
+            options: Default::default(),
+        };
+        let scheme = extract_scheme_from_dynamic_image(&image, params)
+            .unwrap()
+            .scheme;
+        let slots = ansi_slots(&scheme.system);
+        let hue = |slot: &str| {
+            let (red, green, blue) = scheme.palette[slot].rgb;
+
+            Hsl::from_color(Srgb::new(red, green, blue).into_format::<f32>())
+                .hue
+                .into_positive_degrees()
+        };
+
+        for index in 1..7 {
+            let difference = (hue(slots[index]) - hue(slots[index + 8])).abs();
+
+            assert!(
+                difference.min(360.0 - difference) < 15.0,
+                "ANSI {} is {} but bright ANSI {} is {}",
+                index,
+                slots[index],
+                index + 8,
+                slots[index + 8]
+            );
+        }
+    }
+
+    #[test]
+    fn test_nearest_xterm_index() {
+        assert_eq!(nearest_xterm_index(Srgb::new(250, 5, 5)), 196);
+        assert_eq!(nearest_xterm_index(Srgb::new(127, 127, 127)), 244);
+        assert_eq!(nearest_xterm_index(Srgb::new(0, 95, 135)), 24);
+    }
+}
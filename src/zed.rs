@@ -0,0 +1,187 @@
+use std::fmt::Write;
+
+use tinted_builder::{Base16Scheme, SchemeVariant};
+
+use crate::{
+    export::{json_string, slot_hex},
+    xterm::ansi_slots,
+    Error,
+};
+
+/// The user interface colors and the slots they use
+const STYLE: [(&str, &str); 39] = [
+    ("background", "base00"),
+    ("border", "base02"),
+    ("border.variant", "base01"),
+    ("border.focused", "base0D"),
+    ("surface.background", "base01"),
+    ("elevated_surface.background", "base01"),
+    ("element.background", "base01"),
+    ("element.hover", "base02"),
+    ("element.selected", "base02"),
+    ("ghost_element.hover", "base01"),
+    ("ghost_element.selected", "base02"),
+    ("text", "base05"),
+    ("text.muted", "base04"),
+    ("text.placeholder", "base03"),
+    ("text.accent", "base0D"),
+    ("icon", "base05"),
+    ("icon.muted", "base04"),
+    ("status_bar.background", "base01"),
+    ("title_bar.background", "base01"),
+    ("toolbar.background", "base00"),
+    ("tab_bar.background", "base01"),
+    ("tab.inactive_background", "base01"),
+    ("tab.active_background", "base00"),
+    ("panel.background", "base01"),
+    ("editor.background", "base00"),
+    ("editor.foreground", "base05"),
+    ("editor.gutter.background", "base00"),
+    ("editor.active_line.background", "base01"),
+    ("editor.line_number", "base03"),
+    ("editor.active_line_number", "base04"),
+    ("terminal.background", "base00"),
+    ("terminal.foreground", "base05"),
+    ("error", "base08"),
+    ("warning", "base0A"),
+    ("success", "base0B"),
+    ("info", "base0D"),
+    ("created", "base0B"),
+    ("deleted", "base08"),
+    ("modified", "base0E"),
+];
+/// The names of the terminal's ANSI colors, black to bright white
+const TERMINAL_COLORS: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright_black",
+    "bright_red",
+    "bright_green",
+    "bright_yellow",
+    "bright_blue",
+    "bright_magenta",
+    "bright_cyan",
+    "bright_white",
+];
+/// The syntax highlights and the slots they use, after the base16 styling
+/// guidelines
+const SYNTAX: [(&str, &str); 23] = [
+    ("comment", "base03"),
+    ("variable", "base08"),
+    ("property", "base08"),
+    ("tag", "base08"),
+    ("number", "base09"),
+    ("boolean", "base09"),
+    ("constant", "base09"),
+    ("attribute", "base09"),
+    ("link_uri", "base09"),
+    ("type", "base0A"),
+    ("emphasis.strong", "base0A"),
+    ("string", "base0B"),
+    ("string.escape", "base0C"),
+    ("string.regex", "base0C"),
+    ("function", "base0D"),
+    ("constructor", "base0D"),
+    ("title", "base0D"),
+    ("keyword", "base0E"),
+    ("emphasis", "base0E"),
+    ("embedded", "base0F"),
+    ("operator", "base05"),
+    ("punctuation", "base05"),
+    ("preproc", "base0F"),
+];
+
+/// Render a Zed theme family with a single theme: the user interface and
+/// terminal colors from the grays and the ANSI colors, and syntax
+/// highlights mapped from base08 to base0F
+///
+/// # Arguments
+/// * `scheme` - A reference to the generated Base16Scheme
+pub fn zed_theme(scheme: &Base16Scheme) -> Result<String, Error> {
+    let appearance = match scheme.variant {
+        SchemeVariant::Light => "light",
+        _ => "dark",
+    };
+    let mut theme = format!(
+        "{{\n  \"$schema\": \"https://zed.dev/schema/themes/v0.2.0.json\",\n  \"name\": {name},\n  \"author\": {},\n  \"themes\": [\n    {{\n      \"name\": {name},\n      \"appearance\": \"{}\",\n      \"style\": {{\n",
+        json_string(&scheme.author),
+        appearance,
+        name = json_string(&scheme.name),
+    );
+    let terminal = TERMINAL_COLORS
+        .iter()
+        .zip(ansi_slots(&scheme.system))
+        .map(|(name, slot)| (format!("terminal.ansi.{}", name), slot));
+
+    for (key, slot) in STYLE
+        .iter()
+        .map(|(key, slot)| (key.to_string(), *slot))
+        .chain(terminal)
+    {
+        let _ = writeln!(
+            theme,
+            "        \"{}\": \"{}\",",
+            key,
+            slot_hex(scheme, slot)?
+        );
+    }
+
+    let _ = write!(
+        theme,
+        "        \"players\": [\n          {{ \"cursor\": \"{}\", \"background\": \"{}\", \"selection\": \"{}\" }}\n        ],\n        \"syntax\": {{\n",
+        slot_hex(scheme, "base05")?,
+        slot_hex(scheme, "base0D")?,
+        slot_hex(scheme, "base02")?
+    );
+
+    for (index, (name, slot)) in SYNTAX.iter().enumerate() {
+        let font_style = if *name == "comment" {
+            ", \"font_style\": \"italic\""
+        } else {
+            ""
+        };
+        let separator = if index + 1 < SYNTAX.len() { "," } else { "" };
+        let _ = writeln!(
+            theme,
+            "          \"{}\": {{ \"color\": \"{}\"{} }}{}",
+            name,
+            slot_hex(scheme, slot)?,
+            font_style,
+            separator
+        );
+    }
+
+    theme.push_str("        }\n      }\n    }\n  ]\n}\n");
+
+    Ok(theme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::tests::test_scheme;
+    use tinted_builder::SchemeSystem;
+
+    #[test]
+    fn test_zed_theme() {
+        let theme = zed_theme(&test_scheme(SchemeSystem::Base24)).unwrap();
+
+        assert!(theme.contains("  \"name\": \"Test \\\"Scheme\\\"\",\n  \"author\": \"Author\",\n"));
+        assert!(theme.contains("      \"appearance\": \"dark\",\n"));
+        assert!(theme.contains("        \"editor.background\": \"#000000\",\n"));
+        // Base24 schemes have their own bright colors
+        assert!(theme.contains("        \"terminal.ansi.bright_red\": \"#ff4040\",\n"));
+        assert!(theme.contains(
+            "          \"comment\": { \"color\": \"#6d6d6d\", \"font_style\": \"italic\" },\n"
+        ));
+        assert!(theme.ends_with(
+            "          \"preproc\": { \"color\": \"#804000\" }\n        }\n      }\n    }\n  ]\n}\n"
+        ));
+    }
+}